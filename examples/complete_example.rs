@@ -19,7 +19,7 @@ use bevy::{
 use rand::seq::SliceRandom;
 use std::{collections::BTreeMap, fmt, str::FromStr};
 
-use yml_dialog::{Content, DialogNode};
+use yml_dialog::{ConditionContext, Content, Dialog};
 
 // dark purple #25131a = 39/255, 19/255, 26/255
 const CLEAR: bevy::render::color::Color = bevy::render::color::Color::rgb(0.153, 0.07, 0.102);
@@ -90,7 +90,7 @@ impl FromStr for WorldEvent {
 /// - `key`: interlocutor
 /// - `value`: (current state, BinaryTreeMap of the dialog)
 #[derive(Debug, Deref, DerefMut, Default, Resource)]
-struct DialogMap(BTreeMap<Entity, (usize, BTreeMap<usize, DialogNode>)>);
+struct DialogMap(BTreeMap<Entity, (usize, Dialog)>);
 
 /// Contains all the line of the current monolog
 ///
@@ -199,8 +199,8 @@ fn reset_system(
         match interaction {
             Interaction::Clicked => {
                 for (_key, (current_state, dialog)) in dialogs.iter_mut() {
-                    if let Some(lower_state) = dialog.first_entry() {
-                        *current_state = *lower_state.key()
+                    if let Some(entry) = dialog.entry() {
+                        *current_state = entry
                     }
                 }
                 active_world_events.clear();
@@ -219,8 +219,8 @@ fn reset_system(
 
     if keys.just_pressed(KeyCode::R) {
         for (_key, (current_state, dialog)) in dialogs.iter_mut() {
-            if let Some(lower_state) = dialog.first_entry() {
-                *current_state = *lower_state.key()
+            if let Some(entry) = dialog.entry() {
+                *current_state = entry
             }
         }
         active_world_events.clear();
@@ -357,13 +357,13 @@ fn change_dialog_state(
                             Content::Choices(choices) => {
                                 let mut at_least_one_is_verified = false;
                                 for choice in choices {
-                                    if choice.is_verified(
+                                    if choice.is_verified(&ConditionContext::new(
                                         None,
                                         active_world_events
                                             .iter()
                                             .map(|x| x.to_string())
                                             .collect::<Vec<String>>(),
-                                    ) {
+                                    )) {
                                         // transit if at least on verified
                                         at_least_one_is_verified = true;
                                         break;
@@ -491,13 +491,13 @@ fn update_dialog_panel(
                                         let mut verified_choices = Vec::<(usize, String)>::new();
 
                                         for choice in choices.iter() {
-                                            if choice.is_verified(
+                                            if choice.is_verified(&ConditionContext::new(
                                                 None,
                                                 active_world_events
                                                     .iter()
                                                     .map(|x| x.to_string())
                                                     .collect::<Vec<String>>(),
-                                            ) {
+                                            )) {
                                                 // info!(
                                                 //     "{} -> {}",
                                                 //     choice.text().to_owned(),
@@ -782,14 +782,10 @@ fn setup(mut commands: Commands, asset_server: Res<AssetServer>, mut dialogs: Re
         ))
         .id();
     // DOC: unwrap use
-    let old_frog_deserialized_map: BTreeMap<usize, DialogNode> =
-        serde_yaml::from_str(OLD_FROG_DIALOG).unwrap();
+    let old_frog_dialog: Dialog = serde_yaml::from_str(OLD_FROG_DIALOG).unwrap();
     dialogs.insert(
         old_frog_portrait,
-        (
-            *old_frog_deserialized_map.first_key_value().unwrap().0,
-            old_frog_deserialized_map,
-        ),
+        (old_frog_dialog.entry().unwrap(), old_frog_dialog),
     );
 
     let frog_portrait = commands
@@ -812,15 +808,8 @@ fn setup(mut commands: Commands, asset_server: Res<AssetServer>, mut dialogs: Re
         ))
         .id();
     // DOC: unwrap use
-    let frog_deserialized_map: BTreeMap<usize, DialogNode> =
-        serde_yaml::from_str(FROG_DIALOG).unwrap();
-    dialogs.insert(
-        frog_portrait,
-        (
-            *frog_deserialized_map.first_key_value().unwrap().0,
-            frog_deserialized_map,
-        ),
-    );
+    let frog_dialog: Dialog = serde_yaml::from_str(FROG_DIALOG).unwrap();
+    dialogs.insert(frog_portrait, (frog_dialog.entry().unwrap(), frog_dialog));
 
     let warrior_frog_portrait = commands
         .spawn((
@@ -842,14 +831,10 @@ fn setup(mut commands: Commands, asset_server: Res<AssetServer>, mut dialogs: Re
         ))
         .id();
     // DOC: unwrap use
-    let warrior_frog_deserialized_map: BTreeMap<usize, DialogNode> =
-        serde_yaml::from_str(WARRIOR_DIALOG).unwrap();
+    let warrior_frog_dialog: Dialog = serde_yaml::from_str(WARRIOR_DIALOG).unwrap();
     dialogs.insert(
         warrior_frog_portrait,
-        (
-            *warrior_frog_deserialized_map.first_key_value().unwrap().0,
-            warrior_frog_deserialized_map,
-        ),
+        (warrior_frog_dialog.entry().unwrap(), warrior_frog_dialog),
     );
 
     /* -------------------------------------------------------------------------- */
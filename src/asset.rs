@@ -0,0 +1,151 @@
+//! Loads `.dialog.yml` files as [`Dialog`] assets, behind the `bevy_asset` feature.
+//!
+//! With the `filesystem_watcher` feature of Bevy and `AssetPlugin::watch_for_changes`,
+//! an edited file updates the running conversations: see [`DialogAssetPlugin`].
+
+use bevy::{
+    asset::{AssetLoader, BoxedFuture, Error, LoadContext, LoadedAsset},
+    prelude::*,
+    reflect::{TypeUuid, Uuid},
+};
+
+use crate::{plugin::StepWriters, Dialog, DialogState, DialogSystems};
+
+impl TypeUuid for Dialog {
+    const TYPE_UUID: Uuid = Uuid::from_u128(0x6f1c_52b4_8d0e_4a3f_9b71_e2a5_c40d_7e19);
+}
+
+/// Registers the [`Dialog`] asset, its [`DialogAssetLoader`] and the systems running it.
+///
+/// An entity holding a `Handle<Dialog>` gets a [`DialogState`] once the dialog is loaded.
+/// When the file changes, its running conversations go on from the same node and line
+/// if the new dialog still has them, and restart otherwise: a [`DialogReloadedEvent`] tells which.
+///
+/// The [`YmlDialogPlugin`](crate::YmlDialogPlugin) and Bevy's `AssetPlugin` must be added too.
+///
+/// ```rust,no_run
+/// use bevy::prelude::*;
+/// use yml_dialog::*;
+///
+/// fn spawn_frog(mut commands: Commands, asset_server: Res<AssetServer>) {
+///     let dialog: Handle<Dialog> = asset_server.load("dialogs/frog.dialog.yml");
+///     commands.spawn(dialog);
+/// }
+///
+/// App::new()
+///     .add_plugins(MinimalPlugins)
+///     .add_plugin(AssetPlugin {
+///         watch_for_changes: true,
+///         ..default()
+///     })
+///     .add_plugin(YmlDialogPlugin)
+///     .add_plugin(DialogAssetPlugin)
+///     .add_startup_system(spawn_frog)
+///     .run();
+/// ```
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug, Default)]
+pub struct DialogAssetPlugin;
+
+impl Plugin for DialogAssetPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_asset::<Dialog>()
+            .add_asset_loader(DialogAssetLoader)
+            .add_event::<DialogReloadedEvent>()
+            .add_systems((start_loaded_dialogs, reload_dialogs).before(DialogSystems));
+    }
+}
+
+/// Reads `.dialog.yml` and `.dialog.yaml` files as [`Dialog`]s.
+///
+/// A dialog with any error-level [`Diagnostic`](crate::Diagnostic) fails to load,
+/// the diagnostics being the error message. The warnings are logged.
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug, Default)]
+pub struct DialogAssetLoader;
+
+impl AssetLoader for DialogAssetLoader {
+    fn load<'a>(
+        &'a self,
+        bytes: &'a [u8],
+        load_context: &'a mut LoadContext,
+    ) -> BoxedFuture<'a, Result<(), Error>> {
+        Box::pin(async move {
+            let dialog: Dialog = Dialog::from_yaml_reader(bytes)?;
+            let (errors, warnings): (Vec<_>, Vec<_>) = dialog
+                .validate()
+                .into_iter()
+                .partition(|diagnostic| diagnostic.is_error());
+            let path = load_context.path().display();
+            for warning in warnings {
+                warn!("{path}: {warning}");
+            }
+            if !errors.is_empty() {
+                let errors: Vec<String> = errors.iter().map(ToString::to_string).collect();
+                return Err(Error::msg(format!(
+                    "invalid dialog {path}:\n{}",
+                    errors.join("\n")
+                )));
+            }
+            load_context.set_default_asset(LoadedAsset::new(dialog));
+            Ok(())
+        })
+    }
+
+    fn extensions(&self) -> &[&str] {
+        &["dialog.yml", "dialog.yaml"]
+    }
+}
+
+/// The dialog of `entity` was replaced by its edited file.
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
+pub struct DialogReloadedEvent {
+    /// The entity holding the [`DialogState`]
+    pub entity: Entity,
+    /// `true` if the conversation went back to the entry node,
+    /// its node or line being removed from the dialog
+    pub restarted: bool,
+}
+
+/// Inserts the [`DialogState`] of the entities whose dialog is loaded.
+fn start_loaded_dialogs(
+    mut commands: Commands,
+    handles: Query<(Entity, &Handle<Dialog>), Without<DialogState>>,
+    dialogs: Res<Assets<Dialog>>,
+) {
+    for (entity, handle) in &handles {
+        if let Some(dialog) = dialogs.get(handle) {
+            commands
+                .entity(entity)
+                .insert(DialogState::new(dialog.clone()));
+        }
+    }
+}
+
+fn reload_dialogs(
+    mut asset_events: EventReader<AssetEvent<Dialog>>,
+    mut states: Query<(Entity, &Handle<Dialog>, &mut DialogState)>,
+    dialogs: Res<Assets<Dialog>>,
+    mut reloaded_events: EventWriter<DialogReloadedEvent>,
+    mut writers: StepWriters,
+) {
+    for asset_event in asset_events.iter() {
+        let AssetEvent::Modified { handle: modified } = asset_event else {
+            continue;
+        };
+        let Some(dialog) = dialogs.get(modified) else {
+            continue;
+        };
+        for (entity, handle, mut state) in &mut states {
+            if handle != modified {
+                continue;
+            }
+            let step = state.runner_mut().reload(dialog.clone());
+            reloaded_events.send(DialogReloadedEvent {
+                entity,
+                restarted: step.is_some(),
+            });
+            if let Some(step) = step {
+                writers.report(entity, step);
+            }
+        }
+    }
+}
@@ -0,0 +1,409 @@
+//! The `yml_dialog` command-line tool, built with the `cli` feature.
+//!
+//! - `check` reads and validates dialog files,
+//! - `play` plays a dialog in the terminal,
+//! - `graph` draws it as Graphviz DOT or Mermaid,
+//! - `convert` translates it between the supported formats,
+//! - `stats` counts the nodes, lines, words and choices of each speaker.
+
+use std::{
+    collections::BTreeMap,
+    fs,
+    io::{self, BufRead, Write},
+    path::{Path, PathBuf},
+    process::ExitCode,
+};
+
+use clap::{Parser, Subcommand, ValueEnum};
+use yml_dialog::*;
+
+#[derive(Parser)]
+#[command(version, about = "Checks, plays, draws and converts dialog files")]
+struct Cli {
+    /// The format of the dialog files, guessed from their extension by default
+    #[arg(long, global = true, value_enum)]
+    from: Option<Format>,
+    #[command(subcommand)]
+    command: Command,
+}
+
+#[derive(Subcommand)]
+enum Command {
+    /// Reads and validates dialog files, listing their errors and warnings
+    Check {
+        /// The dialog files
+        #[arg(required = true)]
+        files: Vec<PathBuf>,
+    },
+    /// Plays a dialog in the terminal, prompting for each choice
+    Play {
+        /// The dialog file
+        file: PathBuf,
+        /// An event active from the start, can be repeated
+        #[arg(long = "event", value_name = "EVENT")]
+        events: Vec<String>,
+        /// The karma at the start
+        #[arg(long, allow_negative_numbers = true)]
+        karma: Option<i32>,
+    },
+    /// Draws the graph of a dialog
+    Graph {
+        /// The dialog file
+        file: PathBuf,
+        /// The language of the graph
+        #[arg(long, value_enum, default_value_t = GraphFormat::Dot)]
+        format: GraphFormat,
+    },
+    /// Converts a dialog file into another format
+    Convert {
+        /// The dialog file
+        input: PathBuf,
+        /// The converted file, or `-` for the standard output
+        output: PathBuf,
+        /// The format of the converted file, guessed from its extension by default
+        #[arg(long, value_enum)]
+        to: Option<Format>,
+    },
+    /// Counts the nodes, lines, words and choices of each speaker
+    Stats {
+        /// The dialog files
+        #[arg(required = true)]
+        files: Vec<PathBuf>,
+    },
+}
+
+/// A format a dialog is read from or written to.
+#[derive(Clone, Copy, PartialEq, Eq, Debug, ValueEnum)]
+enum Format {
+    #[value(alias = "yml")]
+    Yaml,
+    Json,
+    Ron,
+    Toml,
+    /// Read only
+    Ink,
+    Yarn,
+    #[value(alias = "tw")]
+    Twee,
+}
+
+impl Format {
+    /// Guesses the format of a file from its extension.
+    fn of(path: &Path) -> Option<Self> {
+        let extension = path.extension()?.to_str()?.to_lowercase();
+        Format::from_str(&extension, true).ok()
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug, ValueEnum)]
+enum GraphFormat {
+    Dot,
+    Mermaid,
+}
+
+fn main() -> ExitCode {
+    let cli = Cli::parse();
+    let result = match cli.command {
+        Command::Check { files } => check(&files, cli.from),
+        Command::Play {
+            file,
+            events,
+            karma,
+        } => play(&file, cli.from, ConditionContext::new(karma, events)),
+        Command::Graph { file, format } => graph(&file, cli.from, format),
+        Command::Convert { input, output, to } => convert(&input, cli.from, &output, to),
+        Command::Stats { files } => stats(&files, cli.from),
+    };
+    match result {
+        Ok(code) => code,
+        Err(error) => {
+            eprintln!("error: {error}");
+            ExitCode::FAILURE
+        }
+    }
+}
+
+/// Reads a dialog file in the `from` format, or the one of its extension.
+///
+/// Returns the issues of an import too, like what an Ink script left out.
+fn read(path: &Path, from: Option<Format>) -> Result<(Dialog, Vec<String>), String> {
+    let format = from
+        .or_else(|| Format::of(path))
+        .ok_or_else(|| format!("{}: unknown format, use --from", path.display()))?;
+    let text = fs::read_to_string(path).map_err(|error| format!("{}: {error}", path.display()))?;
+    let read = match format {
+        Format::Yaml => Dialog::from_yaml_str(&text),
+        Format::Json => Dialog::from_json_str(&text),
+        Format::Ron => Dialog::from_ron_str(&text),
+        Format::Toml => Dialog::from_toml_str(&text),
+        Format::Ink => return Ok(with_issues(import_ink(&text))),
+        Format::Yarn => return Ok(with_issues(import_yarn(&text))),
+        Format::Twee => return Ok(with_issues(import_twee(&text))),
+    };
+    read.map(|dialog| (dialog, vec![]))
+        .map_err(|error| format!("{}: {error}", path.display()))
+}
+
+fn with_issues((dialog, report): (Dialog, ImportReport)) -> (Dialog, Vec<String>) {
+    let issues = report.issues().iter().map(ToString::to_string).collect();
+    (dialog, issues)
+}
+
+/// Reads a dialog file, telling the issues of its import on the standard error.
+fn read_quietly(path: &Path, from: Option<Format>) -> Result<Dialog, String> {
+    let (dialog, issues) = read(path, from)?;
+    for issue in issues {
+        eprintln!("{}: warning: {issue}", path.display());
+    }
+    Ok(dialog)
+}
+
+fn check(files: &[PathBuf], from: Option<Format>) -> Result<ExitCode, String> {
+    let mut failed = false;
+    for file in files {
+        let (dialog, issues) = match read(file, from) {
+            Ok(read) => read,
+            Err(error) => {
+                println!("error: {error}");
+                failed = true;
+                continue;
+            }
+        };
+        let diagnostics = dialog.validate();
+        for issue in &issues {
+            println!("{}: warning: {issue}", file.display());
+        }
+        for diagnostic in &diagnostics {
+            println!("{}: {diagnostic}", file.display());
+        }
+        if issues.is_empty() && diagnostics.is_empty() {
+            println!("{}: ok", file.display());
+        }
+        failed |= diagnostics.iter().any(Diagnostic::is_error);
+    }
+    Ok(if failed {
+        ExitCode::FAILURE
+    } else {
+        ExitCode::SUCCESS
+    })
+}
+
+fn play(path: &Path, from: Option<Format>, context: ConditionContext) -> Result<ExitCode, String> {
+    let dialog = read_quietly(path, from)?;
+    playthrough(dialog, context, io::stdin().lock(), io::stdout().lock())
+        .map_err(|error| error.to_string())?;
+    Ok(ExitCode::SUCCESS)
+}
+
+/// Plays the dialog, writing its lines and reading the number of each choice,
+/// until its end or `q`.
+///
+/// The `trigger_event`s become active events, and the effects are applied on the `context`.
+fn playthrough(
+    dialog: Dialog,
+    mut context: ConditionContext,
+    input: impl BufRead,
+    mut output: impl Write,
+) -> io::Result<()> {
+    let mut answers = input.lines();
+    let mut runner = DialogRunner::new(dialog);
+    let mut step = runner.restart();
+    loop {
+        match step {
+            Step::Line => {}
+            Step::Node {
+                trigger_event,
+                effects,
+                ..
+            } => {
+                for event in trigger_event {
+                    writeln!(output, "[event {event}]")?;
+                    context.apply(&Effect::<String>::SetEvent(event));
+                }
+                for effect in &effects {
+                    if let Some(custom) = context.apply(effect) {
+                        writeln!(output, "[custom {custom}]")?;
+                    }
+                }
+            }
+            Step::End {
+                exit_state,
+                effects,
+            } => {
+                for effect in &effects {
+                    if let Some(custom) = context.apply(effect) {
+                        writeln!(output, "[custom {custom}]")?;
+                    }
+                }
+                writeln!(output, "-- end {exit_state} --")?;
+                break;
+            }
+        }
+
+        let speaker = runner.current_speaker().unwrap_or_default().to_string();
+        let Some(node) = runner.current_node() else {
+            break;
+        };
+        let next = if let Content::Monolog { .. } = node.content() {
+            if let Some(line) = runner.current_line() {
+                writeln!(output, "{speaker}: {line}")?;
+            }
+            runner.advance()
+        } else {
+            let choices: Vec<String> = runner
+                .available_choices(&context)
+                .iter()
+                .map(|choice| choice.text().clone())
+                .collect();
+            if choices.is_empty() {
+                writeln!(output, "-- no choice is available, the dialog is stuck --")?;
+                break;
+            }
+            writeln!(output, "{speaker}:")?;
+            for (number, choice) in choices.iter().enumerate() {
+                writeln!(output, "  {}. {choice}", number + 1)?;
+            }
+            let index = loop {
+                write!(output, "> ")?;
+                output.flush()?;
+                let Some(answer) = answers.next().transpose()? else {
+                    writeln!(output)?;
+                    break None;
+                };
+                match answer.trim().parse::<usize>() {
+                    Ok(number) if (1..=choices.len()).contains(&number) => break Some(number - 1),
+                    _ if answer.trim() == "q" => break None,
+                    _ => writeln!(
+                        output,
+                        "pick a number from 1 to {}, or q to quit",
+                        choices.len()
+                    )?,
+                }
+            };
+            let Some(index) = index else {
+                writeln!(output, "-- quit --")?;
+                break;
+            };
+            runner.choose(index, &context)
+        };
+        // The current node is a monolog or has this choice
+        step = next.expect("the runner can go on");
+    }
+
+    let mut events = context.active_events().clone();
+    events.sort();
+    writeln!(
+        output,
+        "karma: {}, events: {}",
+        context.karma().unwrap_or_default(),
+        events.join(", ")
+    )
+}
+
+fn graph(path: &Path, from: Option<Format>, format: GraphFormat) -> Result<ExitCode, String> {
+    let dialog = read_quietly(path, from)?;
+    match format {
+        GraphFormat::Dot => print!("{}", dialog.to_dot()),
+        GraphFormat::Mermaid => print!("{}", dialog.to_mermaid()),
+    }
+    Ok(ExitCode::SUCCESS)
+}
+
+fn convert(
+    input: &Path,
+    from: Option<Format>,
+    output: &Path,
+    to: Option<Format>,
+) -> Result<ExitCode, String> {
+    let to = to
+        .or_else(|| Format::of(output))
+        .ok_or_else(|| format!("{}: unknown format, use --to", output.display()))?;
+    let dialog = read_quietly(input, from)?;
+    let converted = match to {
+        Format::Yaml => dialog.to_yaml_string(),
+        Format::Json => dialog.to_json_string(),
+        Format::Ron => dialog.to_ron_string(),
+        Format::Toml => dialog.to_toml_string(),
+        Format::Ink => return Err("Ink scripts can be read, not written".to_string()),
+        Format::Yarn => Ok(dialog.to_yarn_string()),
+        Format::Twee => Ok(dialog.to_twee_string()),
+    }
+    .map_err(|error| error.to_string())?;
+    if output == Path::new("-") {
+        print!("{converted}");
+    } else {
+        fs::write(output, converted).map_err(|error| format!("{}: {error}", output.display()))?;
+    }
+    Ok(ExitCode::SUCCESS)
+}
+
+/// What a speaker says in a dialog.
+#[derive(Default)]
+struct SpeakerStats {
+    nodes: usize,
+    lines: usize,
+    words: usize,
+    choices: usize,
+}
+
+impl SpeakerStats {
+    fn add(&mut self, other: &SpeakerStats) {
+        self.nodes += other.nodes;
+        self.lines += other.lines;
+        self.words += other.words;
+        self.choices += other.choices;
+    }
+}
+
+fn stats(files: &[PathBuf], from: Option<Format>) -> Result<ExitCode, String> {
+    for file in files {
+        let dialog = read_quietly(file, from)?;
+        let mut speakers: BTreeMap<&str, SpeakerStats> = BTreeMap::new();
+        for node in dialog.nodes().values() {
+            let stats = speakers.entry(dialog.speaker(node)).or_default();
+            stats.nodes += 1;
+            match node.content() {
+                Content::Monolog { text, .. } => {
+                    stats.lines += text.len();
+                    stats.words += text.iter().map(|line| word_count(line)).sum::<usize>();
+                }
+                Content::Choices(choices) => {
+                    stats.choices += choices.len();
+                    stats.words += choices
+                        .iter()
+                        .map(|choice| word_count(choice.text()))
+                        .sum::<usize>();
+                }
+            }
+        }
+        let mut total = SpeakerStats::default();
+        for stats in speakers.values() {
+            total.add(stats);
+        }
+
+        println!(
+            "{}: {} nodes, {} speakers",
+            file.display(),
+            dialog.len(),
+            speakers.len()
+        );
+        let width = speakers
+            .keys()
+            .map(|speaker| speaker.chars().count())
+            .chain(["speaker".len()])
+            .max()
+            .unwrap_or_default();
+        println!("  {:width$}  nodes  lines  words  choices", "speaker");
+        for (speaker, stats) in speakers.iter().chain([(&"total", &total)]) {
+            println!(
+                "  {speaker:width$}  {:>5}  {:>5}  {:>5}  {:>7}",
+                stats.nodes, stats.lines, stats.words, stats.choices
+            );
+        }
+    }
+    Ok(ExitCode::SUCCESS)
+}
+
+fn word_count(text: &str) -> usize {
+    text.split_whitespace().count()
+}
@@ -0,0 +1,573 @@
+//! The state of the editor and its edits, apart from the terminal.
+
+use std::{fs, io, path::PathBuf};
+
+use ratatui::crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+use yml_dialog::*;
+
+/// The panel receiving the keys.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Focus {
+    Nodes,
+    Fields,
+}
+
+/// An editable field of the selected node.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Field {
+    Label,
+    Source,
+    /// A line of the monolog
+    Line(usize),
+    /// The `exit_state` of the monolog
+    Exit,
+    ChoiceText(usize),
+    ChoiceCondition(usize),
+    ChoiceExit(usize),
+    TriggerEvent,
+}
+
+/// The text of a field being edited.
+pub struct Input {
+    pub field: Field,
+    pub text: String,
+    /// In characters
+    pub cursor: usize,
+}
+
+/// An action waiting for a `y`.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Confirm {
+    DeleteNode(usize),
+    Quit,
+}
+
+pub struct App {
+    pub path: PathBuf,
+    pub dialog: Dialog,
+    /// The validation of the current `dialog`
+    pub diagnostics: Vec<Diagnostic>,
+    pub focus: Focus,
+    /// The index of the selected node, in key order
+    pub selected: usize,
+    /// The index of the selected field, within [`App::fields`]
+    pub field: usize,
+    pub input: Option<Input>,
+    pub confirm: Option<Confirm>,
+    pub status: String,
+    /// Edited since the last save
+    pub dirty: bool,
+    pub quit: bool,
+    /// The nodes left by a jump, to come back to
+    history: Vec<usize>,
+}
+
+impl App {
+    /// Opens the dialog at `path`, or a new one if there is no such file.
+    pub fn open(path: PathBuf) -> Result<Self, String> {
+        let (dialog, status) = match fs::read_to_string(&path) {
+            Ok(yaml) => (
+                Dialog::from_yaml_str(&yaml).map_err(|error| error.to_string())?,
+                format!("opened {}", path.display()),
+            ),
+            Err(error) if error.kind() == io::ErrorKind::NotFound => {
+                (Dialog::default(), format!("new file {}", path.display()))
+            }
+            Err(error) => return Err(error.to_string()),
+        };
+        Ok(App {
+            path,
+            diagnostics: dialog.validate(),
+            dialog,
+            focus: Focus::Nodes,
+            selected: 0,
+            field: 0,
+            input: None,
+            confirm: None,
+            status,
+            dirty: false,
+            quit: false,
+            history: vec![],
+        })
+    }
+
+    pub fn keys(&self) -> Vec<usize> {
+        self.dialog.nodes().keys().copied().collect()
+    }
+
+    pub fn selected_key(&self) -> Option<usize> {
+        self.keys().get(self.selected).copied()
+    }
+
+    pub fn selected_node(&self) -> Option<&DialogNode> {
+        self.dialog.get(&self.selected_key()?)
+    }
+
+    /// The fields of the selected node, in the order they are shown.
+    pub fn fields(&self) -> Vec<Field> {
+        let Some(node) = self.selected_node() else {
+            return vec![];
+        };
+        let mut fields = vec![Field::Label, Field::Source];
+        match node.content() {
+            Content::Monolog { text, .. } => {
+                fields.extend((0..text.len()).map(Field::Line));
+                fields.push(Field::Exit);
+            }
+            Content::Choices(choices) => {
+                for index in 0..choices.len() {
+                    fields.extend([
+                        Field::ChoiceText(index),
+                        Field::ChoiceCondition(index),
+                        Field::ChoiceExit(index),
+                    ]);
+                }
+            }
+        }
+        fields.push(Field::TriggerEvent);
+        fields
+    }
+
+    pub fn selected_field(&self) -> Option<Field> {
+        self.fields().get(self.field).copied()
+    }
+
+    /// The text of a field of the selected node, as it is edited.
+    pub fn value(&self, field: Field) -> String {
+        let (Some(key), Some(node)) = (self.selected_key(), self.selected_node()) else {
+            return String::new();
+        };
+        let choice = |index: usize| match node.content() {
+            Content::Choices(choices) => choices.get(index),
+            Content::Monolog { .. } => None,
+        };
+        match field {
+            Field::Label => self.dialog.label(&key).cloned().unwrap_or_default(),
+            Field::Source => node.source().clone(),
+            Field::Line(index) => match node.content() {
+                Content::Monolog { text, .. } => text.get(index).cloned().unwrap_or_default(),
+                Content::Choices(_) => String::new(),
+            },
+            Field::Exit => match node.content() {
+                Content::Monolog { exit_state, .. } => self.written_key(*exit_state),
+                Content::Choices(_) => String::new(),
+            },
+            Field::ChoiceText(index) => {
+                choice(index).map(Choice::text).cloned().unwrap_or_default()
+            }
+            Field::ChoiceCondition(index) => choice(index)
+                .and_then(|choice| choice.condition().as_ref())
+                .map(ToString::to_string)
+                .unwrap_or_default(),
+            Field::ChoiceExit(index) => choice(index)
+                .map(|choice| self.written_key(*choice.exit_state()))
+                .unwrap_or_default(),
+            Field::TriggerEvent => node.trigger_event().join(", "),
+        }
+    }
+
+    /// The label of a node, or its key.
+    pub fn written_key(&self, key: usize) -> String {
+        self.dialog
+            .label(&key)
+            .cloned()
+            .unwrap_or_else(|| key.to_string())
+    }
+
+    pub fn handle_key(&mut self, key: KeyEvent) {
+        if let Some(confirm) = self.confirm.take() {
+            if key.code == KeyCode::Char('y') {
+                match confirm {
+                    Confirm::DeleteNode(node) => self.delete_node(node),
+                    Confirm::Quit => self.quit = true,
+                }
+            } else {
+                self.status = "cancelled".to_string();
+            }
+            return;
+        }
+        if self.input.is_some() {
+            self.handle_input_key(key);
+            return;
+        }
+        if key.modifiers.contains(KeyModifiers::CONTROL) && key.code == KeyCode::Char('s') {
+            self.save();
+            return;
+        }
+        match (self.focus, key.code) {
+            (_, KeyCode::Char('s')) => self.save(),
+            (_, KeyCode::Char('q')) if self.dirty => {
+                self.confirm = Some(Confirm::Quit);
+                self.status = "unsaved changes, quit anyway? (y/n)".to_string();
+            }
+            (_, KeyCode::Char('q')) => self.quit = true,
+            (_, KeyCode::Char('n')) => self.add_node(false),
+            (_, KeyCode::Char('c')) => self.add_node(true),
+            (Focus::Nodes, KeyCode::Up | KeyCode::Char('k')) => {
+                self.select(self.selected.saturating_sub(1))
+            }
+            (Focus::Nodes, KeyCode::Down | KeyCode::Char('j')) => self.select(self.selected + 1),
+            (Focus::Nodes, KeyCode::Enter | KeyCode::Right | KeyCode::Tab)
+                if self.selected_node().is_some() =>
+            {
+                self.focus = Focus::Fields
+            }
+            (Focus::Nodes, KeyCode::Char('d')) => {
+                if let Some(node) = self.selected_key() {
+                    self.confirm = Some(Confirm::DeleteNode(node));
+                    self.status = format!("delete the node {}? (y/n)", self.written_key(node));
+                }
+            }
+            (Focus::Fields, KeyCode::Up | KeyCode::Char('k')) => {
+                self.field = self.field.saturating_sub(1)
+            }
+            (Focus::Fields, KeyCode::Down | KeyCode::Char('j')) => {
+                self.field = (self.field + 1).min(self.fields().len().saturating_sub(1))
+            }
+            (Focus::Fields, KeyCode::Enter | KeyCode::Char('e')) => self.edit(),
+            (Focus::Fields, KeyCode::Char('g')) => self.jump(),
+            (Focus::Fields, KeyCode::Char('b') | KeyCode::Backspace) => self.back(),
+            (Focus::Fields, KeyCode::Char('a')) => self.add_item(),
+            (Focus::Fields, KeyCode::Char('d')) => self.delete_item(),
+            (Focus::Fields, KeyCode::Esc | KeyCode::Left | KeyCode::Tab) => {
+                self.focus = Focus::Nodes
+            }
+            _ => {}
+        }
+    }
+
+    fn handle_input_key(&mut self, key: KeyEvent) {
+        let Some(input) = self.input.as_mut() else {
+            return;
+        };
+        let byte = |text: &str, cursor: usize| {
+            text.char_indices()
+                .nth(cursor)
+                .map_or(text.len(), |(byte, _)| byte)
+        };
+        match key.code {
+            KeyCode::Esc => {
+                self.input = None;
+                self.status = "cancelled".to_string();
+            }
+            KeyCode::Enter => {
+                let Input { field, text, .. } = self.input.take().expect("an input is edited");
+                match self.commit(field, text.trim()) {
+                    Ok(()) => self.changed("edited".to_string()),
+                    Err(error) => {
+                        self.status = error;
+                        self.input = Some(Input {
+                            field,
+                            cursor: text.chars().count(),
+                            text,
+                        });
+                    }
+                }
+            }
+            KeyCode::Char(character) => {
+                let at = byte(&input.text, input.cursor);
+                input.text.insert(at, character);
+                input.cursor += 1;
+            }
+            KeyCode::Backspace if input.cursor > 0 => {
+                input.cursor -= 1;
+                let at = byte(&input.text, input.cursor);
+                input.text.remove(at);
+            }
+            KeyCode::Delete if input.cursor < input.text.chars().count() => {
+                let at = byte(&input.text, input.cursor);
+                input.text.remove(at);
+            }
+            KeyCode::Left => input.cursor = input.cursor.saturating_sub(1),
+            KeyCode::Right => input.cursor = (input.cursor + 1).min(input.text.chars().count()),
+            KeyCode::Home => input.cursor = 0,
+            KeyCode::End => input.cursor = input.text.chars().count(),
+            _ => {}
+        }
+    }
+
+    fn select(&mut self, index: usize) {
+        self.selected = index.min(self.dialog.len().saturating_sub(1));
+        self.field = 0;
+    }
+
+    fn select_key(&mut self, key: usize) {
+        if let Some(index) = self.keys().iter().position(|node| *node == key) {
+            self.select(index);
+        }
+    }
+
+    fn edit(&mut self) {
+        if let Some(field) = self.selected_field() {
+            let text = self.value(field);
+            self.input = Some(Input {
+                field,
+                cursor: text.chars().count(),
+                text,
+            });
+            self.status = "Enter to confirm, Esc to cancel".to_string();
+        }
+    }
+
+    /// Writes the edited `text` into the `field` of the selected node.
+    fn commit(&mut self, field: Field, text: &str) -> Result<(), String> {
+        let key = self.selected_key().ok_or("no node is selected")?;
+        if field == Field::Label {
+            return self.relabel(key, text);
+        }
+        let exit_state = self.parse_key(text);
+        let node = self.dialog.get_mut(&key).ok_or("no node is selected")?;
+        match field {
+            Field::Source => *node.source_mut() = text.to_string(),
+            Field::TriggerEvent => {
+                *node.trigger_event_mut() = text
+                    .split(',')
+                    .map(str::trim)
+                    .filter(|event| !event.is_empty())
+                    .map(ToString::to_string)
+                    .collect()
+            }
+            _ => {}
+        }
+        match (field, node.content_mut()) {
+            (Field::Label | Field::Source | Field::TriggerEvent, _) => {}
+            (Field::Line(index), Content::Monolog { text: lines, .. }) => {
+                lines[index] = text.to_string()
+            }
+            (
+                Field::Exit,
+                Content::Monolog {
+                    exit_state: exit, ..
+                },
+            ) => *exit = exit_state?,
+            (Field::ChoiceText(index), Content::Choices(choices)) => {
+                *choices[index].text_mut() = text.to_string()
+            }
+            (Field::ChoiceCondition(index), Content::Choices(choices)) => {
+                let condition = choices[index].condition_mut();
+                // Unchanged, it keeps its `karma_threshold` and `thresholds` as they are written
+                if condition
+                    .as_ref()
+                    .map(ToString::to_string)
+                    .unwrap_or_default()
+                    != text
+                {
+                    *condition = if text.is_empty() {
+                        None
+                    } else {
+                        let expression: Expression = text
+                            .parse()
+                            .map_err(|error| format!("invalid condition: {error}"))?;
+                        Some(condition_of(expression))
+                    }
+                }
+            }
+            (Field::ChoiceExit(index), Content::Choices(choices)) => {
+                *choices[index].exit_state_mut() = exit_state?
+            }
+            _ => return Err("this field is gone".to_string()),
+        }
+        Ok(())
+    }
+
+    /// Reads a node key or label, as written in an `exit_state`.
+    fn parse_key(&self, text: &str) -> Result<usize, String> {
+        text.parse()
+            .ok()
+            .or_else(|| self.dialog.key_of(text))
+            .ok_or_else(|| format!("`{text}` is neither a key nor a label"))
+    }
+
+    fn relabel(&mut self, key: usize, label: &str) -> Result<(), String> {
+        if label.is_empty() {
+            self.dialog.remove_label(&key);
+            return Ok(());
+        }
+        // A number or a field of the dialog is refused by `set_label`
+        match self.dialog.key_of(label) {
+            Some(other) if other != key => {
+                Err(format!("`{label}` already labels the node {other}"))
+            }
+            _ => self
+                .dialog
+                .set_label(key, label.to_string())
+                .map(|_| ())
+                .map_err(|error| error.to_string()),
+        }
+    }
+
+    /// Adds a node with an empty line or choice, leading to a new end of the dialog.
+    fn add_node(&mut self, choices: bool) {
+        let key = self.dialog.free_key();
+        let content = if choices {
+            Content::Choices(vec![Choice::new(String::new(), None, key + 1)])
+        } else {
+            Content::Monolog {
+                text: vec![String::new()],
+                exit_state: key + 1,
+            }
+        };
+        self.dialog
+            .insert(key, DialogNode::new(String::new(), content, vec![]));
+        self.changed(format!("added the node {key}"));
+        self.select_key(key);
+        self.focus = Focus::Fields;
+    }
+
+    fn delete_node(&mut self, key: usize) {
+        self.dialog.remove(&key);
+        self.history.retain(|node| *node != key);
+        self.select(self.selected);
+        self.focus = Focus::Nodes;
+        self.changed(format!("deleted the node {key}"));
+    }
+
+    /// Adds a line after the selected one, or a choice after the selected one.
+    fn add_item(&mut self) {
+        let Some(key) = self.selected_key() else {
+            return;
+        };
+        let field = self.selected_field();
+        let end = self.dialog.free_key();
+        let Some(node) = self.dialog.get_mut(&key) else {
+            return;
+        };
+        let added = match node.content_mut() {
+            Content::Monolog { text, .. } => {
+                let index = match field {
+                    Some(Field::Line(index)) => index + 1,
+                    _ => text.len(),
+                };
+                text.insert(index, String::new());
+                Field::Line(index)
+            }
+            Content::Choices(choices) => {
+                let index = match field {
+                    Some(
+                        Field::ChoiceText(index)
+                        | Field::ChoiceCondition(index)
+                        | Field::ChoiceExit(index),
+                    ) => index + 1,
+                    _ => choices.len(),
+                };
+                choices.insert(index, Choice::new(String::new(), None, end));
+                Field::ChoiceText(index)
+            }
+        };
+        self.changed("added".to_string());
+        self.field = self
+            .fields()
+            .iter()
+            .position(|field| *field == added)
+            .unwrap_or_default();
+        self.edit();
+    }
+
+    fn delete_item(&mut self) {
+        let (Some(key), Some(field)) = (self.selected_key(), self.selected_field()) else {
+            return;
+        };
+        let Some(node) = self.dialog.get_mut(&key) else {
+            return;
+        };
+        match (field, node.content_mut()) {
+            (Field::Line(index), Content::Monolog { text, .. }) => {
+                text.remove(index);
+            }
+            (
+                Field::ChoiceText(index) | Field::ChoiceCondition(index) | Field::ChoiceExit(index),
+                Content::Choices(choices),
+            ) => {
+                choices.remove(index);
+            }
+            _ => {
+                self.status = "only a line or a choice can be deleted".to_string();
+                return;
+            }
+        }
+        self.changed("deleted".to_string());
+        self.field = self.field.min(self.fields().len().saturating_sub(1));
+    }
+
+    /// Selects the node of the `exit_state` under the cursor.
+    fn jump(&mut self) {
+        let target = match self.selected_field() {
+            Some(field @ (Field::Exit | Field::ChoiceExit(_))) => {
+                self.parse_key(&self.value(field))
+            }
+            _ => Err("select an exit_state to jump to its node".to_string()),
+        };
+        match target {
+            Ok(target) if self.dialog.contains(&target) => {
+                self.history.extend(self.selected_key());
+                self.select_key(target);
+                self.status = format!("jumped to {}, b to come back", self.written_key(target));
+            }
+            Ok(target) => self.status = format!("{target} is an end of the dialog"),
+            Err(error) => self.status = error,
+        }
+    }
+
+    fn back(&mut self) {
+        match self.history.pop() {
+            Some(key) => {
+                self.select_key(key);
+                self.status = format!("back to {}", self.written_key(key));
+            }
+            None => self.status = "no jump to come back from".to_string(),
+        }
+    }
+
+    fn changed(&mut self, status: String) {
+        self.dirty = true;
+        self.diagnostics = self.dialog.validate();
+        self.status = status;
+    }
+
+    /// Writes the dialog as YAML, in the canonical format.
+    fn save(&mut self) {
+        let written = self
+            .dialog
+            .to_yaml_string()
+            .map_err(|error| error.to_string())
+            .and_then(|yaml| fs::write(&self.path, yaml).map_err(|error| error.to_string()));
+        self.status = match written {
+            Ok(()) => {
+                self.dirty = false;
+                format!("saved {}", self.path.display())
+            }
+            Err(error) => format!("could not save: {error}"),
+        };
+    }
+}
+
+/// Makes a condition of the `expression`, its `has(..)`s joined by `&&` going back to `events`.
+fn condition_of(expression: Expression) -> Condition {
+    let mut conjuncts = Vec::new();
+    let mut pending = vec![expression];
+    while let Some(expression) = pending.pop() {
+        match expression {
+            Expression::And(left, right) => pending.extend([*right, *left]),
+            expression => conjuncts.push(expression),
+        }
+    }
+
+    let mut events = Vec::new();
+    let mut rest = None;
+    for conjunct in conjuncts {
+        match conjunct {
+            Expression::Has(event) => events.push(event),
+            conjunct => {
+                rest = Some(match rest {
+                    Some(left) => Expression::And(Box::new(left), Box::new(conjunct)),
+                    None => conjunct,
+                })
+            }
+        }
+    }
+    let condition = Condition::new(None, events);
+    match rest {
+        Some(expression) => condition.with_expression(expression),
+        None => condition,
+    }
+}
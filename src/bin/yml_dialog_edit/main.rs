@@ -0,0 +1,54 @@
+//! `yml_dialog_edit`, a terminal editor of YAML dialog files, built with the `editor` feature.
+//!
+//! It lists the nodes, edits their `source`, lines, choices, conditions and `trigger_event`s,
+//! jumps along the `exit_state`s, and shows the validation of the dialog as it is edited.
+//! It saves the dialog in the canonical YAML format.
+
+mod app;
+mod ui;
+
+use std::{env, io, path::PathBuf, process::ExitCode};
+
+use ratatui::{
+    crossterm::event::{self, Event, KeyEventKind},
+    DefaultTerminal,
+};
+
+use app::App;
+
+fn main() -> ExitCode {
+    let Some(path) = env::args_os().nth(1) else {
+        eprintln!("usage: yml_dialog_edit <FILE>");
+        return ExitCode::FAILURE;
+    };
+    let mut app = match App::open(PathBuf::from(path)) {
+        Ok(app) => app,
+        Err(error) => {
+            eprintln!("error: {error}");
+            return ExitCode::FAILURE;
+        }
+    };
+
+    let mut terminal = ratatui::init();
+    let result = run(&mut terminal, &mut app);
+    ratatui::restore();
+    match result {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(error) => {
+            eprintln!("error: {error}");
+            ExitCode::FAILURE
+        }
+    }
+}
+
+fn run(terminal: &mut DefaultTerminal, app: &mut App) -> io::Result<()> {
+    while !app.quit {
+        terminal.draw(|frame| ui::draw(frame, app))?;
+        if let Event::Key(key) = event::read()? {
+            if key.kind == KeyEventKind::Press {
+                app.handle_key(key);
+            }
+        }
+    }
+    Ok(())
+}
@@ -0,0 +1,223 @@
+//! Draws the editor: the nodes, the fields of the selected one, the validation and the status.
+
+use ratatui::{
+    layout::{Constraint, Layout, Position, Rect},
+    style::{Color, Modifier, Style, Stylize},
+    text::{Line, Span},
+    widgets::{Block, List, ListItem, ListState, Paragraph},
+    Frame,
+};
+use yml_dialog::*;
+
+use crate::app::{App, Field, Focus};
+
+/// The width of the names of the fields.
+const NAME_WIDTH: usize = 15;
+
+pub fn draw(frame: &mut Frame, app: &App) {
+    let diagnostics = app.diagnostics.len().clamp(1, 6) as u16 + 2;
+    let [main, validation, status] = Layout::vertical([
+        Constraint::Min(5),
+        Constraint::Length(diagnostics),
+        Constraint::Length(1),
+    ])
+    .areas(frame.area());
+    let [nodes, fields] =
+        Layout::horizontal([Constraint::Percentage(35), Constraint::Percentage(65)]).areas(main);
+
+    draw_nodes(frame, app, nodes);
+    draw_fields(frame, app, fields);
+    draw_validation(frame, app, validation);
+    frame.render_widget(Paragraph::new(status_line(app)), status);
+}
+
+/// The style of the worst diagnostic of the node, if any.
+fn severity_style(app: &App, key: usize) -> Option<Style> {
+    let severities = app
+        .diagnostics
+        .iter()
+        .filter(|diagnostic| diagnostic.node() == Some(key))
+        .map(Diagnostic::severity);
+    if severities
+        .clone()
+        .any(|severity| severity == Severity::Error)
+    {
+        Some(Style::new().fg(Color::Red))
+    } else if severities.count() > 0 {
+        Some(Style::new().fg(Color::Yellow))
+    } else {
+        None
+    }
+}
+
+fn draw_nodes(frame: &mut Frame, app: &App, area: Rect) {
+    let entry = app.dialog.entry();
+    let items: Vec<ListItem> = app
+        .dialog
+        .iter()
+        .map(|(key, node)| {
+            let marker = match severity_style(app, *key) {
+                Some(style) => Span::styled("! ", style),
+                None if entry == Some(*key) => Span::raw("> "),
+                None => Span::raw("  "),
+            };
+            let summary = match node.content() {
+                Content::Monolog { text, .. } => text.first().cloned().unwrap_or_default(),
+                Content::Choices(choices) => format!("{} choice(s)", choices.len()),
+            };
+            ListItem::new(Line::from(vec![
+                marker,
+                Span::raw(app.written_key(*key)).bold(),
+                Span::raw(format!(" {}: ", app.dialog.speaker(node))),
+                Span::raw(summary).dim(),
+            ]))
+        })
+        .collect();
+    let title = format!(
+        " {}{} ",
+        app.path.display(),
+        if app.dirty { " [+]" } else { "" }
+    );
+    let mut state = ListState::default().with_selected(Some(app.selected));
+    frame.render_stateful_widget(
+        List::new(items)
+            .block(panel(title, app.focus == Focus::Nodes))
+            .highlight_style(Style::new().add_modifier(Modifier::REVERSED)),
+        area,
+        &mut state,
+    );
+}
+
+fn field_name(field: Field) -> String {
+    match field {
+        Field::Label => "label".to_string(),
+        Field::Source => "source".to_string(),
+        Field::Line(index) => format!("line {}", index + 1),
+        Field::Exit => "exit_state".to_string(),
+        Field::ChoiceText(index) => format!("choice {}", index + 1),
+        Field::ChoiceCondition(_) => "  condition".to_string(),
+        Field::ChoiceExit(_) => "  exit_state".to_string(),
+        Field::TriggerEvent => "trigger_event".to_string(),
+    }
+}
+
+fn draw_fields(frame: &mut Frame, app: &App, area: Rect) {
+    let Some(key) = app.selected_key() else {
+        frame.render_widget(
+            Paragraph::new("No node yet: n adds a monolog, c a set of choices")
+                .block(panel(" Node ".to_string(), false)),
+            area,
+        );
+        return;
+    };
+
+    let fields = app.fields();
+    let items: Vec<ListItem> = fields
+        .iter()
+        .map(|field| {
+            let value = match &app.input {
+                Some(input) if input.field == *field => input.text.clone(),
+                _ => app.value(*field),
+            };
+            let mut line = vec![Span::raw(format!("{:NAME_WIDTH$}", field_name(*field))).dim()];
+            line.push(Span::raw(value.clone()));
+            match field {
+                Field::Exit | Field::ChoiceExit(_) => {
+                    let target = value.parse().ok().or_else(|| app.dialog.key_of(&value));
+                    if let Some(target) = target.filter(|target| !app.dialog.contains(target)) {
+                        line.push(Span::raw(format!("  end {target}")).dim());
+                    }
+                }
+                Field::Source if value.is_empty() => {
+                    if let Some(speaker) = app.dialog.default_speaker() {
+                        line.push(Span::raw(format!("default: {speaker}")).dim());
+                    }
+                }
+                _ => {}
+            }
+            ListItem::new(Line::from(line))
+        })
+        .collect();
+
+    let references: Vec<String> = app
+        .dialog
+        .references_to(&key)
+        .into_iter()
+        .map(|from| app.written_key(from))
+        .collect();
+    let title = if references.is_empty() {
+        format!(" Node {key} ")
+    } else {
+        format!(" Node {key}, reached from {} ", references.join(", "))
+    };
+    let focused = app.focus == Focus::Fields;
+    let block = panel(title, focused);
+    let inner = block.inner(area);
+    let mut state = ListState::default().with_selected(focused.then_some(app.field));
+    frame.render_stateful_widget(
+        List::new(items)
+            .block(block)
+            .highlight_style(Style::new().add_modifier(Modifier::REVERSED)),
+        area,
+        &mut state,
+    );
+
+    if let Some(input) = &app.input {
+        let row = state.selected().unwrap_or_default() - state.offset();
+        let column = NAME_WIDTH + input.cursor;
+        frame.set_cursor_position(Position::new(
+            inner.x + column.min(u16::MAX as usize) as u16,
+            inner.y + row as u16,
+        ));
+    }
+}
+
+fn draw_validation(frame: &mut Frame, app: &App, area: Rect) {
+    let errors = app
+        .diagnostics
+        .iter()
+        .filter(|diagnostic| diagnostic.is_error())
+        .count();
+    let warnings = app.diagnostics.len() - errors;
+    let title = format!(" Validation: {errors} error(s), {warnings} warning(s) ");
+    let lines: Vec<Line> = if app.diagnostics.is_empty() {
+        vec![Line::from("ok").green()]
+    } else {
+        app.diagnostics
+            .iter()
+            .map(|diagnostic| {
+                let color = if diagnostic.is_error() {
+                    Color::Red
+                } else {
+                    Color::Yellow
+                };
+                Line::from(diagnostic.to_string()).fg(color)
+            })
+            .collect()
+    };
+    frame.render_widget(Paragraph::new(lines).block(panel(title, false)), area);
+}
+
+fn status_line(app: &App) -> Line<'static> {
+    let help = match (app.input.is_some(), app.focus) {
+        (true, _) => "Enter confirm  Esc cancel",
+        (false, Focus::Nodes) => "Enter edit  n/c new monolog/choices  d delete  s save  q quit",
+        (false, Focus::Fields) => {
+            "Enter edit  a/d add/delete line or choice  g jump  b back  Esc nodes  s save"
+        }
+    };
+    Line::from(vec![
+        Span::raw(app.status.clone()).bold(),
+        Span::raw("  "),
+        Span::raw(help).dim(),
+    ])
+}
+
+fn panel(title: String, focused: bool) -> Block<'static> {
+    let block = Block::bordered().title(title);
+    if focused {
+        block.border_style(Style::new().fg(Color::Cyan))
+    } else {
+        block
+    }
+}
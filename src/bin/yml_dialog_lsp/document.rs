@@ -0,0 +1,361 @@
+//! The analysis of an open dialog file: diagnostics, definitions, references,
+//! completions and hovers.
+
+use std::collections::BTreeSet;
+
+use lsp_types::{
+    CompletionItem, CompletionItemKind, Diagnostic, DiagnosticSeverity, Hover, HoverContents,
+    MarkupContent, MarkupKind, Position, Range,
+};
+use yml_dialog::{Content, Dialog, DialogError, DialogNode, Effect, Severity};
+
+use crate::outline::{Entry, Outline, Span};
+
+/// The `source` of the diagnostics.
+const SOURCE: &str = "yml_dialog";
+
+pub struct Document {
+    lines: Vec<String>,
+    outline: Outline,
+    /// The dialog as last read without error, for completions and hovers
+    dialog: Option<Dialog>,
+    error: Option<DialogError>,
+}
+
+impl Document {
+    /// Reads the `text` of the file, keeping the `previous` dialog if it does not parse.
+    pub fn new(text: &str, previous: Option<Dialog>) -> Self {
+        let (dialog, error) = match Dialog::from_yaml_str(text) {
+            Ok(dialog) => (Some(dialog), None),
+            Err(error) => (previous, Some(error)),
+        };
+        Document {
+            lines: text.lines().map(ToString::to_string).collect(),
+            outline: Outline::new(text),
+            dialog,
+            error,
+        }
+    }
+
+    /// Takes the dialog as last read without error.
+    pub fn into_dialog(self) -> Option<Dialog> {
+        self.dialog
+    }
+
+    /// Returns the error of reading the file, or the validation of the dialog.
+    pub fn diagnostics(&self) -> Vec<Diagnostic> {
+        if let Some(error) = &self.error {
+            let span = match (error.line(), error.column()) {
+                (Some(line), Some(column)) => {
+                    let line = line.saturating_sub(1);
+                    let end = self
+                        .lines
+                        .get(line)
+                        .map_or(0, |text| text.trim_end().chars().count());
+                    let start = column.saturating_sub(1).min(end);
+                    Span { line, start, end }
+                }
+                _ => error
+                    .node()
+                    .and_then(|node| self.outline.node(node))
+                    .map(|node| node.span)
+                    .unwrap_or_default(),
+            };
+            let message = if error.path().is_empty() {
+                error.message().clone()
+            } else {
+                format!("{}: {}", error.path(), error.message())
+            };
+            return vec![self.diagnostic(span, DiagnosticSeverity::ERROR, message)];
+        }
+
+        let Some(dialog) = &self.dialog else {
+            return vec![];
+        };
+        dialog
+            .validate()
+            .iter()
+            .map(|diagnostic| {
+                let span = match diagnostic.node() {
+                    None => self
+                        .outline
+                        .references
+                        .iter()
+                        .find(|reference| reference.node.is_none())
+                        .map(|reference| reference.span),
+                    Some(key) => self.node_index(dialog, key).and_then(|index| {
+                        let exit = diagnostic
+                            .path()
+                            .ends_with("exit_state")
+                            .then(|| exit_index(diagnostic.path()));
+                        match exit {
+                            Some(exit) => self
+                                .outline
+                                .exits_of(index)
+                                .nth(exit)
+                                .map(|reference| reference.span),
+                            None => Some(self.outline.nodes[index].span),
+                        }
+                    }),
+                };
+                let severity = match diagnostic.severity() {
+                    Severity::Error => DiagnosticSeverity::ERROR,
+                    Severity::Warning => DiagnosticSeverity::WARNING,
+                };
+                let message = if diagnostic.path().is_empty() {
+                    diagnostic.kind().to_string()
+                } else {
+                    format!("{}: {}", diagnostic.path(), diagnostic.kind())
+                };
+                self.diagnostic(span.unwrap_or_default(), severity, message)
+            })
+            .collect()
+    }
+
+    fn diagnostic(&self, span: Span, severity: DiagnosticSeverity, message: String) -> Diagnostic {
+        Diagnostic {
+            range: self.range(span),
+            severity: Some(severity),
+            source: Some(SOURCE.to_string()),
+            message,
+            ..Default::default()
+        }
+    }
+
+    /// Returns where the node pointed at is written.
+    pub fn definition(&self, position: Position) -> Option<Range> {
+        let key = self.symbol_at(position)?;
+        self.outline.node(key).map(|node| self.range(node.span))
+    }
+
+    /// Returns where the node pointed at is referenced, and where it is written if asked.
+    pub fn references(&self, position: Position, declaration: bool) -> Vec<Range> {
+        let Some(key) = self.symbol_at(position) else {
+            return vec![];
+        };
+        let definition = self
+            .outline
+            .node(key)
+            .filter(|_| declaration)
+            .map(|node| node.span);
+        definition
+            .into_iter()
+            .chain(
+                self.outline
+                    .references
+                    .iter()
+                    .filter(|reference| reference.target == key)
+                    .map(|reference| reference.span),
+            )
+            .map(|span| self.range(span))
+            .collect()
+    }
+
+    /// Tells the `source` and the first line of the node pointed at.
+    pub fn hover(&self, position: Position) -> Option<Hover> {
+        let key = self.symbol_at(position)?;
+        let dialog = self.dialog.as_ref()?;
+        let text = match key_of(dialog, key) {
+            Some(node_key) => match dialog.get(&node_key) {
+                Some(node) => format!(
+                    "**{key}**, node {node_key}\n\n{}: {}",
+                    dialog.speaker(node),
+                    first_line(node)
+                ),
+                None => format!("**{key}**: an end of the dialog"),
+            },
+            None => format!("**{key}**: no such node"),
+        };
+        Some(Hover {
+            contents: HoverContents::Markup(MarkupContent {
+                kind: MarkupKind::Markdown,
+                value: text,
+            }),
+            range: None,
+        })
+    }
+
+    /// Offers the nodes after an `exit_state:` or the `entry:`,
+    /// and the events within `has(..)`, a `trigger_event`, a condition's `events`
+    /// or a `set_event` and `clear_event` effect.
+    pub fn completion(&self, position: Position) -> Vec<CompletionItem> {
+        let Some(line) = self.lines.get(position.line as usize) else {
+            return vec![];
+        };
+        let column = self.column(position);
+        let prefix: String = line.chars().take(column).collect();
+        let entry = Entry::parse(&prefix);
+        let key = entry.as_ref().map(|entry| entry.key);
+
+        if matches!(key, Some("exit_state")) || key == Some("entry") && !prefix.starts_with(' ') {
+            return self
+                .outline
+                .nodes
+                .iter()
+                .map(|node| {
+                    let detail = self.dialog.as_ref().and_then(|dialog| {
+                        let node = dialog.get(&key_of(dialog, &node.key)?)?;
+                        Some(format!("{}: {}", dialog.speaker(node), first_line(node)))
+                    });
+                    CompletionItem {
+                        label: node.key.clone(),
+                        kind: Some(CompletionItemKind::REFERENCE),
+                        detail,
+                        ..Default::default()
+                    }
+                })
+                .collect();
+        }
+
+        let within_has = prefix
+            .rfind("has(")
+            .is_some_and(|has| prefix.rfind(')').is_none_or(|close| close < has));
+        let event_field =
+            |key: &str| ["trigger_event", "events", "set_event", "clear_event"].contains(&key);
+        let events = within_has
+            || key.is_some_and(event_field)
+            || prefix.trim_start().starts_with('-')
+                && self
+                    .parent_key(position.line as usize)
+                    .is_some_and(|parent| event_field(&parent));
+        if !events {
+            return vec![];
+        }
+        self.events()
+            .into_iter()
+            .map(|event| CompletionItem {
+                label: event,
+                kind: Some(CompletionItemKind::CONSTANT),
+                ..Default::default()
+            })
+            .collect()
+    }
+
+    /// Returns the key of the field holding the sequence item at `line`.
+    fn parent_key(&self, line: usize) -> Option<String> {
+        let indent = self.lines[line].len() - self.lines[line].trim_start().len();
+        self.lines[..line]
+            .iter()
+            .rev()
+            .filter_map(|text| Entry::parse(text))
+            .find(|entry| !entry.dash && entry.indent <= indent && entry.value.is_empty())
+            .map(|entry| entry.key.to_string())
+    }
+
+    /// Returns the events thrown, set, cleared or checked in the dialog.
+    fn events(&self) -> BTreeSet<String> {
+        let Some(dialog) = &self.dialog else {
+            return BTreeSet::new();
+        };
+        let mut events = BTreeSet::new();
+        let effect_events = |effects: &Vec<Effect>| -> Vec<String> {
+            effects
+                .iter()
+                .filter_map(|effect| match effect {
+                    Effect::SetEvent(event) | Effect::ClearEvent(event) => Some(event.clone()),
+                    _ => None,
+                })
+                .collect()
+        };
+        for node in dialog.nodes().values() {
+            events.extend(node.trigger_event().iter().cloned());
+            events.extend(effect_events(node.effects()));
+            if let Content::Choices(choices) = node.content() {
+                for choice in choices {
+                    events.extend(effect_events(choice.effects()));
+                    if let Some(condition) = choice.condition() {
+                        events.extend(condition.events().iter().cloned());
+                        if let Some(expression) = condition.expression() {
+                            events.extend(expression.events().into_iter().cloned());
+                        }
+                    }
+                }
+            }
+        }
+        events
+    }
+
+    /// Returns the key or label under the cursor, where a node is written or referenced.
+    fn symbol_at(&self, position: Position) -> Option<&str> {
+        let (line, column) = (position.line as usize, self.column(position));
+        let node = self
+            .outline
+            .nodes
+            .iter()
+            .find(|node| node.span.contains(line, column))
+            .map(|node| node.key.as_str());
+        node.or_else(|| {
+            self.outline
+                .references
+                .iter()
+                .find(|reference| reference.span.contains(line, column))
+                .map(|reference| reference.target.as_str())
+        })
+    }
+
+    /// Returns the index of the outline of the node `key`.
+    fn node_index(&self, dialog: &Dialog, key: usize) -> Option<usize> {
+        let written = dialog
+            .label(&key)
+            .cloned()
+            .unwrap_or_else(|| key.to_string());
+        self.outline
+            .nodes
+            .iter()
+            .position(|node| node.key == written)
+    }
+
+    /// Converts a position in UTF-16 code units, as sent by the editor, into characters.
+    fn column(&self, position: Position) -> usize {
+        let Some(line) = self.lines.get(position.line as usize) else {
+            return 0;
+        };
+        let mut units = 0;
+        line.chars()
+            .take_while(|character| {
+                units += character.len_utf16();
+                units <= position.character as usize
+            })
+            .count()
+    }
+
+    fn range(&self, span: Span) -> Range {
+        let units = |column: usize| {
+            self.lines.get(span.line).map_or(0, |line| {
+                line.chars()
+                    .take(column)
+                    .map(char::len_utf16)
+                    .sum::<usize>()
+            }) as u32
+        };
+        Range::new(
+            Position::new(span.line as u32, units(span.start)),
+            Position::new(span.line as u32, units(span.end)),
+        )
+    }
+}
+
+/// Returns the key of the node written `key`, a key or a label.
+fn key_of(dialog: &Dialog, key: &str) -> Option<usize> {
+    key.parse().ok().or_else(|| dialog.key_of(key))
+}
+
+/// Returns the index of the choice of `content[index].exit_state`, 0 for a monolog.
+fn exit_index(path: &str) -> usize {
+    path.strip_prefix("content[")
+        .and_then(|rest| rest.split(']').next())
+        .and_then(|index| index.parse().ok())
+        .unwrap_or_default()
+}
+
+/// Returns the first line of a monolog, or the texts of the choices.
+fn first_line(node: &DialogNode) -> String {
+    match node.content() {
+        Content::Monolog { text, .. } => text.first().cloned().unwrap_or_default(),
+        Content::Choices(choices) => choices
+            .iter()
+            .map(|choice| choice.text().as_str())
+            .collect::<Vec<_>>()
+            .join(" / "),
+    }
+}
@@ -0,0 +1,219 @@
+//! `yml_dialog_lsp`, a language server for YAML dialog files, built with the `lsp` feature.
+//!
+//! It talks the Language Server Protocol over the standard input and output, and provides
+//! - the errors of reading the file, like malformed conditions, and the validation of the dialog,
+//! - the definition of the node of an `exit_state`, and the references to a node,
+//! - the completion of the nodes and of the events,
+//! - the `source` and the first line of a node on hover.
+
+mod document;
+mod outline;
+
+use std::{collections::HashMap, error::Error, process::ExitCode};
+
+use lsp_server::{Connection, ErrorCode, Message, Notification, Request, Response};
+use lsp_types::{
+    notification::{
+        DidChangeTextDocument, DidCloseTextDocument, DidOpenTextDocument,
+        Notification as NotificationTrait, PublishDiagnostics,
+    },
+    request::{Completion, GotoDefinition, HoverRequest, References, Request as RequestTrait},
+    CompletionOptions, CompletionResponse, GotoDefinitionResponse, HoverProviderCapability,
+    Location, OneOf, PublishDiagnosticsParams, ServerCapabilities, TextDocumentSyncCapability,
+    TextDocumentSyncKind, Url,
+};
+use serde::de::DeserializeOwned;
+
+use document::Document;
+
+type Result<T> = std::result::Result<T, Box<dyn Error + Send + Sync>>;
+
+fn main() -> ExitCode {
+    let (connection, io_threads) = Connection::stdio();
+    let result = serve(&connection);
+    drop(connection);
+    match result.and_then(|()| io_threads.join().map_err(Into::into)) {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(error) => {
+            eprintln!("error: {error}");
+            ExitCode::FAILURE
+        }
+    }
+}
+
+fn serve(connection: &Connection) -> Result<()> {
+    let capabilities = ServerCapabilities {
+        text_document_sync: Some(TextDocumentSyncCapability::Kind(TextDocumentSyncKind::FULL)),
+        definition_provider: Some(OneOf::Left(true)),
+        references_provider: Some(OneOf::Left(true)),
+        hover_provider: Some(HoverProviderCapability::Simple(true)),
+        completion_provider: Some(CompletionOptions {
+            trigger_characters: Some(vec![" ".to_string(), "(".to_string()]),
+            ..Default::default()
+        }),
+        ..Default::default()
+    };
+    connection.initialize(serde_json::to_value(capabilities)?)?;
+
+    let mut server = Server::default();
+    for message in &connection.receiver {
+        match message {
+            Message::Request(request) => {
+                if connection.handle_shutdown(&request)? {
+                    return Ok(());
+                }
+                let response = server.respond(request);
+                connection.sender.send(Message::Response(response))?;
+            }
+            Message::Notification(notification) => {
+                if let Some(diagnostics) = server.notify(notification) {
+                    connection
+                        .sender
+                        .send(Message::Notification(Notification::new(
+                            PublishDiagnostics::METHOD.to_string(),
+                            diagnostics,
+                        )))?;
+                }
+            }
+            Message::Response(_) => {}
+        }
+    }
+    Ok(())
+}
+
+#[derive(Default)]
+struct Server {
+    documents: HashMap<Url, Document>,
+}
+
+impl Server {
+    /// Updates the documents, returning their new diagnostics.
+    fn notify(&mut self, notification: Notification) -> Option<PublishDiagnosticsParams> {
+        let (uri, text, version) = match notification.method.as_str() {
+            DidOpenTextDocument::METHOD => {
+                let params: <DidOpenTextDocument as NotificationTrait>::Params =
+                    serde_json::from_value(notification.params).ok()?;
+                let document = params.text_document;
+                (document.uri, document.text, Some(document.version))
+            }
+            DidChangeTextDocument::METHOD => {
+                let params: <DidChangeTextDocument as NotificationTrait>::Params =
+                    serde_json::from_value(notification.params).ok()?;
+                // The whole text is sent on each change
+                let change = params.content_changes.into_iter().last()?;
+                let document = params.text_document;
+                (document.uri, change.text, Some(document.version))
+            }
+            DidCloseTextDocument::METHOD => {
+                let params: <DidCloseTextDocument as NotificationTrait>::Params =
+                    serde_json::from_value(notification.params).ok()?;
+                let uri = params.text_document.uri;
+                self.documents.remove(&uri);
+                return Some(PublishDiagnosticsParams::new(uri, vec![], None));
+            }
+            _ => return None,
+        };
+        let previous = self.documents.remove(&uri).and_then(Document::into_dialog);
+        let document = Document::new(&text, previous);
+        let diagnostics = document.diagnostics();
+        self.documents.insert(uri.clone(), document);
+        Some(PublishDiagnosticsParams::new(uri, diagnostics, version))
+    }
+
+    fn respond(&self, request: Request) -> Response {
+        let id = request.id.clone();
+        let result = match request.method.as_str() {
+            GotoDefinition::METHOD => self.handle::<GotoDefinition>(request, |document, params| {
+                let position = params.text_document_position_params;
+                document.definition(position.position).map(|range| {
+                    GotoDefinitionResponse::Scalar(Location::new(position.text_document.uri, range))
+                })
+            }),
+            References::METHOD => self.handle::<References>(request, |document, params| {
+                let position = params.text_document_position;
+                let uri = position.text_document.uri;
+                Some(
+                    document
+                        .references(position.position, params.context.include_declaration)
+                        .into_iter()
+                        .map(|range| Location::new(uri.clone(), range))
+                        .collect(),
+                )
+            }),
+            HoverRequest::METHOD => self.handle::<HoverRequest>(request, |document, params| {
+                document.hover(params.text_document_position_params.position)
+            }),
+            Completion::METHOD => self.handle::<Completion>(request, |document, params| {
+                Some(CompletionResponse::Array(
+                    document.completion(params.text_document_position.position),
+                ))
+            }),
+            method => Err(Response::new_err(
+                id.clone(),
+                ErrorCode::MethodNotFound as i32,
+                format!("unknown method `{method}`"),
+            )),
+        };
+        result.unwrap_or_else(|error| error)
+    }
+
+    /// Answers the request `R` on its document, with `None` for an unknown document.
+    fn handle<R>(
+        &self,
+        request: Request,
+        answer: impl FnOnce(&Document, R::Params) -> R::Result,
+    ) -> std::result::Result<Response, Response>
+    where
+        R: RequestTrait,
+        R::Params: DeserializeOwned + HasUri,
+    {
+        let params: R::Params = serde_json::from_value(request.params).map_err(|error| {
+            Response::new_err(
+                request.id.clone(),
+                ErrorCode::InvalidParams as i32,
+                error.to_string(),
+            )
+        })?;
+        let result = match self.documents.get(params.uri()) {
+            Some(document) => serde_json::to_value(answer(document, params)),
+            None => Ok(serde_json::Value::Null),
+        };
+        Ok(match result {
+            Ok(result) => Response::new_ok(request.id, result),
+            Err(error) => Response::new_err(
+                request.id,
+                ErrorCode::InternalError as i32,
+                error.to_string(),
+            ),
+        })
+    }
+}
+
+/// The parameters of a request on a document.
+trait HasUri {
+    fn uri(&self) -> &Url;
+}
+
+impl HasUri for lsp_types::GotoDefinitionParams {
+    fn uri(&self) -> &Url {
+        &self.text_document_position_params.text_document.uri
+    }
+}
+
+impl HasUri for lsp_types::ReferenceParams {
+    fn uri(&self) -> &Url {
+        &self.text_document_position.text_document.uri
+    }
+}
+
+impl HasUri for lsp_types::HoverParams {
+    fn uri(&self) -> &Url {
+        &self.text_document_position_params.text_document.uri
+    }
+}
+
+impl HasUri for lsp_types::CompletionParams {
+    fn uri(&self) -> &Url {
+        &self.text_document_position.text_document.uri
+    }
+}
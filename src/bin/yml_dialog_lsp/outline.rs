@@ -0,0 +1,208 @@
+//! Where the nodes and the `exit_state`s are written in a YAML dialog file.
+//!
+//! The file is scanned line by line, in the block style the crate writes,
+//! so that a file which does not parse yet still has an outline.
+
+/// The fields of a dialog which are not nodes, in the bare map of nodes.
+const METADATA_FIELDS: &[&str] = &["name", "default_speaker", "entry", "nodes", "version"];
+
+/// A part of a line, in characters.
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Default)]
+pub struct Span {
+    pub line: usize,
+    pub start: usize,
+    pub end: usize,
+}
+
+impl Span {
+    pub fn contains(&self, line: usize, column: usize) -> bool {
+        self.line == line && self.start <= column && column <= self.end
+    }
+}
+
+/// A node, written under its key or label.
+pub struct NodeOutline {
+    /// The key or label, as written
+    pub key: String,
+    pub span: Span,
+    /// The line following its last one
+    pub end_line: usize,
+}
+
+/// A key or label written as an `exit_state`, or as the `entry`.
+pub struct Reference {
+    /// The key or label, as written
+    pub target: String,
+    pub span: Span,
+    /// The index of the node it is written in, `None` for the `entry`
+    pub node: Option<usize>,
+}
+
+#[derive(Default)]
+pub struct Outline {
+    pub nodes: Vec<NodeOutline>,
+    pub references: Vec<Reference>,
+}
+
+/// A `key: value` line, the positions being in characters.
+pub struct Entry<'a> {
+    pub indent: usize,
+    /// Within a sequence, after a `- `
+    pub dash: bool,
+    pub key: &'a str,
+    pub key_start: usize,
+    /// Without quotes nor comment
+    pub value: &'a str,
+    pub value_start: usize,
+}
+
+impl<'a> Entry<'a> {
+    pub fn parse(line: &'a str) -> Option<Self> {
+        let columns = |byte: usize| line[..byte].chars().count();
+        let rest = line.trim_start_matches(' ');
+        let indent = line.len() - rest.len();
+        let (dash, rest) = match rest.strip_prefix("- ") {
+            Some(item) => (true, item.trim_start_matches(' ')),
+            None => (false, rest),
+        };
+        let mut key_start = line.len() - rest.len();
+        let (key, after_key) = match rest.chars().next()? {
+            quote @ ('"' | '\'') => {
+                let end = rest[1..].find(quote)? + 1;
+                key_start += 1;
+                (&rest[1..end], &rest[end + 1..])
+            }
+            '#' | '[' | '{' => return None,
+            _ => {
+                let colon = rest
+                    .find(": ")
+                    .or_else(|| rest.strip_suffix(':').map(str::len))?;
+                (&rest[..colon], &rest[colon..])
+            }
+        };
+        let after_colon = after_key.strip_prefix(':')?;
+        let mut value = after_colon.trim_start();
+        if let Some(comment) = value.find(" #") {
+            value = &value[..comment];
+        }
+        if value.starts_with('#') {
+            value = "";
+        }
+        value = value.trim_end();
+        let mut value_start = line.len() - after_colon.trim_start().len();
+        if value.len() >= 2
+            && (value.starts_with('"') && value.ends_with('"')
+                || value.starts_with('\'') && value.ends_with('\''))
+        {
+            value = &value[1..value.len() - 1];
+            value_start += 1;
+        }
+        Some(Entry {
+            indent: columns(indent),
+            dash,
+            key,
+            key_start: columns(key_start),
+            value,
+            value_start: columns(value_start),
+        })
+    }
+
+    pub fn key_span(&self, line: usize) -> Span {
+        Span {
+            line,
+            start: self.key_start,
+            end: self.key_start + self.key.chars().count(),
+        }
+    }
+
+    pub fn value_span(&self, line: usize) -> Span {
+        Span {
+            line,
+            start: self.value_start,
+            end: self.value_start + self.value.chars().count(),
+        }
+    }
+}
+
+impl Outline {
+    pub fn new(text: &str) -> Self {
+        let entries: Vec<Option<Entry>> = text.lines().map(Entry::parse).collect();
+        let top_level = |line: usize| {
+            entries[line]
+                .as_ref()
+                .filter(|entry| entry.indent == 0 && !entry.dash)
+        };
+
+        // The nodes are under `nodes:`, or are the bare map
+        let nodes_field = (0..entries.len()).find(|line| {
+            top_level(*line).is_some_and(|entry| entry.key == "nodes" && entry.value.is_empty())
+        });
+        let (start, end, indent) = match nodes_field {
+            Some(field) => {
+                let end = (field + 1..entries.len())
+                    .find(|line| top_level(*line).is_some())
+                    .unwrap_or(entries.len());
+                let indent = entries[field + 1..end]
+                    .iter()
+                    .flatten()
+                    .map(|entry| entry.indent)
+                    .next()
+                    .unwrap_or(2);
+                (field + 1, end, indent)
+            }
+            None => (0, entries.len(), 0),
+        };
+
+        let mut outline = Outline::default();
+        for (line, entry) in entries.iter().enumerate().take(end).skip(start) {
+            let Some(entry) = entry else {
+                continue;
+            };
+            if entry.indent == indent
+                && !entry.dash
+                && (nodes_field.is_some() || !METADATA_FIELDS.contains(&entry.key))
+            {
+                if let Some(previous) = outline.nodes.last_mut() {
+                    previous.end_line = line;
+                }
+                outline.nodes.push(NodeOutline {
+                    key: entry.key.to_string(),
+                    span: entry.key_span(line),
+                    end_line: end,
+                });
+            }
+        }
+
+        for (line, entry) in entries.iter().enumerate() {
+            let Some(entry) = entry else {
+                continue;
+            };
+            let node = outline
+                .nodes
+                .iter()
+                .position(|node| node.span.line < line && line < node.end_line);
+            let is_exit = entry.key == "exit_state" && node.is_some();
+            let is_entry = entry.key == "entry" && top_level(line).is_some();
+            if (is_exit || is_entry) && !entry.value.is_empty() {
+                outline.references.push(Reference {
+                    target: entry.value.to_string(),
+                    span: entry.value_span(line),
+                    node: node.filter(|_| is_exit),
+                });
+            }
+        }
+        outline
+    }
+
+    /// Returns the node written under `key`, a key or a label.
+    pub fn node(&self, key: &str) -> Option<&NodeOutline> {
+        self.nodes.iter().find(|node| node.key == key)
+    }
+
+    /// Returns the `exit_state`s written in the node `index`, in order.
+    pub fn exits_of(&self, index: usize) -> impl Iterator<Item = &Reference> {
+        self.references
+            .iter()
+            .filter(move |reference| reference.node == Some(index))
+    }
+}
@@ -0,0 +1,217 @@
+//! What happens to the game when a node is entered or a choice is picked.
+
+use serde::{
+    de::{self, MapAccess, Visitor},
+    ser::SerializeMap,
+    Deserialize, Deserializer, Serialize, Serializer,
+};
+use std::{fmt, marker::PhantomData};
+
+use crate::{FieldKey, Value};
+
+/// A change on the state of the game, declared on a [`DialogNode`](crate::DialogNode)
+/// or on a [`Choice`](crate::Choice) and applied through an [`EffectHandler`].
+///
+/// It is written as a single-entry map, any unknown effect being refused when the dialog is read.
+/// The payload of `custom` is any type, `String` by default:
+/// an enum of your own effects is then checked at load time too.
+///
+/// ```rust
+/// use yml_dialog::*;
+///
+/// let effects: Vec<Effect> = serde_yaml::from_str(
+///     "- set_event: FrogLove
+/// - clear_event: FrogHate
+/// - add_karma: -5
+/// - set_var:
+///     name: trust
+///     value: 2
+/// - set_var:
+///     name: last_place
+///     value: Frog Pond
+/// - custom: ShakeCamera\n",
+/// )
+/// .unwrap();
+///
+/// assert_eq!(effects[2], Effect::AddKarma(-5));
+///
+/// let error = serde_yaml::from_str::<Vec<Effect>>("- set_evnt: FrogLove\n")
+///     .unwrap_err()
+///     .to_string();
+/// assert!(error.contains("unknown variant `set_evnt`"));
+/// ```
+#[derive(Clone, Eq, PartialEq, Ord, PartialOrd, Hash, Debug)]
+pub enum Effect<F = String> {
+    /// Activates an event.
+    SetEvent(String),
+    /// Deactivates an event.
+    ClearEvent(String),
+    /// Adds to the karma, which can be negative.
+    AddKarma(i32),
+    /// Sets a variable.
+    SetVar {
+        /// The name of the variable
+        name: String,
+        /// Its new value
+        value: Value,
+    },
+    /// Any effect of the game.
+    Custom(F),
+}
+
+impl<F> Effect<F> {
+    /// Applies the effect through the given `handler`.
+    pub fn apply<H: EffectHandler<F> + ?Sized>(&self, handler: &mut H) {
+        match self {
+            Effect::SetEvent(event) => handler.set_event(event),
+            Effect::ClearEvent(event) => handler.clear_event(event),
+            Effect::AddKarma(amount) => handler.add_karma(*amount),
+            Effect::SetVar { name, value } => handler.set_var(name, value),
+            Effect::Custom(effect) => handler.custom(effect),
+        }
+    }
+}
+
+/// The fields of an `Effect::SetVar`.
+#[derive(Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct SetVar<N, V> {
+    name: N,
+    value: V,
+}
+
+impl<F: Serialize> Serialize for Effect<F> {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        // As the effects are declared, `add_karma: -5`, not as the `!AddKarma` tag YAML gives a variant
+        let mut state = serializer.serialize_map(Some(1))?;
+        match self {
+            Effect::SetEvent(event) => state.serialize_entry("set_event", event)?,
+            Effect::ClearEvent(event) => state.serialize_entry("clear_event", event)?,
+            Effect::AddKarma(amount) => state.serialize_entry("add_karma", amount)?,
+            Effect::SetVar { name, value } => {
+                state.serialize_entry("set_var", &SetVar { name, value })?
+            }
+            Effect::Custom(effect) => state.serialize_entry("custom", effect)?,
+        }
+        state.end()
+    }
+}
+
+/// The key of the map of an [`Effect`].
+#[derive(Deserialize)]
+#[serde(variant_identifier, rename_all = "snake_case")]
+enum EffectKind {
+    SetEvent,
+    ClearEvent,
+    AddKarma,
+    SetVar,
+    Custom,
+}
+
+impl<'de, F: Deserialize<'de>> Deserialize<'de> for Effect<F> {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        struct EffectVisitor<F>(PhantomData<F>);
+
+        impl<'de, F: Deserialize<'de>> Visitor<'de> for EffectVisitor<F> {
+            type Value = Effect<F>;
+
+            fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+                formatter.write_str("an effect like `set_event: FrogLove`")
+            }
+
+            fn visit_map<A>(self, mut map: A) -> Result<Effect<F>, A::Error>
+            where
+                A: MapAccess<'de>,
+            {
+                let effect = match map.next_key()?.map(|FieldKey(kind)| kind) {
+                    None => return Err(de::Error::custom("expected an effect, found none")),
+                    Some(EffectKind::SetEvent) => Effect::SetEvent(map.next_value()?),
+                    Some(EffectKind::ClearEvent) => Effect::ClearEvent(map.next_value()?),
+                    Some(EffectKind::AddKarma) => Effect::AddKarma(map.next_value()?),
+                    Some(EffectKind::SetVar) => {
+                        let SetVar { name, value } = map.next_value()?;
+                        Effect::SetVar { name, value }
+                    }
+                    Some(EffectKind::Custom) => Effect::Custom(map.next_value()?),
+                };
+                if map.next_key::<de::IgnoredAny>()?.is_some() {
+                    return Err(de::Error::custom(
+                        "an effect must be the only field of its map",
+                    ));
+                }
+                Ok(effect)
+            }
+        }
+
+        deserializer.deserialize_map(EffectVisitor(PhantomData))
+    }
+}
+
+/// Applies the [`Effect`]s on the state of the game.
+///
+/// ```rust
+/// use yml_dialog::*;
+///
+/// #[derive(Default)]
+/// struct Game {
+///     events: Vec<String>,
+///     karma: i32,
+///     shakes: usize,
+/// }
+///
+/// impl EffectHandler for Game {
+///     fn set_event(&mut self, event: &str) {
+///         self.events.push(event.to_string());
+///     }
+///
+///     fn clear_event(&mut self, event: &str) {
+///         self.events.retain(|active| active != event);
+///     }
+///
+///     fn add_karma(&mut self, amount: i32) {
+///         self.karma += amount;
+///     }
+///
+///     fn set_var(&mut self, _name: &str, _value: &Value) {}
+///
+///     fn custom(&mut self, effect: &String) {
+///         assert_eq!(effect, "ShakeCamera");
+///         self.shakes += 1;
+///     }
+/// }
+///
+/// let mut game = Game::default();
+/// for effect in [
+///     Effect::SetEvent("FrogLove".to_string()),
+///     Effect::AddKarma(3),
+///     Effect::Custom("ShakeCamera".to_string()),
+/// ] {
+///     effect.apply(&mut game);
+/// }
+///
+/// assert_eq!(game.events, vec!["FrogLove"]);
+/// assert_eq!(game.karma, 3);
+/// assert_eq!(game.shakes, 1);
+/// ```
+pub trait EffectHandler<F = String> {
+    /// Activates the `event`.
+    fn set_event(&mut self, event: &str);
+
+    /// Deactivates the `event`.
+    fn clear_event(&mut self, event: &str);
+
+    /// Adds the `amount` to the karma.
+    fn add_karma(&mut self, amount: i32);
+
+    /// Sets the variable `name` to `value`, see [`Variables::set`](crate::Variables::set).
+    fn set_var(&mut self, name: &str, value: &Value);
+
+    /// Applies a custom `effect`.
+    fn custom(&mut self, effect: &F);
+}
@@ -0,0 +1,142 @@
+//! The error of reading or writing a dialog file.
+
+use std::fmt;
+
+#[cfg(any(feature = "yaml", feature = "json", feature = "ron", feature = "toml"))]
+use crate::{
+    label::METADATA_FIELDS,
+    track::{display_path, Segment},
+};
+
+/// Why a [`Dialog`](crate::Dialog) file could not be read or written, and where.
+///
+#[cfg_attr(feature = "yaml", doc = "```rust")]
+#[cfg_attr(not(feature = "yaml"), doc = "```ignore")]
+/// use yml_dialog::*;
+///
+/// let error = Dialog::<Condition>::from_yaml_str(
+///     "1:
+///   source: Frog
+///   content:
+///     choices:
+///     - text: Hi
+///       exit_state: 2
+///     - text: Bye
+///       exit_state: true\n",
+/// )
+/// .unwrap_err();
+///
+/// assert_eq!(error.node(), Some("1"));
+/// assert_eq!(error.path(), "content.choices[1].exit_state");
+/// assert_eq!(error.line(), Some(8));
+/// assert_eq!(
+///     error.to_string(),
+///     "node 1, content.choices[1].exit_state: \
+///      invalid type: boolean `true`, expected a node key or label at line 8 column 19"
+/// );
+/// ```
+#[derive(Clone, Eq, PartialEq, Ord, PartialOrd, Hash, Debug, Default)]
+pub struct DialogError {
+    message: String,
+    line: Option<usize>,
+    column: Option<usize>,
+    node: Option<String>,
+    path: String,
+}
+
+impl DialogError {
+    /// Constructs a new DialogError, without any location.
+    pub fn new(message: impl Into<String>) -> Self {
+        DialogError {
+            message: message.into(),
+            ..Default::default()
+        }
+    }
+
+    /// Locates the error at the `path` of the dialog, splitting the node off.
+    #[cfg(any(feature = "yaml", feature = "json", feature = "ron", feature = "toml"))]
+    pub(crate) fn at_path(mut self, path: &[Segment]) -> Self {
+        let within_node = match path {
+            [Segment::Key(nodes), node, within_node @ ..] if nodes == "nodes" => {
+                Some((node, within_node))
+            }
+            [node @ Segment::Key(key), within_node @ ..]
+                if !METADATA_FIELDS.contains(&key.as_str()) =>
+            {
+                Some((node, within_node))
+            }
+            _ => None,
+        };
+        match within_node {
+            Some((node, within_node)) => {
+                self.node = Some(display_path(std::slice::from_ref(node)));
+                self.path = display_path(within_node);
+            }
+            None => self.path = display_path(path),
+        }
+        self
+    }
+
+    /// Locates the error at the `(line, column)` of the file, both starting at 1,
+    /// unless it is already located.
+    #[cfg(any(feature = "yaml", feature = "json", feature = "ron", feature = "toml"))]
+    pub(crate) fn at_position(mut self, position: Option<(usize, usize)>) -> Self {
+        if let (None, Some((line, column))) = (self.line, position) {
+            self.line = Some(line);
+            self.column = Some(column);
+        }
+        self
+    }
+
+    /// Returns the read-only `message` of the error, without its location.
+    pub fn message(&self) -> &String {
+        &self.message
+    }
+
+    /// Returns the `line` of the error in the file, starting at 1, if known.
+    pub fn line(&self) -> Option<usize> {
+        self.line
+    }
+
+    /// Returns the `column` of the error in the file, starting at 1, if known.
+    pub fn column(&self) -> Option<usize> {
+        self.column
+    }
+
+    /// Returns the key or label of the node being read, if the error comes from a node.
+    pub fn node(&self) -> Option<&str> {
+        self.node.as_deref()
+    }
+
+    /// Returns the path of the field being read, within the node if any,
+    /// like `content.choices[1].exit_state`.
+    ///
+    /// Empty if the error concerns the whole node or dialog.
+    pub fn path(&self) -> &String {
+        &self.path
+    }
+}
+
+impl fmt::Display for DialogError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        if let Some(node) = &self.node {
+            write!(f, "node {node}")?;
+            if !self.path.is_empty() {
+                write!(f, ", ")?;
+            }
+        }
+        if !self.path.is_empty() {
+            write!(f, "{}", self.path)?;
+        }
+        if self.node.is_some() || !self.path.is_empty() {
+            write!(f, ": ")?;
+        }
+        write!(f, "{}", self.message)?;
+        if let (Some(line), Some(column)) = (self.line, self.column) {
+            write!(f, " at line {line} column {column}")?;
+        }
+        Ok(())
+    }
+}
+
+impl std::error::Error for DialogError {}
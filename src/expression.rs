@@ -0,0 +1,622 @@
+//! A small language to write conditions,
+//! like `has(HasFriend) && !has(FirstKill) || karma >= 10`.
+//!
+//! - `has(Event)` or `has("Any Event")`: the event is active
+//! - `variable == 3`, `!=`, `<`, `<=`, `>`, `>=`: compares a variable, like `karma`,
+//!   to an integer, a float like `0.5` or `1e-5`, a boolean or a string like `"Frog Pond"`
+//! - `true`, `false`
+//! - `!`, `&&`, `||` and parentheses, `&&` binding tighter than `||`
+
+use serde::{de, Deserialize, Deserializer, Serialize, Serializer};
+use std::{cmp::Ordering, fmt, str::FromStr};
+
+use crate::{ConditionContext, DialogCondition, Value};
+
+/// What an [`Expression`] is evaluated against.
+pub trait ExpressionContext {
+    /// Returns `true` if the `event` is active.
+    fn has(&self, event: &str) -> bool;
+
+    /// Returns the value of the variable `name`, `None` if it is unknown.
+    fn variable(&self, name: &str) -> Option<Value>;
+}
+
+impl ExpressionContext for ConditionContext {
+    fn has(&self, event: &str) -> bool {
+        self.active_events().iter().any(|active| active == event)
+    }
+
+    /// `karma` is the one of the context if any, the other names are looked up in its `variables`.
+    fn variable(&self, name: &str) -> Option<Value> {
+        match (name, self.karma()) {
+            ("karma", Some(karma)) => Some(Value::Int(*karma)),
+            _ => self.variables().get(name).cloned(),
+        }
+    }
+}
+
+/// A comparison operator between a variable and a [`Value`].
+#[derive(Copy, Clone, Eq, PartialEq, Ord, PartialOrd, Hash, Debug)]
+pub enum Comparison {
+    /// `==`
+    Equal,
+    /// `!=`
+    NotEqual,
+    /// `<`
+    Less,
+    /// `<=`
+    LessOrEqual,
+    /// `>`
+    Greater,
+    /// `>=`
+    GreaterOrEqual,
+}
+
+impl Comparison {
+    /// Compares `left` to `right` with this operator, see [`Value::compare`].
+    ///
+    /// Values of different types are only different.
+    pub fn compare(&self, left: &Value, right: &Value) -> bool {
+        let ordering = left.compare(right);
+        match self {
+            Comparison::Equal => ordering == Some(Ordering::Equal),
+            Comparison::NotEqual => ordering != Some(Ordering::Equal),
+            Comparison::Less => ordering == Some(Ordering::Less),
+            Comparison::LessOrEqual => matches!(ordering, Some(Ordering::Less | Ordering::Equal)),
+            Comparison::Greater => ordering == Some(Ordering::Greater),
+            Comparison::GreaterOrEqual => {
+                matches!(ordering, Some(Ordering::Greater | Ordering::Equal))
+            }
+        }
+    }
+}
+
+impl fmt::Display for Comparison {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Comparison::Equal => write!(f, "=="),
+            Comparison::NotEqual => write!(f, "!="),
+            Comparison::Less => write!(f, "<"),
+            Comparison::LessOrEqual => write!(f, "<="),
+            Comparison::Greater => write!(f, ">"),
+            Comparison::GreaterOrEqual => write!(f, ">="),
+        }
+    }
+}
+
+/// The parsed form of a condition written in the expression language.
+///
+/// ```rust
+/// use yml_dialog::*;
+///
+/// let expression: Expression = "has(HasFriend) && !has(FirstKill) || karma >= 10"
+///     .parse()
+///     .unwrap();
+///
+/// let friend = ConditionContext::new(Some(0), vec!["HasFriend".to_string()]);
+/// let killer = ConditionContext::new(Some(0), vec!["HasFriend".to_string(), "FirstKill".to_string()]);
+/// let saint = ConditionContext::new(Some(12), vec!["FirstKill".to_string()]);
+///
+/// assert!(expression.evaluate(&friend));
+/// assert!(!expression.evaluate(&killer));
+/// assert!(expression.evaluate(&saint));
+///
+/// let error = "has(HasFriend) && karma >=".parse::<Expression>().unwrap_err();
+/// assert_eq!(error.to_string(), "expected a value, found the end at column 27");
+/// ```
+#[derive(Clone, Eq, PartialEq, Ord, PartialOrd, Hash, Debug)]
+pub enum Expression {
+    /// `true` or `false`
+    Bool(bool),
+    /// `has(Event)`
+    Has(String),
+    /// `variable >= 10`
+    Compare {
+        /// The name of the variable
+        variable: String,
+        /// The operator
+        comparison: Comparison,
+        /// The value compared to
+        value: Value,
+    },
+    /// `!expression`
+    Not(Box<Expression>),
+    /// `left && right`
+    And(Box<Expression>, Box<Expression>),
+    /// `left || right`
+    Or(Box<Expression>, Box<Expression>),
+}
+
+impl Expression {
+    /// Evaluates the expression within the given `context`.
+    ///
+    /// A comparison with an unknown variable is `false`.
+    pub fn evaluate(&self, context: &dyn ExpressionContext) -> bool {
+        match self {
+            Expression::Bool(value) => *value,
+            Expression::Has(event) => context.has(event),
+            Expression::Compare {
+                variable,
+                comparison,
+                value,
+            } => context
+                .variable(variable)
+                .is_some_and(|variable| comparison.compare(&variable, value)),
+            Expression::Not(expression) => !expression.evaluate(context),
+            Expression::And(left, right) => left.evaluate(context) && right.evaluate(context),
+            Expression::Or(left, right) => left.evaluate(context) || right.evaluate(context),
+        }
+    }
+
+    /// Returns the events read by `has(..)` in the expression.
+    pub fn events(&self) -> Vec<&String> {
+        match self {
+            Expression::Bool(_) | Expression::Compare { .. } => vec![],
+            Expression::Has(event) => vec![event],
+            Expression::Not(expression) => expression.events(),
+            Expression::And(left, right) | Expression::Or(left, right) => {
+                let mut events = left.events();
+                events.extend(right.events());
+                events
+            }
+        }
+    }
+
+    /// Writes the expression, between parentheses if it binds looser than `precedence`.
+    pub(crate) fn fmt_with_precedence(
+        &self,
+        f: &mut fmt::Formatter,
+        precedence: u8,
+    ) -> fmt::Result {
+        let own_precedence = match self {
+            Expression::Or(..) => 0,
+            Expression::And(..) => 1,
+            Expression::Compare { .. } => 2,
+            _ => 3,
+        };
+        if own_precedence < precedence {
+            write!(f, "(")?;
+        }
+        match self {
+            Expression::Bool(value) => write!(f, "{value}")?,
+            Expression::Has(event) => {
+                if is_identifier(event) {
+                    write!(f, "has({event})")?
+                } else {
+                    write!(f, "has({})", Value::String(event.clone()))?
+                }
+            }
+            Expression::Compare {
+                variable,
+                comparison,
+                value,
+            } => write!(f, "{variable} {comparison} {value}")?,
+            Expression::Not(expression) => {
+                write!(f, "!")?;
+                // `!` only applies to an atom: `!(karma > 2)`
+                expression.fmt_with_precedence(f, 3)?
+            }
+            Expression::And(left, right) => {
+                left.fmt_with_precedence(f, 1)?;
+                write!(f, " && ")?;
+                right.fmt_with_precedence(f, 2)?
+            }
+            Expression::Or(left, right) => {
+                left.fmt_with_precedence(f, 0)?;
+                write!(f, " || ")?;
+                right.fmt_with_precedence(f, 1)?
+            }
+        }
+        if own_precedence < precedence {
+            write!(f, ")")?;
+        }
+        Ok(())
+    }
+}
+
+impl fmt::Display for Expression {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        self.fmt_with_precedence(f, 0)
+    }
+}
+
+impl DialogCondition for Expression {
+    type Context = dyn ExpressionContext;
+
+    fn is_verified(&self, context: &dyn ExpressionContext) -> bool {
+        self.evaluate(context)
+    }
+}
+
+impl FromStr for Expression {
+    type Err = ParseError;
+
+    fn from_str(input: &str) -> Result<Self, Self::Err> {
+        let mut parser = Parser {
+            tokens: tokenize(input)?,
+            position: 0,
+            end: input.chars().count(),
+            depth: 0,
+        };
+        let expression = parser.or()?;
+        match parser.peek() {
+            None => Ok(expression),
+            Some(_) => Err(parser.expected("`&&`, `||` or the end")),
+        }
+    }
+}
+
+impl Serialize for Expression {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        serializer.collect_str(self)
+    }
+}
+
+impl<'de> Deserialize<'de> for Expression {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        let input = String::deserialize(deserializer)?;
+        input
+            .parse()
+            .map_err(|error| de::Error::custom(format!("invalid expression `{input}`: {error}")))
+    }
+}
+
+/// Why an [`Expression`] could not be parsed.
+#[derive(Clone, Eq, PartialEq, Ord, PartialOrd, Hash, Debug)]
+pub struct ParseError {
+    column: usize,
+    message: String,
+}
+
+impl ParseError {
+    /// Returns the column, starting at 1, where the error occurs.
+    pub fn column(&self) -> usize {
+        self.column
+    }
+
+    /// Returns what went wrong.
+    pub fn message(&self) -> &String {
+        &self.message
+    }
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{} at column {}", self.message, self.column)
+    }
+}
+
+impl std::error::Error for ParseError {}
+
+#[derive(Clone, PartialEq, Debug)]
+enum Token {
+    Identifier(String),
+    String(String),
+    Integer(i32),
+    Float(f64),
+    OpenParenthesis,
+    CloseParenthesis,
+    Not,
+    And,
+    Or,
+    Comparison(Comparison),
+}
+
+impl fmt::Display for Token {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Token::Identifier(identifier) => write!(f, "`{identifier}`"),
+            Token::String(string) => write!(f, "{string:?}"),
+            Token::Integer(integer) => write!(f, "`{integer}`"),
+            Token::Float(float) => write!(f, "`{float:?}`"),
+            Token::OpenParenthesis => write!(f, "`(`"),
+            Token::CloseParenthesis => write!(f, "`)`"),
+            Token::Not => write!(f, "`!`"),
+            Token::And => write!(f, "`&&`"),
+            Token::Or => write!(f, "`||`"),
+            Token::Comparison(comparison) => write!(f, "`{comparison}`"),
+        }
+    }
+}
+
+fn is_identifier(input: &str) -> bool {
+    let mut chars = input.chars();
+    chars
+        .next()
+        .is_some_and(|first| first.is_alphabetic() || first == '_')
+        && chars.all(|c| c.is_alphanumeric() || c == '_')
+}
+
+/// Splits the `input` into tokens, with their column.
+fn tokenize(input: &str) -> Result<Vec<(usize, Token)>, ParseError> {
+    let chars: Vec<char> = input.chars().collect();
+    let mut tokens = Vec::new();
+    let mut index = 0;
+
+    while index < chars.len() {
+        let start = index;
+        let error = |message: String| ParseError {
+            column: start + 1,
+            message,
+        };
+        let next = chars.get(index + 1).copied();
+        let token = match chars[index] {
+            c if c.is_whitespace() => {
+                index += 1;
+                continue;
+            }
+            '(' => Token::OpenParenthesis,
+            ')' => Token::CloseParenthesis,
+            '&' if next == Some('&') => Token::And,
+            '|' if next == Some('|') => Token::Or,
+            '=' if next == Some('=') => Token::Comparison(Comparison::Equal),
+            '!' if next == Some('=') => Token::Comparison(Comparison::NotEqual),
+            '<' if next == Some('=') => Token::Comparison(Comparison::LessOrEqual),
+            '>' if next == Some('=') => Token::Comparison(Comparison::GreaterOrEqual),
+            '!' => Token::Not,
+            '<' => Token::Comparison(Comparison::Less),
+            '>' => Token::Comparison(Comparison::Greater),
+            '"' => {
+                let mut string = String::new();
+                index += 1;
+                loop {
+                    match chars.get(index) {
+                        None => return Err(error("unterminated string".to_string())),
+                        Some('"') => break,
+                        Some('\\') if chars.get(index + 1).is_some() => {
+                            string.push(chars[index + 1]);
+                            index += 2;
+                        }
+                        Some(c) => {
+                            string.push(*c);
+                            index += 1;
+                        }
+                    }
+                }
+                Token::String(string)
+            }
+            c if c.is_ascii_digit() || (c == '-' && next.is_some_and(|n| n.is_ascii_digit())) => {
+                index += 1;
+                while chars.get(index).is_some_and(char::is_ascii_digit) {
+                    index += 1;
+                }
+                let mut is_float = chars.get(index) == Some(&'.')
+                    && chars.get(index + 1).is_some_and(char::is_ascii_digit);
+                if is_float {
+                    index += 1;
+                    while chars.get(index).is_some_and(char::is_ascii_digit) {
+                        index += 1;
+                    }
+                }
+                // An exponent, like the `1e-5` a tiny float is written as
+                let sign = match chars.get(index + 1) {
+                    Some('+' | '-') => 1,
+                    _ => 0,
+                };
+                if chars.get(index).is_some_and(|e| *e == 'e' || *e == 'E')
+                    && chars
+                        .get(index + 1 + sign)
+                        .is_some_and(char::is_ascii_digit)
+                {
+                    is_float = true;
+                    index += 1 + sign;
+                    while chars.get(index).is_some_and(char::is_ascii_digit) {
+                        index += 1;
+                    }
+                }
+                let literal: String = chars[start..index].iter().collect();
+                let token = if is_float {
+                    Token::Float(
+                        literal
+                            .parse()
+                            .map_err(|_| error(format!("invalid float `{literal}`")))?,
+                    )
+                } else {
+                    Token::Integer(
+                        literal
+                            .parse()
+                            .map_err(|_| error(format!("the integer `{literal}` is too large")))?,
+                    )
+                };
+                tokens.push((start + 1, token));
+                continue;
+            }
+            c if c.is_alphabetic() || c == '_' => {
+                while chars
+                    .get(index)
+                    .is_some_and(|c| c.is_alphanumeric() || *c == '_')
+                {
+                    index += 1;
+                }
+                tokens.push((
+                    start + 1,
+                    Token::Identifier(chars[start..index].iter().collect()),
+                ));
+                continue;
+            }
+            c => return Err(error(format!("unexpected character `{c}`"))),
+        };
+        index += match token {
+            Token::And
+            | Token::Or
+            | Token::Comparison(Comparison::Equal)
+            | Token::Comparison(Comparison::NotEqual)
+            | Token::Comparison(Comparison::LessOrEqual)
+            | Token::Comparison(Comparison::GreaterOrEqual) => 2,
+            _ => 1,
+        };
+        tokens.push((start + 1, token));
+    }
+
+    Ok(tokens)
+}
+
+/// How deep `!`s, parentheses, `&&`s and `||`s can be nested,
+/// so that no input overflows the stack.
+///
+/// Each `&&` or `||` of a chain counts as one level, an expression being a binary tree.
+const MAX_DEPTH: usize = 128;
+
+/// A recursive descent parser, one function per precedence level.
+struct Parser {
+    tokens: Vec<(usize, Token)>,
+    position: usize,
+    /// The number of characters of the input.
+    end: usize,
+    /// The number of `!`s, parentheses, `&&`s and `||`s the current token is nested in.
+    depth: usize,
+}
+
+impl Parser {
+    fn peek(&self) -> Option<&Token> {
+        self.tokens.get(self.position).map(|(_, token)| token)
+    }
+
+    fn bump(&mut self) -> Option<Token> {
+        let token = self
+            .tokens
+            .get(self.position)
+            .map(|(_, token)| token.clone());
+        self.position += 1;
+        token
+    }
+
+    /// Returns an error on the current token.
+    fn expected(&self, expected: &str) -> ParseError {
+        match self.tokens.get(self.position) {
+            None => ParseError {
+                column: self.end + 1,
+                message: format!("expected {expected}, found the end"),
+            },
+            Some((column, token)) => ParseError {
+                column: *column,
+                message: format!("expected {expected}, found {token}"),
+            },
+        }
+    }
+
+    fn expect(&mut self, token: Token, expected: &str) -> Result<(), ParseError> {
+        if self.peek() == Some(&token) {
+            self.position += 1;
+            Ok(())
+        } else {
+            Err(self.expected(expected))
+        }
+    }
+
+    /// Goes one level deeper for the operator just read.
+    fn deeper(&mut self) -> Result<(), ParseError> {
+        if self.depth == MAX_DEPTH {
+            return Err(ParseError {
+                column: self.tokens[self.position - 1].0,
+                message: format!("the expression is nested more than {MAX_DEPTH} levels deep"),
+            });
+        }
+        self.depth += 1;
+        Ok(())
+    }
+
+    /// Parses what follows the `!` or `(` just read, one level deeper.
+    fn nested(
+        &mut self,
+        parse: impl FnOnce(&mut Self) -> Result<Expression, ParseError>,
+    ) -> Result<Expression, ParseError> {
+        self.deeper()?;
+        let expression = parse(self);
+        self.depth -= 1;
+        expression
+    }
+
+    fn or(&mut self) -> Result<Expression, ParseError> {
+        let depth = self.depth;
+        let mut expression = self.and()?;
+        while self.peek() == Some(&Token::Or) {
+            self.position += 1;
+            self.deeper()?;
+            expression = Expression::Or(Box::new(expression), Box::new(self.and()?));
+        }
+        self.depth = depth;
+        Ok(expression)
+    }
+
+    fn and(&mut self) -> Result<Expression, ParseError> {
+        let depth = self.depth;
+        let mut expression = self.not()?;
+        while self.peek() == Some(&Token::And) {
+            self.position += 1;
+            self.deeper()?;
+            expression = Expression::And(Box::new(expression), Box::new(self.not()?));
+        }
+        self.depth = depth;
+        Ok(expression)
+    }
+
+    fn not(&mut self) -> Result<Expression, ParseError> {
+        if self.peek() == Some(&Token::Not) {
+            self.position += 1;
+            Ok(Expression::Not(Box::new(self.nested(Self::not)?)))
+        } else {
+            self.atom()
+        }
+    }
+
+    fn atom(&mut self) -> Result<Expression, ParseError> {
+        let error = self.expected("`has(..)`, a comparison, `true`, `false`, `!` or `(`");
+        match self.bump() {
+            Some(Token::OpenParenthesis) => {
+                let expression = self.nested(Self::or)?;
+                self.expect(Token::CloseParenthesis, "`)`")?;
+                Ok(expression)
+            }
+            Some(Token::Identifier(identifier)) => match identifier.as_str() {
+                "true" => Ok(Expression::Bool(true)),
+                "false" => Ok(Expression::Bool(false)),
+                "has" if self.peek() == Some(&Token::OpenParenthesis) => {
+                    self.position += 1;
+                    let event = match self.bump() {
+                        Some(Token::Identifier(event)) | Some(Token::String(event)) => event,
+                        _ => {
+                            self.position -= 1;
+                            return Err(self.expected("an event name"));
+                        }
+                    };
+                    self.expect(Token::CloseParenthesis, "`)`")?;
+                    Ok(Expression::Has(event))
+                }
+                _ => {
+                    let comparison = match self.bump() {
+                        Some(Token::Comparison(comparison)) => comparison,
+                        _ => {
+                            self.position -= 1;
+                            return Err(self
+                                .expected(&format!("a comparison operator after `{identifier}`")));
+                        }
+                    };
+                    let value = match self.bump() {
+                        Some(Token::Integer(value)) => Value::Int(value),
+                        Some(Token::Float(value)) => Value::Float(value),
+                        Some(Token::String(value)) => Value::String(value),
+                        Some(Token::Identifier(value)) if value == "true" => Value::Bool(true),
+                        Some(Token::Identifier(value)) if value == "false" => Value::Bool(false),
+                        _ => {
+                            self.position -= 1;
+                            return Err(self.expected("a value"));
+                        }
+                    };
+                    Ok(Expression::Compare {
+                        variable: identifier,
+                        comparison,
+                        value,
+                    })
+                }
+            },
+            _ => Err(error),
+        }
+    }
+}
@@ -0,0 +1,352 @@
+//! Translates the strings of a dialog with Project Fluent (`.ftl`) resources,
+//! with the `fluent` feature.
+//!
+//! A string id is a Fluent message, like `coins`,
+//! or one of its attributes after a dot, like `greet_player.line0` for the attribute `line0`
+//! of the message `greet_player`.
+//! The [`Variables`] of the game are the arguments of the messages,
+//! so a line can depend on a count or a gender.
+
+use std::{borrow::Cow, fmt};
+
+use fluent_bundle::{
+    concurrent::FluentBundle, FluentArgs, FluentError, FluentResource, FluentValue,
+};
+use fluent_langneg::{negotiate_languages, NegotiationStrategy};
+use unic_langid::LanguageIdentifier;
+
+use crate::{Dialog, Localizer, Value, Variables};
+
+/// An error found in the Fluent resources, see [`FluentLocalizer::add_resource`]
+/// and [`FluentLocalizer::check`].
+#[derive(Clone, Eq, PartialEq, Ord, PartialOrd, Hash, Debug)]
+pub struct FluentDiagnostic {
+    locale: String,
+    id: Option<String>,
+    line: Option<usize>,
+    message: String,
+}
+
+impl FluentDiagnostic {
+    /// Returns the locale of the resource, like `fr`.
+    pub fn locale(&self) -> &String {
+        &self.locale
+    }
+
+    /// Returns the string id concerned, if any.
+    pub fn id(&self) -> &Option<String> {
+        &self.id
+    }
+
+    /// Returns the 1-based line of the error in the `.ftl` resource, if known.
+    pub fn line(&self) -> &Option<usize> {
+        &self.line
+    }
+
+    /// Returns what is wrong.
+    pub fn message(&self) -> &String {
+        &self.message
+    }
+}
+
+impl fmt::Display for FluentDiagnostic {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}: ", self.locale)?;
+        if let Some(line) = self.line {
+            write!(f, "line {line}: ")?;
+        }
+        write!(f, "{}", self.message)
+    }
+}
+
+/// A [`Localizer`] formatting Project Fluent messages with the [`Variables`] of the game,
+/// through a chain of locales tried in order.
+///
+/// Each locale is given its `.ftl` resources by [`FluentLocalizer::add_resource`],
+/// the first added being the chain until [`FluentLocalizer::request_locales`] is called.
+///
+/// ```rust
+/// use yml_dialog::*;
+///
+/// let mut fluent = FluentLocalizer::new();
+/// fluent
+///     .add_resource(
+///         "en",
+///         "coins = You have { $coins ->
+///     [one] one coin
+///    *[other] { $coins } coins
+/// }\n",
+///     )
+///     .unwrap();
+///
+/// let variables = Variables::from_iter([("coins", 3)]);
+/// assert_eq!(
+///     fluent.localize_with("coins", &variables).as_deref(),
+///     Some("You have 3 coins")
+/// );
+/// let variables = Variables::from_iter([("coins", 1)]);
+/// assert_eq!(
+///     fluent.localize_with("coins", &variables).as_deref(),
+///     Some("You have one coin")
+/// );
+/// ```
+#[derive(Default)]
+pub struct FluentLocalizer {
+    bundles: Vec<FluentBundle<FluentResource>>,
+    /// The indices of the `bundles`, in the order they are tried
+    chain: Vec<usize>,
+}
+
+impl fmt::Debug for FluentLocalizer {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("FluentLocalizer")
+            .field("locales", &self.locales())
+            .finish_non_exhaustive()
+    }
+}
+
+/// Returns the 1-based line of the byte `offset` in the `source`.
+fn line_at(source: &str, offset: usize) -> usize {
+    source.get(..offset).unwrap_or(source).matches('\n').count() + 1
+}
+
+/// Converts the variables of the game to the arguments of a message.
+///
+/// Fluent has no booleans: a flag is the string `true` or `false`, to be selected on.
+fn arguments(variables: &Variables) -> FluentArgs<'_> {
+    let mut arguments = FluentArgs::new();
+    for (name, value) in variables {
+        let value = match value {
+            Value::Int(integer) => FluentValue::from(*integer),
+            Value::Float(float) => FluentValue::from(*float),
+            Value::Bool(flag) => FluentValue::from(flag.to_string()),
+            Value::String(string) => FluentValue::from(string.as_str()),
+        };
+        arguments.set(name.as_str(), value);
+    }
+    arguments
+}
+
+impl FluentLocalizer {
+    /// Constructs a new FluentLocalizer, without any locale.
+    pub fn new() -> Self {
+        FluentLocalizer::default()
+    }
+
+    /// Adds the Fluent resource `ftl` to the `locale`, like `fr` or `en-US`,
+    /// adding the locale at the end of the chain if it is new.
+    ///
+    /// The messages which can be read are added even if others cannot,
+    /// the errors being returned with their line.
+    /// A message already given to the locale is kept, and reported.
+    pub fn add_resource(&mut self, locale: &str, ftl: &str) -> Result<(), Vec<FluentDiagnostic>> {
+        let diagnostic = |line, message| FluentDiagnostic {
+            locale: locale.to_string(),
+            id: None,
+            line,
+            message,
+        };
+        let identifier: LanguageIdentifier = locale
+            .parse()
+            .map_err(|_| vec![diagnostic(None, format!("invalid locale `{locale}`"))])?;
+
+        let mut diagnostics = Vec::new();
+        let resource = match FluentResource::try_new(ftl.to_string()) {
+            Ok(resource) => resource,
+            Err((resource, errors)) => {
+                diagnostics.extend(errors.into_iter().map(|error| {
+                    diagnostic(Some(line_at(ftl, error.pos.start)), error.to_string())
+                }));
+                resource
+            }
+        };
+
+        let index = match self
+            .bundles
+            .iter()
+            .position(|bundle| bundle.locales.first() == Some(&identifier))
+        {
+            Some(index) => index,
+            None => {
+                let mut bundle = FluentBundle::new_concurrent(vec![identifier]);
+                // The lines are shown as they are, without Unicode isolation marks
+                bundle.set_use_isolating(false);
+                self.bundles.push(bundle);
+                self.chain.push(self.bundles.len() - 1);
+                self.bundles.len() - 1
+            }
+        };
+        if let Err(errors) = self.bundles[index].add_resource(resource) {
+            diagnostics.extend(
+                errors
+                    .into_iter()
+                    .map(|error| diagnostic(None, error.to_string())),
+            );
+        }
+
+        if diagnostics.is_empty() {
+            Ok(())
+        } else {
+            Err(diagnostics)
+        }
+    }
+
+    /// Returns the locales tried in order.
+    pub fn locales(&self) -> Vec<String> {
+        self.chain
+            .iter()
+            .filter_map(|index| self.bundles[*index].locales.first())
+            .map(ToString::to_string)
+            .collect()
+    }
+
+    /// Sets the chain to the locales matching the `requested` ones, by order of preference,
+    /// like the languages of the player then the one the game is written in.
+    ///
+    /// A requested `fr-CA` matches a `fr` resource, and the first added locale ends the chain.
+    /// The requested locales which cannot be read are left out.
+    ///
+    /// ```rust
+    /// use yml_dialog::*;
+    ///
+    /// let mut fluent = FluentLocalizer::new();
+    /// fluent.add_resource("en", "bye = Bye\nhello = Hello\n").unwrap();
+    /// fluent.add_resource("fr", "hello = Salut\n").unwrap();
+    /// fluent.add_resource("de", "hello = Hallo\n").unwrap();
+    ///
+    /// fluent.request_locales(&["fr-CA"]);
+    /// assert_eq!(fluent.locales(), ["fr", "en"]);
+    /// assert_eq!(fluent.localize("hello").as_deref(), Some("Salut"));
+    /// assert_eq!(fluent.localize("bye").as_deref(), Some("Bye"));
+    /// ```
+    pub fn request_locales(&mut self, requested: &[&str]) {
+        let requested: Vec<LanguageIdentifier> = requested
+            .iter()
+            .filter_map(|locale| locale.parse().ok())
+            .collect();
+        let available: Vec<LanguageIdentifier> = self
+            .bundles
+            .iter()
+            .filter_map(|bundle| bundle.locales.first().cloned())
+            .collect();
+        let chain: Vec<usize> = negotiate_languages(
+            &requested,
+            &available,
+            available.first(),
+            NegotiationStrategy::Filtering,
+        )
+        .into_iter()
+        .filter_map(|locale| available.iter().position(|other| other == locale))
+        .collect();
+        self.chain = chain;
+    }
+
+    /// Formats the string `id` in the bundle at `index`,
+    /// `None` if it has no such message or attribute.
+    fn format(
+        &self,
+        index: usize,
+        id: &str,
+        variables: &Variables,
+        errors: &mut Vec<FluentError>,
+    ) -> Option<Cow<'_, str>> {
+        let bundle = &self.bundles[index];
+        let pattern = match bundle.get_message(id) {
+            Some(message) => message.value(),
+            None => {
+                let (message, attribute) = id.split_once('.')?;
+                bundle
+                    .get_message(message)?
+                    .get_attribute(attribute)
+                    .map(|attribute| attribute.value())
+            }
+        }?;
+        Some(bundle.format_pattern(pattern, Some(&arguments(variables)), errors))
+    }
+
+    /// Checks every string of the `dialog` against each locale of the chain,
+    /// formatted with the `variables`:
+    /// reports the messages a locale does not have, and the ones which cannot be formatted,
+    /// like a message using a variable the game does not set.
+    ///
+    /// ```rust
+    /// use yml_dialog::*;
+    ///
+    /// let dialog: Dialog = serde_yaml::from_str(
+    ///     "1:
+    ///   source: Merchant
+    ///   content:
+    ///     text: [You have no coin]
+    ///     exit_state: 2
+    ///   line_ids: [coins]\n",
+    /// )
+    /// .unwrap();
+    /// let mut fluent = FluentLocalizer::new();
+    /// fluent.add_resource("en", "coins = You have { $coins } coins\n").unwrap();
+    /// fluent.add_resource("fr", "hello = Salut\n").unwrap();
+    ///
+    /// let diagnostics: Vec<String> = fluent
+    ///     .check(&dialog, &Variables::new())
+    ///     .iter()
+    ///     .map(ToString::to_string)
+    ///     .collect();
+    /// assert_eq!(
+    ///     diagnostics,
+    ///     [
+    ///         "en: `coins`: Resolver error: Unknown variable: $coins",
+    ///         "fr: no message for `coins`",
+    ///     ]
+    /// );
+    /// ```
+    pub fn check<C, E, F>(
+        &self,
+        dialog: &Dialog<C, E, F>,
+        variables: &Variables,
+    ) -> Vec<FluentDiagnostic> {
+        let mut diagnostics = Vec::new();
+        for string in dialog.translatable_strings() {
+            for index in &self.chain {
+                let locale = self.bundles[*index]
+                    .locales
+                    .first()
+                    .map(ToString::to_string)
+                    .unwrap_or_default();
+                let diagnostic = |message| FluentDiagnostic {
+                    locale: locale.clone(),
+                    id: Some(string.id().clone()),
+                    line: None,
+                    message,
+                };
+                let mut errors = Vec::new();
+                if self
+                    .format(*index, string.id(), variables, &mut errors)
+                    .is_none()
+                {
+                    diagnostics.push(diagnostic(format!("no message for `{}`", string.id())));
+                }
+                diagnostics.extend(
+                    errors
+                        .into_iter()
+                        .map(|error| diagnostic(format!("`{}`: {error}", string.id()))),
+                );
+            }
+        }
+        diagnostics
+    }
+}
+
+impl Localizer for FluentLocalizer {
+    fn localize(&self, id: &str) -> Option<Cow<'_, str>> {
+        self.localize_with(id, &Variables::new())
+    }
+
+    /// Formats the message `id` of the first locale of the chain which has it.
+    ///
+    /// A message which cannot be fully formatted, like one using a variable the game
+    /// does not set, shows the placeholder where it failed: see [`FluentLocalizer::check`].
+    fn localize_with(&self, id: &str, variables: &Variables) -> Option<Cow<'_, str>> {
+        self.chain
+            .iter()
+            .find_map(|index| self.format(*index, id, variables, &mut Vec::new()))
+    }
+}
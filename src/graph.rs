@@ -0,0 +1,337 @@
+//! Draws the graph of a dialog, as Graphviz DOT or as a Mermaid flowchart.
+
+use std::{
+    collections::BTreeSet,
+    fmt::{self, Write},
+};
+
+use crate::{Content, Dialog, DialogNode};
+
+/// The longest line of text shown in a node, in characters.
+const LINE_LENGTH: usize = 40;
+
+/// What a graph shows of a dialog, whatever its language.
+struct Graph {
+    entry: Option<usize>,
+    nodes: Vec<GraphNode>,
+    edges: Vec<Edge>,
+    /// The `exit_state`s which are not nodes.
+    ends: BTreeSet<usize>,
+}
+
+struct GraphNode {
+    key: usize,
+    /// The key, and the label if any.
+    heading: String,
+    speaker: String,
+    /// The first line of a monolog, or the number of choices.
+    line: String,
+    trigger_event: Vec<String>,
+}
+
+struct Edge {
+    from: usize,
+    to: usize,
+    /// The text of a choice.
+    text: Option<String>,
+    /// The summary of its condition.
+    condition: Option<String>,
+}
+
+impl Graph {
+    fn new<C: fmt::Display, E, F>(dialog: &Dialog<C, E, F>) -> Self {
+        let mut graph = Graph {
+            entry: dialog.entry().filter(|entry| dialog.contains(entry)),
+            nodes: Vec::with_capacity(dialog.len()),
+            edges: Vec::new(),
+            ends: BTreeSet::new(),
+        };
+        for (key, node) in dialog {
+            graph.nodes.push(GraphNode {
+                key: *key,
+                heading: match dialog.label(key) {
+                    Some(label) => format!("{key}: {label}"),
+                    None => key.to_string(),
+                },
+                speaker: dialog.speaker(node).to_string(),
+                line: first_line(node),
+                trigger_event: node.trigger_event().clone(),
+            });
+            match node.content() {
+                Content::Monolog { exit_state, .. } => graph.edges.push(Edge {
+                    from: *key,
+                    to: *exit_state,
+                    text: None,
+                    condition: None,
+                }),
+                Content::Choices(choices) => {
+                    graph.edges.extend(choices.iter().map(|choice| Edge {
+                        from: *key,
+                        to: *choice.exit_state(),
+                        text: Some(choice.text().clone()),
+                        condition: choice.condition().as_ref().map(ToString::to_string),
+                    }))
+                }
+            }
+        }
+        graph.ends = graph
+            .edges
+            .iter()
+            .map(|edge| edge.to)
+            .filter(|to| !dialog.contains(to))
+            .collect();
+        graph
+    }
+}
+
+/// Returns the first line of a monolog, shortened, or the number of choices.
+fn first_line<C, E, F>(node: &DialogNode<C, E, F>) -> String {
+    match node.content() {
+        Content::Monolog { text, .. } => {
+            let line = text
+                .first()
+                .map_or("", |line| line.lines().next().unwrap_or(""));
+            if line.chars().count() > LINE_LENGTH {
+                let shortened: String = line.chars().take(LINE_LENGTH - 1).collect();
+                format!("{}…", shortened.trim_end())
+            } else {
+                line.to_string()
+            }
+        }
+        Content::Choices(choices) if choices.len() == 1 => "1 choice".to_string(),
+        Content::Choices(choices) => format!("{} choices", choices.len()),
+    }
+}
+
+/// Escapes a string of the DOT language.
+fn dot_escape(text: &str) -> String {
+    text.replace('\\', "\\\\").replace('"', "\\\"")
+}
+
+/// Escapes a quoted label of Mermaid.
+fn mermaid_escape(text: &str) -> String {
+    text.replace('"', "#quot;")
+}
+
+impl<C: fmt::Display, E, F> Dialog<C, E, F> {
+    /// Draws the dialog as a [Graphviz](https://graphviz.org) DOT graph, like `dot -Tsvg`.
+    ///
+    /// Each node shows its key and label, its speaker and its first line of text,
+    /// or its number of choices. The nodes with a `trigger_event` are highlighted, listing them.
+    /// The choices are edges labeled by their text, and their condition between brackets.
+    /// The `exit_state`s which are not nodes are drawn as terminal nodes, and the entry
+    /// is pointed at by a dot.
+    ///
+    /// ```rust
+    /// use yml_dialog::*;
+    ///
+    /// let dialog: Dialog = serde_yaml::from_str(
+    ///     "name: Frog Pond
+    /// nodes:
+    ///   1:
+    ///     source: Frog
+    ///     content:
+    ///       monolog:
+    ///         text: [KeroKero]
+    ///         exit_state: greet
+    ///   greet:
+    ///     source: Player
+    ///     content:
+    ///       choices:
+    ///       - text: Hi
+    ///         condition:
+    ///           events: [FrogLove]
+    ///         exit_state: 1
+    ///       - text: Bye
+    ///         exit_state: 3
+    ///     trigger_event: [FrogTalk]\n",
+    /// )
+    /// .unwrap();
+    ///
+    /// assert_eq!(
+    ///     dialog.to_dot(),
+    ///     r##"digraph "Frog Pond" {
+    ///     node [shape=box, style=rounded];
+    ///     start [shape=point];
+    ///     start -> n1;
+    ///     n1 [label="1\nFrog\nKeroKero"];
+    ///     n4 [label="4: greet\nPlayer\n2 choices\ntrigger: FrogTalk", style="rounded,filled,bold", fillcolor="#ffe08a"];
+    ///     exit3 [label="end 3", shape=doublecircle];
+    ///     n1 -> n4;
+    ///     n4 -> n1 [label="Hi\n[has(FrogLove)]"];
+    ///     n4 -> exit3 [label="Bye"];
+    /// }
+    /// "##
+    /// );
+    /// ```
+    pub fn to_dot(&self) -> String {
+        let graph = Graph::new(self);
+        let mut dot = String::new();
+        // Writing to a `String` does not fail
+        let _ = writeln!(
+            dot,
+            "digraph \"{}\" {{",
+            dot_escape(self.name().as_deref().unwrap_or("dialog"))
+        );
+        let _ = writeln!(dot, "    node [shape=box, style=rounded];");
+        if let Some(entry) = graph.entry {
+            let _ = writeln!(dot, "    start [shape=point];");
+            let _ = writeln!(dot, "    start -> {};", node_id(self, entry));
+        }
+        for node in &graph.nodes {
+            let mut label = vec![node.heading.clone()];
+            label.extend(
+                [&node.speaker, &node.line]
+                    .into_iter()
+                    .filter(|line| !line.is_empty())
+                    .cloned(),
+            );
+            let highlight = if node.trigger_event.is_empty() {
+                String::new()
+            } else {
+                label.push(format!("trigger: {}", node.trigger_event.join(", ")));
+                ", style=\"rounded,filled,bold\", fillcolor=\"#ffe08a\"".to_string()
+            };
+            let label: Vec<String> = label.iter().map(|line| dot_escape(line)).collect();
+            let _ = writeln!(
+                dot,
+                "    n{} [label=\"{}\"{highlight}];",
+                node.key,
+                label.join("\\n")
+            );
+        }
+        for end in &graph.ends {
+            let _ = writeln!(
+                dot,
+                "    exit{end} [label=\"end {end}\", shape=doublecircle];"
+            );
+        }
+        for edge in &graph.edges {
+            let mut label: Vec<String> = edge.text.iter().map(|text| dot_escape(text)).collect();
+            label.extend(
+                edge.condition
+                    .iter()
+                    .map(|condition| format!("[{}]", dot_escape(condition))),
+            );
+            let _ = write!(
+                dot,
+                "    {} -> {}",
+                node_id(self, edge.from),
+                node_id(self, edge.to)
+            );
+            if !label.is_empty() {
+                let _ = write!(dot, " [label=\"{}\"]", label.join("\\n"));
+            }
+            let _ = writeln!(dot, ";");
+        }
+        dot.push_str("}\n");
+        dot
+    }
+
+    /// Draws the dialog as a [Mermaid](https://mermaid.js.org) flowchart,
+    /// which GitHub renders within a ```` ```mermaid ```` block.
+    ///
+    /// It shows the same as [`Dialog::to_dot`], the highlighted nodes having the `trigger` class.
+    ///
+    /// ```rust
+    /// use yml_dialog::*;
+    ///
+    /// let dialog: Dialog = serde_yaml::from_str(
+    ///     "1:
+    ///   source: Frog
+    ///   content:
+    ///     choices:
+    ///     - text: Hi
+    ///       condition: karma >= 2
+    ///       exit_state: 2
+    ///     - text: \"Say \\\"Bye\\\"\"
+    ///       exit_state: 3
+    /// 2:
+    ///   source: Frog
+    ///   content:
+    ///     monolog:
+    ///       text: [Hello there!]
+    ///       exit_state: 3
+    ///   trigger_event: [FrogLove]\n",
+    /// )
+    /// .unwrap();
+    ///
+    /// assert_eq!(
+    ///     dialog.to_mermaid(),
+    ///     r#"flowchart TD
+    ///     start(( )) --> n1
+    ///     n1["1<br/>Frog<br/>2 choices"]
+    ///     n2["2<br/>Frog<br/>Hello there!<br/>trigger: FrogLove"]:::trigger
+    ///     exit3((("end 3")))
+    ///     n1 -->|"Hi<br/>[karma >= 2]"| n2
+    ///     n1 -->|"Say #quot;Bye#quot;"| exit3
+    ///     n2 --> exit3
+    ///     classDef trigger fill:#ffe08a,stroke:#b58900,stroke-width:2px
+    /// "#
+    /// );
+    /// ```
+    pub fn to_mermaid(&self) -> String {
+        let graph = Graph::new(self);
+        let mut mermaid = String::from("flowchart TD\n");
+        if let Some(entry) = graph.entry {
+            let _ = writeln!(mermaid, "    start(( )) --> {}", node_id(self, entry));
+        }
+        for node in &graph.nodes {
+            let mut label = vec![node.heading.clone()];
+            label.extend(
+                [&node.speaker, &node.line]
+                    .into_iter()
+                    .filter(|line| !line.is_empty())
+                    .cloned(),
+            );
+            let class = if node.trigger_event.is_empty() {
+                ""
+            } else {
+                label.push(format!("trigger: {}", node.trigger_event.join(", ")));
+                ":::trigger"
+            };
+            let label: Vec<String> = label.iter().map(|line| mermaid_escape(line)).collect();
+            let _ = writeln!(
+                mermaid,
+                "    n{}[\"{}\"]{class}",
+                node.key,
+                label.join("<br/>")
+            );
+        }
+        for end in &graph.ends {
+            let _ = writeln!(mermaid, "    exit{end}(((\"end {end}\")))");
+        }
+        for edge in &graph.edges {
+            let mut label: Vec<String> =
+                edge.text.iter().map(|text| mermaid_escape(text)).collect();
+            label.extend(
+                edge.condition
+                    .iter()
+                    .map(|condition| format!("[{}]", mermaid_escape(condition))),
+            );
+            let _ = write!(mermaid, "    {} -->", node_id(self, edge.from));
+            if !label.is_empty() {
+                let _ = write!(mermaid, "|\"{}\"|", label.join("<br/>"));
+            }
+            let _ = writeln!(mermaid, " {}", node_id(self, edge.to));
+        }
+        if graph
+            .nodes
+            .iter()
+            .any(|node| !node.trigger_event.is_empty())
+        {
+            mermaid.push_str("    classDef trigger fill:#ffe08a,stroke:#b58900,stroke-width:2px\n");
+        }
+        mermaid
+    }
+}
+
+/// The identifier of a node or of an end, the same in DOT and Mermaid,
+/// where `end` cannot start an identifier.
+fn node_id<C, E, F>(dialog: &Dialog<C, E, F>, key: usize) -> String {
+    if dialog.contains(&key) {
+        format!("n{key}")
+    } else {
+        format!("exit{key}")
+    }
+}
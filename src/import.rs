@@ -0,0 +1,310 @@
+//! What the importers of other dialog languages could not convert, like [`import_ink`](crate::import_ink),
+//! how they build their [`Dialog`], and how the exporters name its nodes.
+
+use std::{collections::BTreeMap, fmt};
+
+use crate::{Choice, Condition, Content, Dialog, DialogNode, Effect, Value};
+
+/// A construct of the imported script which could not be converted as is, see [`ImportReport`].
+#[derive(Clone, Eq, PartialEq, Ord, PartialOrd, Hash, Debug)]
+pub struct ImportIssue {
+    line: usize,
+    message: String,
+}
+
+impl ImportIssue {
+    /// Returns the `line` of the construct in the script, starting at 1.
+    pub fn line(&self) -> usize {
+        self.line
+    }
+
+    /// Returns the read-only `message` of the issue.
+    pub fn message(&self) -> &String {
+        &self.message
+    }
+}
+
+impl fmt::Display for ImportIssue {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "line {}: {}", self.line, self.message)
+    }
+}
+
+/// What an import went through.
+#[derive(Clone, Eq, PartialEq, Ord, PartialOrd, Hash, Debug, Default)]
+pub struct ImportReport {
+    issues: Vec<ImportIssue>,
+}
+
+impl ImportReport {
+    pub(crate) fn report(&mut self, line: usize, message: impl Into<String>) {
+        self.issues.push(ImportIssue {
+            line,
+            message: message.into(),
+        });
+    }
+
+    /// Sorts the issues by line, keeping the order of those of a same line.
+    pub(crate) fn finish(mut self) -> Self {
+        self.issues.sort_by_key(|issue| issue.line);
+        self
+    }
+
+    /// Returns what could not be converted as is, sorted by line.
+    pub fn issues(&self) -> &Vec<ImportIssue> {
+        &self.issues
+    }
+}
+
+/// Where an imported dialog goes.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub(crate) enum Target {
+    /// The index of an imported node.
+    Node(usize),
+    /// The index of a flow, whose entry is known once all flows are compiled.
+    Flow(usize),
+    End,
+}
+
+/// A node whose targets are not resolved yet.
+pub(crate) struct ImportedNode {
+    /// The line of the node, which orders the keys.
+    pub(crate) number: usize,
+    pub(crate) source: String,
+    pub(crate) content: ImportedContent,
+    pub(crate) trigger_event: Vec<String>,
+    pub(crate) effects: Vec<Effect>,
+}
+
+pub(crate) enum ImportedContent {
+    Monolog(Vec<String>, Target),
+    Choices(Vec<ImportedChoice>),
+}
+
+pub(crate) struct ImportedChoice {
+    pub(crate) text: String,
+    pub(crate) condition: Option<Condition>,
+    pub(crate) effects: Vec<Effect>,
+    pub(crate) exit_state: Target,
+}
+
+/// A named part of the script, like an Ink knot or a Yarn node.
+pub(crate) struct ImportedFlow {
+    /// The label of its entry node, none if empty.
+    pub(crate) label: String,
+    pub(crate) number: usize,
+    pub(crate) entry: Target,
+}
+
+/// The nodes and flows of an imported script, before their keys are given.
+#[derive(Default)]
+pub(crate) struct Imported {
+    pub(crate) nodes: Vec<ImportedNode>,
+    pub(crate) flows: Vec<ImportedFlow>,
+}
+
+impl Imported {
+    pub(crate) fn push(&mut self, number: usize, content: ImportedContent) -> usize {
+        self.nodes.push(ImportedNode {
+            number,
+            source: String::new(),
+            content,
+            trigger_event: Vec::new(),
+            effects: Vec::new(),
+        });
+        self.nodes.len() - 1
+    }
+
+    /// Returns the entry of each flow, following the flows made of a divert alone,
+    /// up to a node or the end. The loops are reported as `"`label` {verb} in a loop"`.
+    pub(crate) fn flow_entries(&self, verb: &str, report: &mut ImportReport) -> Vec<Target> {
+        (0..self.flows.len())
+            .map(|flow| {
+                let mut seen = vec![flow];
+                let mut entry = self.flows[flow].entry;
+                while let Target::Flow(next) = entry {
+                    if seen.contains(&next) {
+                        report.report(
+                            self.flows[flow].number,
+                            format!(
+                                "`{}` {verb} in a loop, the dialog ends there",
+                                self.flows[flow].label
+                            ),
+                        );
+                        return Target::End;
+                    }
+                    seen.push(next);
+                    entry = self.flows[next].entry;
+                }
+                entry
+            })
+            .collect()
+    }
+
+    /// Keys the nodes in the order of the script, starting at 1, the end being the key after the last one,
+    /// and labels the entry node of each flow.
+    pub(crate) fn into_dialog(self, entries: &[Target], entry: usize) -> Dialog {
+        let mut order: Vec<usize> = (0..self.nodes.len()).collect();
+        order.sort_by_key(|index| self.nodes[*index].number);
+        let mut keys = vec![0; self.nodes.len()];
+        for (position, index) in order.iter().enumerate() {
+            keys[*index] = position + 1;
+        }
+        let end = self.nodes.len() + 1;
+        let resolve = |target: Target| match target {
+            Target::Node(index) => keys[index],
+            Target::Flow(flow) => match entries[flow] {
+                Target::Node(index) => keys[index],
+                _ => end,
+            },
+            Target::End => end,
+        };
+
+        let mut nodes = BTreeMap::new();
+        for (index, node) in self.nodes.into_iter().enumerate() {
+            let content = match node.content {
+                ImportedContent::Monolog(text, exit_state) => Content::Monolog {
+                    text,
+                    exit_state: resolve(exit_state),
+                },
+                ImportedContent::Choices(choices) => Content::Choices(
+                    choices
+                        .into_iter()
+                        .map(|choice| {
+                            Choice::new(choice.text, choice.condition, resolve(choice.exit_state))
+                                .with_effects(choice.effects)
+                        })
+                        .collect(),
+                ),
+            };
+            let node = DialogNode::new(node.source, content, node.trigger_event)
+                .with_effects(node.effects);
+            nodes.insert(keys[index], node);
+        }
+
+        let mut dialog = Dialog::new(nodes);
+        if let Target::Node(index) = entries[entry] {
+            dialog = dialog.with_entry(keys[index]);
+        }
+        for (flow, entry) in self.flows.iter().zip(entries) {
+            if let Target::Node(index) = entry {
+                if !flow.label.is_empty() && dialog.label(&keys[*index]).is_none() {
+                    // A flow named like a number or a field of the dialog stays unlabeled
+                    let _ = dialog.set_label(keys[*index], flow.label.clone());
+                }
+            }
+        }
+        dialog
+    }
+}
+
+/// Names each node for an export: its label, `Start` for an unlabeled entry, or `node_` and its key.
+///
+/// Also returns the keys in the order to write them, the entry node first.
+pub(crate) fn export_names<C, E, F>(
+    dialog: &Dialog<C, E, F>,
+) -> (BTreeMap<usize, String>, Vec<usize>) {
+    let entry = dialog.entry().filter(|entry| dialog.contains(entry));
+    let names = dialog
+        .nodes()
+        .keys()
+        .map(|key| {
+            let name = match dialog.label(key) {
+                Some(label) => label.clone(),
+                None if entry == Some(*key) && dialog.key_of("Start").is_none() => {
+                    "Start".to_string()
+                }
+                None => {
+                    let mut name = format!("node_{key}");
+                    while dialog.key_of(&name).is_some() {
+                        name.insert(0, '_');
+                    }
+                    name
+                }
+            };
+            (*key, name)
+        })
+        .collect();
+
+    let mut order: Vec<usize> = entry.into_iter().collect();
+    order.extend(dialog.nodes().keys().filter(|key| Some(**key) != entry));
+    (names, order)
+}
+
+/// What an assignment of a script does.
+pub(crate) enum Assignment {
+    /// Adds the event to the `trigger_event` of the node.
+    Trigger(String),
+    Effect(Effect),
+}
+
+/// Removes the `// line` and `/* block */` comments, keeping the line numbers.
+pub(crate) fn strip_comments(script: &str) -> String {
+    let mut stripped = String::with_capacity(script.len());
+    let mut chars = script.chars().peekable();
+    while let Some(c) = chars.next() {
+        match (c, chars.peek()) {
+            ('/', Some('/')) => {
+                while chars.peek().is_some_and(|c| *c != '\n') {
+                    chars.next();
+                }
+            }
+            ('/', Some('*')) => {
+                chars.next();
+                let mut previous = ' ';
+                for c in chars.by_ref() {
+                    if c == '\n' {
+                        stripped.push('\n');
+                    }
+                    if previous == '*' && c == '/' {
+                        break;
+                    }
+                    previous = c;
+                }
+            }
+            _ => stripped.push(c),
+        }
+    }
+    stripped
+}
+
+/// Removes the `# tags` of a line.
+pub(crate) fn strip_tags<'a>(line: &'a str, number: usize, report: &mut ImportReport) -> &'a str {
+    match line.split_once('#') {
+        Some((line, tags)) => {
+            for tag in tags.split('#').map(str::trim).filter(|tag| !tag.is_empty()) {
+                report.report(number, format!("tag `{tag}` left out"));
+            }
+            line
+        }
+        None => line,
+    }
+}
+
+/// Replaces the runs of whitespace by a single space.
+pub(crate) fn collapse(text: &str) -> String {
+    text.split_whitespace().collect::<Vec<_>>().join(" ")
+}
+
+/// Returns `true` for a variable or flow name.
+pub(crate) fn is_name(name: &str) -> bool {
+    !name.is_empty()
+        && name
+            .chars()
+            .all(|c| c.is_alphanumeric() || c == '_' || c == '.')
+}
+
+/// Reads a number or a `"string"`.
+pub(crate) fn literal(value: &str) -> Option<Value> {
+    if let Some(string) = value
+        .strip_prefix('"')
+        .and_then(|value| value.strip_suffix('"'))
+    {
+        return Some(Value::String(string.to_string()));
+    }
+    value
+        .parse()
+        .map(Value::Int)
+        .ok()
+        .or_else(|| value.parse().map(Value::Float).ok())
+}
@@ -0,0 +1,704 @@
+//! Imports Ink scripts, see [`import_ink`].
+//!
+//! The script is first split into flows, the root one and a flow per knot and stitch,
+//! then each flow is woven: its lines are grouped into segments separated by gathers,
+//! a segment being some statements followed by a set of choices, each with its own weave.
+//! The segments are then compiled backwards, each one knowing where its loose ends go.
+
+use crate::{
+    import::{
+        collapse, is_name, literal, strip_comments, strip_tags, Assignment, Imported,
+        ImportedChoice, ImportedContent, ImportedFlow, Target,
+    },
+    Condition, Dialog, Effect, Expression, ImportReport,
+};
+
+/// Converts an [Ink](https://github.com/inkle/ink) script into a [`Dialog`].
+///
+/// The subset of Ink understood is:
+///
+/// - the lines of text, grouped into [`Content::Monolog`](crate::Content::Monolog)s;
+/// - the knots `=== knot ===` and stitches `= stitch`, becoming labeled nodes,
+///   like `knot` and `knot.stitch`;
+/// - the choices `*` and `+`, nested or not, with their `[bracketed]` text,
+///   becoming [`Content::Choices`](crate::Content::Choices), and the gathers `-`;
+///   once-only `*` choices are offered again if their node is entered again;
+/// - the diverts `-> knot`, `-> knot.stitch`, `-> stitch`, `-> END` and `-> DONE`,
+///   the ends leading to an `exit_state` which is not a node;
+/// - the conditions `{flag}`, `{not flag}` and `{!flag}` of the choices,
+///   becoming a [`Condition`] on the event `flag`.
+///   A condition on a knot or a stitch, visited or not, adds its name to the
+///   `trigger_event` of its node;
+/// - the assignments `~ flag = true`, adding `flag` to the `trigger_event` of the node,
+///   `~ flag = false`, `~ variable = 5` and `~ karma += 2`, becoming [`Effect`]s.
+///
+/// Anything else, like the tags, the inline logic, the tunnels or the functions,
+/// is reported in the [`ImportReport`] with its line, and left out or kept as written.
+///
+/// ```rust
+/// use yml_dialog::*;
+///
+/// let (dialog, report) = import_ink(
+///     "-> pond
+///
+/// === pond ===
+/// KeroKero
+/// * [Hi] -> greet
+/// * {FrogLove} [Bye]
+///     ~ FrogHate = true
+///     Bye bye. # sad
+///     -> END
+///
+/// = greet
+/// Hello there!
+/// -> DONE
+/// ",
+/// );
+///
+/// assert_eq!(report.issues().len(), 1);
+/// assert_eq!(report.issues()[0].to_string(), "line 8: tag `sad` left out");
+///
+/// let pond = dialog.key_of("pond").unwrap();
+/// assert_eq!(dialog.entry(), Some(pond));
+/// let Content::Monolog { exit_state: choices, .. } = dialog.get(&pond).unwrap().content() else {
+///     panic!("the knot starts with a monolog");
+/// };
+/// let Content::Choices(choices) = dialog.get(choices).unwrap().content() else {
+///     panic!("the monolog leads to the choices");
+/// };
+/// assert_eq!(choices[0].exit_state(), &dialog.key_of("pond.greet").unwrap());
+/// assert_eq!(
+///     choices[1].condition(),
+///     &Some(Condition::new(None, vec!["FrogLove".to_string()]))
+/// );
+/// assert_eq!(
+///     dialog.get(choices[1].exit_state()).unwrap().trigger_event(),
+///     &vec!["FrogHate".to_string()]
+/// );
+/// ```
+pub fn import_ink(ink: &str) -> (Dialog, ImportReport) {
+    let mut report = ImportReport::default();
+    let flows = parse(ink, &mut report);
+    let dialog = Compiler::new(&flows, &mut report).compile();
+    (dialog, report.finish())
+}
+
+/// A line of a flow.
+#[derive(Clone, PartialEq, Debug)]
+struct Line {
+    number: usize,
+    kind: LineKind,
+}
+
+#[derive(Clone, PartialEq, Debug)]
+enum LineKind {
+    Text(String),
+    /// The code after `~`.
+    Logic(String),
+    /// The target of `->`.
+    Divert(String),
+    /// The text shown is empty for a fallback choice, left out.
+    Choice {
+        depth: usize,
+        text: String,
+        conditions: Vec<String>,
+    },
+    Gather {
+        depth: usize,
+    },
+}
+
+/// The root flow, a knot or a stitch.
+#[derive(Default)]
+struct Flow {
+    /// Empty for the root flow, `knot` or `knot.stitch` otherwise.
+    address: String,
+    /// The knot of the flow, to find the stitches diverted to by name alone.
+    knot: String,
+    number: usize,
+    lines: Vec<Line>,
+}
+
+/// Splits the script into its flows, reporting what is left out.
+fn parse(ink: &str, report: &mut ImportReport) -> Vec<Flow> {
+    let mut flows = vec![Flow {
+        number: 1,
+        ..Default::default()
+    }];
+    let mut in_function = false;
+
+    for (index, line) in strip_comments(ink).lines().enumerate() {
+        let number = index + 1;
+        let line = line.trim();
+        if line.is_empty() || line.starts_with("TODO") {
+            continue;
+        }
+
+        if line.starts_with("==") {
+            let header = line.trim_matches('=').trim();
+            in_function = header.starts_with("function ");
+            if in_function {
+                report.report(number, format!("function `{header}` left out"));
+                continue;
+            }
+            let name = flow_name(header, number, report);
+            flows.push(Flow {
+                address: name.clone(),
+                knot: name,
+                number,
+                lines: Vec::new(),
+            });
+            continue;
+        }
+        if in_function {
+            continue;
+        }
+        if let Some(header) = line.strip_prefix('=') {
+            let name = flow_name(header.trim(), number, report);
+            let knot = flows.last().map_or(String::new(), |flow| flow.knot.clone());
+            flows.push(Flow {
+                address: if knot.is_empty() {
+                    name
+                } else {
+                    format!("{knot}.{name}")
+                },
+                knot,
+                number,
+                lines: Vec::new(),
+            });
+            continue;
+        }
+
+        let lines = &mut flows.last_mut().expect("the root flow").lines;
+        let mut push = |kind| lines.push(Line { number, kind });
+        let declaration = ["INCLUDE", "VAR", "CONST", "LIST", "EXTERNAL"]
+            .into_iter()
+            .find(|keyword| {
+                line.strip_prefix(keyword)
+                    .is_some_and(|rest| rest.starts_with(' '))
+            });
+        if let Some(keyword) = declaration {
+            report.report(number, format!("`{keyword}` left out: `{line}`"));
+        } else if let Some(code) = line.strip_prefix('~') {
+            push(LineKind::Logic(code.trim().to_string()));
+        } else if line.starts_with('*') || line.starts_with('+') {
+            let (depth, rest) = markers(line, |c| c == '*' || c == '+');
+            choice(depth, rest, number, report, push);
+        } else if line.starts_with('-') && !line.starts_with("->") {
+            let (depth, rest) = markers(line, |c| c == '-');
+            push(LineKind::Gather { depth });
+            let rest = strip_label(rest, number, report);
+            content(rest, number, report, push);
+        } else if line.starts_with("<-") {
+            report.report(number, format!("thread `{line}` left out"));
+        } else {
+            content(line, number, report, push);
+        }
+    }
+    flows
+}
+
+/// Returns the name of a knot or stitch `header`, reporting its parameters.
+fn flow_name(header: &str, number: usize, report: &mut ImportReport) -> String {
+    match header.split_once('(') {
+        Some((name, _)) => {
+            report.report(number, format!("parameters of `{header}` left out"));
+            name.trim().to_string()
+        }
+        None => header.to_string(),
+    }
+}
+
+/// Counts the leading `marker`s of a choice or a gather, maybe spaced.
+fn markers(line: &str, marker: impl Fn(char) -> bool + Copy) -> (usize, &str) {
+    let mut depth = 0;
+    let mut rest = line;
+    while rest.starts_with(marker) && !rest.starts_with("->") {
+        depth += 1;
+        rest = rest[1..].trim_start();
+    }
+    (depth, rest)
+}
+
+/// Removes the `(label)` of a choice or a gather.
+fn strip_label<'a>(rest: &'a str, number: usize, report: &mut ImportReport) -> &'a str {
+    if let Some(label) = rest.strip_prefix('(') {
+        if let Some((label, rest)) = label.split_once(')') {
+            report.report(number, format!("label `({label})` left out"));
+            return rest.trim_start();
+        }
+    }
+    rest
+}
+
+/// Pushes a choice, then its output line and divert, which start its weave.
+fn choice(
+    depth: usize,
+    rest: &str,
+    number: usize,
+    report: &mut ImportReport,
+    mut push: impl FnMut(LineKind),
+) {
+    let mut rest = strip_label(rest, number, report);
+    let mut conditions = Vec::new();
+    while let Some(condition) = rest.strip_prefix('{') {
+        let Some((condition, after)) = condition.split_once('}') else {
+            break;
+        };
+        conditions.push(condition.trim().to_string());
+        rest = after.trim_start();
+    }
+
+    let (rest, divert) = split_divert(strip_tags(rest, number, report), number, report);
+    let (text, output) = match rest.split_once('[').and_then(|(before, bracketed)| {
+        bracketed
+            .split_once(']')
+            .map(|(inside, after)| (before, inside, after))
+    }) {
+        Some((before, inside, after)) => (
+            collapse(&format!("{before}{inside}")),
+            collapse(&format!("{before}{after}")),
+        ),
+        None => (collapse(rest), collapse(rest)),
+    };
+    if text.is_empty() {
+        report.report(number, "fallback choice left out");
+    }
+
+    push(LineKind::Choice {
+        depth,
+        text,
+        conditions,
+    });
+    if !output.is_empty() {
+        push(LineKind::Text(text_line(&output, number, report)));
+    }
+    if let Some(divert) = divert {
+        push(LineKind::Divert(divert));
+    }
+}
+
+/// Pushes a line of text and its divert, if any.
+fn content(line: &str, number: usize, report: &mut ImportReport, mut push: impl FnMut(LineKind)) {
+    let (text, divert) = split_divert(strip_tags(line, number, report), number, report);
+    let text = collapse(text);
+    if !text.is_empty() {
+        push(LineKind::Text(text_line(&text, number, report)));
+    }
+    if let Some(divert) = divert {
+        push(LineKind::Divert(divert));
+    }
+}
+
+/// Reports the glue and the inline logic of a line of `text`, kept as written.
+fn text_line(text: &str, number: usize, report: &mut ImportReport) -> String {
+    let text = if text.contains("<>") {
+        report.report(number, "glue `<>` left out");
+        collapse(&text.replace("<>", " "))
+    } else {
+        text.to_string()
+    };
+    if text.contains('{') {
+        report.report(number, format!("inline logic kept as written: `{text}`"));
+    }
+    text
+}
+
+/// Splits the `-> divert` off the end of a line.
+fn split_divert<'a>(
+    line: &'a str,
+    number: usize,
+    report: &mut ImportReport,
+) -> (&'a str, Option<String>) {
+    let Some((line, target)) = line.split_once("->") else {
+        return (line, None);
+    };
+    let target = target.trim();
+    if target.is_empty() || target.starts_with("->") || target.contains("->") {
+        report.report(number, "tunnel left out, the dialog ends there");
+        return (line, Some("END".to_string()));
+    }
+    match target.split_once('(') {
+        Some((name, _)) => {
+            report.report(number, format!("arguments of `-> {target}` left out"));
+            (line, Some(name.trim().to_string()))
+        }
+        None => (line, Some(target.to_string())),
+    }
+}
+
+/// A part of a weave, ended by a gather or by the end of the weave.
+#[derive(Default)]
+struct Segment<'a> {
+    statements: Vec<&'a Line>,
+    choices: Vec<WovenChoice<'a>>,
+}
+
+struct WovenChoice<'a> {
+    line: &'a Line,
+    weave: Vec<Segment<'a>>,
+}
+
+/// Groups the lines of a flow into its weave.
+struct Weaver<'a> {
+    lines: &'a [Line],
+    position: usize,
+}
+
+impl<'a> Weaver<'a> {
+    /// Reads the weave within the choices of `depth`, `0` for the flow itself.
+    fn weave(&mut self, depth: usize) -> Vec<Segment<'a>> {
+        let mut segments = vec![Segment::default()];
+        while let Some(line) = self.lines.get(self.position) {
+            match line.kind {
+                LineKind::Choice { depth: choice, .. } | LineKind::Gather { depth: choice }
+                    if choice <= depth =>
+                {
+                    break
+                }
+                LineKind::Gather { .. } => {
+                    self.position += 1;
+                    segments.push(Segment::default());
+                }
+                LineKind::Choice { depth: choice, .. } => {
+                    self.position += 1;
+                    let weave = self.weave(choice);
+                    let segment = segments.last_mut().expect("a segment");
+                    segment.choices.push(WovenChoice { line, weave });
+                }
+                _ => {
+                    self.position += 1;
+                    let segment = segments.last_mut().expect("a segment");
+                    segment.statements.push(line);
+                }
+            }
+        }
+        segments
+    }
+}
+
+/// The lines of a node, after the assignments happening when it is entered.
+struct Chunk {
+    number: usize,
+    lines: Vec<String>,
+    assignments: Vec<Assignment>,
+}
+
+impl Chunk {
+    fn new(number: usize, lines: Vec<String>, assignments: Vec<Assignment>) -> Self {
+        Chunk {
+            number,
+            lines,
+            assignments,
+        }
+    }
+}
+
+struct Compiler<'a> {
+    flows: &'a [Flow],
+    report: &'a mut ImportReport,
+    imported: Imported,
+    /// The flow being compiled, to find the stitches named alone.
+    current: usize,
+    /// The flows whose visit is checked by a condition.
+    visited: Vec<usize>,
+}
+
+impl<'a> Compiler<'a> {
+    fn new(flows: &'a [Flow], report: &'a mut ImportReport) -> Self {
+        Compiler {
+            flows,
+            report,
+            imported: Imported::default(),
+            current: 0,
+            visited: Vec::new(),
+        }
+    }
+
+    fn compile(mut self) -> Dialog {
+        for (index, flow) in self.flows.iter().enumerate() {
+            self.current = index;
+            let weave = Weaver {
+                lines: &flow.lines,
+                position: 0,
+            }
+            .weave(0);
+            let entry = if !flow.lines.is_empty() {
+                self.weave(weave, Target::End)
+            } else if let Some(first) = self.first_stitch(index) {
+                // A knot without content of its own starts at its first stitch
+                Target::Flow(first)
+            } else if index == 0 {
+                // The script starting right away with a knot
+                if self.flows.len() > 1 {
+                    Target::Flow(1)
+                } else {
+                    Target::End
+                }
+            } else {
+                self.report.report(
+                    flow.number,
+                    format!("`{}` is empty, the dialog ends there", flow.address),
+                );
+                Target::End
+            };
+            self.imported.flows.push(ImportedFlow {
+                label: flow.address.clone(),
+                number: flow.number,
+                entry,
+            });
+        }
+
+        let entries = self.imported.flow_entries("diverts", self.report);
+        let mut visited = self.visited.clone();
+        visited.sort_unstable();
+        visited.dedup();
+        for flow in visited {
+            if let Target::Node(index) = entries[flow] {
+                let address = self.flows[flow].address.clone();
+                let trigger_event = &mut self.imported.nodes[index].trigger_event;
+                if !trigger_event.contains(&address) {
+                    trigger_event.push(address);
+                }
+            }
+        }
+        self.imported.into_dialog(&entries, 0)
+    }
+
+    /// Returns the first stitch of the knot `flow`, if it is a knot.
+    fn first_stitch(&self, flow: usize) -> Option<usize> {
+        let knot = &self.flows[flow];
+        if flow == 0 || knot.address != knot.knot {
+            return None;
+        }
+        let first = flow + 1;
+        self.flows
+            .get(first)
+            .filter(|stitch| stitch.knot == knot.knot)
+            .map(|_| first)
+    }
+
+    /// Compiles a weave whose loose ends go to `next`, returning its entry.
+    fn weave(&mut self, weave: Vec<Segment>, next: Target) -> Target {
+        weave
+            .into_iter()
+            .rev()
+            .fold(next, |next, segment| self.segment(segment, next))
+    }
+
+    fn segment(&mut self, segment: Segment, next: Target) -> Target {
+        // The statements up to the first divert: the rest cannot be reached
+        let mut statements = Vec::new();
+        let mut divert = None;
+        for line in segment.statements {
+            if divert.is_some() {
+                self.report
+                    .report(line.number, "unreachable after a divert, left out");
+                continue;
+            }
+            match &line.kind {
+                LineKind::Divert(target) => divert = Some(self.divert(target, line.number)),
+                _ => statements.push(line),
+            }
+        }
+
+        let mut choices_node = None;
+        let tail = match divert {
+            Some(divert) => {
+                for choice in &segment.choices {
+                    self.report
+                        .report(choice.line.number, "unreachable after a divert, left out");
+                }
+                divert
+            }
+            None if segment.choices.is_empty() => next,
+            None => {
+                let number = segment.choices[0].line.number;
+                let mut choices = Vec::new();
+                for choice in segment.choices {
+                    let LineKind::Choice {
+                        text, conditions, ..
+                    } = &choice.line.kind
+                    else {
+                        unreachable!("a woven choice is a choice line");
+                    };
+                    if text.is_empty() {
+                        continue;
+                    }
+                    let condition = self.condition(conditions, choice.line.number);
+                    let exit_state = self.weave(choice.weave, next);
+                    choices.push(ImportedChoice {
+                        text: text.clone(),
+                        condition,
+                        effects: Vec::new(),
+                        exit_state,
+                    });
+                }
+                let index = self
+                    .imported
+                    .push(number, ImportedContent::Choices(choices));
+                choices_node = Some(index);
+                Target::Node(index)
+            }
+        };
+
+        // A new node for the lines following an assignment, which happens when it is entered.
+        // The assignments after the last line happen on the node of the choices, if any,
+        // or on the node of the last line rather than on an empty one.
+        let mut chunks: Vec<Chunk> = Vec::new();
+        for line in statements {
+            match &line.kind {
+                LineKind::Text(text) => match chunks.last_mut() {
+                    Some(chunk) => chunk.lines.push(text.clone()),
+                    None => chunks.push(Chunk::new(line.number, vec![text.clone()], Vec::new())),
+                },
+                LineKind::Logic(code) => {
+                    let Some(assignment) = self.assignment(code, line.number) else {
+                        continue;
+                    };
+                    match chunks.last_mut() {
+                        Some(chunk) if chunk.lines.is_empty() => chunk.assignments.push(assignment),
+                        _ => chunks.push(Chunk::new(line.number, Vec::new(), vec![assignment])),
+                    }
+                }
+                _ => unreachable!("the statements are text and logic"),
+            }
+        }
+        if chunks.len() > 1
+            && choices_node.is_none()
+            && chunks.last().is_some_and(|chunk| chunk.lines.is_empty())
+        {
+            let last = chunks.pop().expect("several chunks");
+            let previous = chunks.last_mut().expect("several chunks");
+            previous.assignments.extend(last.assignments);
+        }
+
+        let mut target = tail;
+        for chunk in chunks.into_iter().rev() {
+            let index = match choices_node {
+                Some(index) if chunk.lines.is_empty() && target == Target::Node(index) => index,
+                _ => self
+                    .imported
+                    .push(chunk.number, ImportedContent::Monolog(chunk.lines, target)),
+            };
+            let node = &mut self.imported.nodes[index];
+            node.number = node.number.min(chunk.number);
+            for assignment in chunk.assignments.into_iter().rev() {
+                match assignment {
+                    Assignment::Trigger(event) => node.trigger_event.insert(0, event),
+                    Assignment::Effect(effect) => node.effects.insert(0, effect),
+                }
+            }
+            target = Target::Node(index);
+        }
+        target
+    }
+
+    /// Finds the flow named `name` from the current one: a stitch of its knot, a knot or an address.
+    fn find_flow(&self, name: &str) -> Option<usize> {
+        let knot = &self.flows[self.current].knot;
+        let local = format!("{knot}.{name}");
+        let position = |address: &str| {
+            self.flows
+                .iter()
+                .skip(1)
+                .position(|flow| flow.address == address)
+                .map(|position| position + 1)
+        };
+        position(&local).or_else(|| position(name))
+    }
+
+    fn divert(&mut self, target: &str, number: usize) -> Target {
+        if target == "END" || target == "DONE" {
+            return Target::End;
+        }
+        match self.find_flow(target) {
+            Some(flow) => Target::Flow(flow),
+            None => {
+                self.report.report(
+                    number,
+                    format!("unknown divert target `{target}`, the dialog ends there"),
+                );
+                Target::End
+            }
+        }
+    }
+
+    /// Builds the condition of a choice, reporting the unsupported ones.
+    fn condition(&mut self, conditions: &[String], number: usize) -> Option<Condition> {
+        let mut events = Vec::new();
+        let mut negated = Vec::new();
+        for condition in conditions {
+            let negation = condition
+                .strip_prefix("not ")
+                .or_else(|| condition.strip_prefix('!'))
+                .map(str::trim);
+            match negation {
+                Some(name) if is_name(name) => negated.push(self.event(name)),
+                None if is_name(condition) => events.push(self.event(condition)),
+                _ => self
+                    .report
+                    .report(number, format!("condition `{{{condition}}}` left out")),
+            }
+        }
+        if events.is_empty() && negated.is_empty() {
+            return None;
+        }
+        let condition = Condition::new(None, events);
+        Some(
+            match negated
+                .into_iter()
+                .map(|event| Expression::Not(Box::new(Expression::Has(event))))
+                .reduce(|left, right| Expression::And(Box::new(left), Box::new(right)))
+            {
+                Some(expression) => condition.with_expression(expression),
+                None => condition,
+            },
+        )
+    }
+
+    /// Returns the event of a condition, the address of a flow if it checks its visit.
+    fn event(&mut self, name: &str) -> String {
+        match self.find_flow(name) {
+            Some(flow) => {
+                self.visited.push(flow);
+                self.flows[flow].address.clone()
+            }
+            None => name.to_string(),
+        }
+    }
+
+    /// Reads a `~` line, reporting the unsupported ones.
+    fn assignment(&mut self, code: &str, number: usize) -> Option<Assignment> {
+        let code = code.strip_prefix("temp ").unwrap_or(code).trim();
+        let assignment = if let Some((name, amount)) = code.split_once("+=") {
+            (name.trim() == "karma")
+                .then(|| amount.trim().parse().ok())
+                .flatten()
+                .map(|amount| Assignment::Effect(Effect::AddKarma(amount)))
+        } else if let Some((name, amount)) = code.split_once("-=") {
+            (name.trim() == "karma")
+                .then(|| amount.trim().parse::<i32>().ok())
+                .flatten()
+                .and_then(i32::checked_neg)
+                .map(|amount| Assignment::Effect(Effect::AddKarma(amount)))
+        } else {
+            code.split_once('=')
+                .filter(|(name, value)| is_name(name.trim()) && !value.starts_with('='))
+                .and_then(|(name, value)| {
+                    let name = name.trim().to_string();
+                    match value.trim() {
+                        "true" => Some(Assignment::Trigger(name)),
+                        "false" => Some(Assignment::Effect(Effect::ClearEvent(name))),
+                        value => literal(value)
+                            .map(|value| Assignment::Effect(Effect::SetVar { name, value })),
+                    }
+                })
+        };
+        if assignment.is_none() {
+            self.report
+                .report(number, format!("logic `~ {code}` left out"));
+        }
+        assignment
+    }
+}
@@ -0,0 +1,83 @@
+//! Reads and writes dialogs as JSON, behind the `json` feature.
+
+use serde::{de::DeserializeOwned, Serialize};
+use std::io;
+
+use crate::{
+    track::{deserialize_tracked, read_checked, split_position},
+    Dialog, DialogError,
+};
+
+impl<C, E, F> Dialog<C, E, F> {
+    /// Reads a dialog from a JSON string.
+    ///
+    /// The dialog has the same shape as in YAML, its numeric node keys being strings like `"12"`.
+    /// The error tells the node, the field and the position in the file of the faulty value,
+    /// see [`DialogError`].
+    ///
+    /// ```rust
+    /// use yml_dialog::*;
+    ///
+    /// let dialog: Dialog = Dialog::from_json_str(
+    ///     r#"{
+    ///   "name": "Frog",
+    ///   "nodes": {
+    ///     "1": {
+    ///       "source": "Frog",
+    ///       "content": { "monolog": { "text": ["KeroKero"], "exit_state": 2 } }
+    ///     }
+    ///   }
+    /// }"#,
+    /// )
+    /// .unwrap();
+    ///
+    /// assert_eq!(dialog.name(), &Some("Frog".to_string()));
+    /// assert_eq!(Dialog::from_json_str(&dialog.to_json_string().unwrap()), Ok(dialog));
+    /// ```
+    pub fn from_json_str(json: &str) -> Result<Self, DialogError>
+    where
+        Self: DeserializeOwned,
+    {
+        read_checked(
+            || {
+                serde_json::from_str(json).map_err(|error| {
+                    let (message, position) = split_position(&error.to_string());
+                    DialogError::new(message).at_position(position)
+                })
+            },
+            || {
+                deserialize_tracked(&mut serde_json::Deserializer::from_str(json)).map_err(
+                    |(error, json_error)| {
+                        // `0` when the error was not raised while parsing
+                        error.at_position(
+                            (json_error.line() > 0)
+                                .then(|| (json_error.line(), json_error.column())),
+                        )
+                    },
+                )
+            },
+        )
+    }
+
+    /// Reads a dialog from a JSON `reader`, like a file.
+    ///
+    /// See [`Dialog::from_json_str`].
+    pub fn from_json_reader<R: io::Read>(mut reader: R) -> Result<Self, DialogError>
+    where
+        Self: DeserializeOwned,
+    {
+        let mut json = String::new();
+        reader
+            .read_to_string(&mut json)
+            .map_err(|error| DialogError::new(error.to_string()))?;
+        Self::from_json_str(&json)
+    }
+
+    /// Writes the dialog as pretty-printed JSON.
+    pub fn to_json_string(&self) -> Result<String, DialogError>
+    where
+        Self: Serialize,
+    {
+        serde_json::to_string_pretty(self).map_err(|error| DialogError::new(error.to_string()))
+    }
+}
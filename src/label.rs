@@ -0,0 +1,287 @@
+//! String labels standing for node keys, like `exit_state: greet_player`.
+//!
+//! A label is only meaningful within a [`Dialog`](crate::Dialog):
+//! its (de)serialization opens a scope in which the `exit_state`s are written or read as labels.
+//! In memory, every node has a `usize` key.
+
+use serde::{
+    de::{self, Visitor},
+    Deserialize, Deserializer, Serialize, Serializer,
+};
+use std::{cell::RefCell, collections::BTreeMap, fmt};
+
+use crate::{Content, DialogNode};
+
+/// The fields of a [`Dialog`](crate::Dialog) which are not nodes.
+///
+/// A node labeled like one of them is only read as a node under `nodes:`.
+pub(crate) const METADATA_FIELDS: &[&str] =
+    &["name", "default_speaker", "entry", "nodes", "version"];
+
+/// The first key given to a label while the dialog is read, before being renumbered.
+pub(crate) const PROVISIONAL_KEY: usize = usize::MAX / 2;
+
+/// The labels known while a dialog is read or written.
+#[derive(Default)]
+pub(crate) struct LabelScope {
+    /// Reading: the provisional key of each label met, as a node key or as an `exit_state`.
+    pub(crate) provisional: BTreeMap<String, usize>,
+    /// Reading: the first node referencing each label by an `exit_state`.
+    pub(crate) referenced_by: BTreeMap<String, String>,
+    /// Reading: the key or label of the node being read.
+    current_node: Option<String>,
+    /// Writing: the label of each labeled key.
+    labels: BTreeMap<usize, String>,
+}
+
+thread_local! {
+    static SCOPE: RefCell<Option<LabelScope>> = const { RefCell::new(None) };
+}
+
+/// Opens a [`LabelScope`] until it is finished or dropped, restoring the enclosing one.
+pub(crate) struct LabelGuard {
+    previous: Option<LabelScope>,
+}
+
+impl LabelGuard {
+    /// Opens a scope writing the keys of `labels` as their label.
+    pub(crate) fn enter(labels: BTreeMap<usize, String>) -> Self {
+        let scope = LabelScope {
+            labels,
+            ..Default::default()
+        };
+        LabelGuard {
+            previous: SCOPE.with(|current| current.replace(Some(scope))),
+        }
+    }
+
+    /// Closes the scope, returning it.
+    pub(crate) fn finish(mut self) -> LabelScope {
+        let previous = self.previous.take();
+        SCOPE
+            .with(|current| current.replace(previous))
+            .unwrap_or_default()
+    }
+}
+
+impl Drop for LabelGuard {
+    fn drop(&mut self) {
+        let previous = self.previous.take();
+        SCOPE.with(|current| current.replace(previous));
+    }
+}
+
+/// Returns the provisional key of the `label`, giving it a new one if it is met for the first time.
+///
+/// Returns `None` outside of a scope.
+pub(crate) fn provisional_key(label: &str) -> Option<usize> {
+    SCOPE.with(|current| {
+        current.borrow_mut().as_mut().map(|scope| {
+            let next = PROVISIONAL_KEY + scope.provisional.len();
+            *scope.provisional.entry(label.to_string()).or_insert(next)
+        })
+    })
+}
+
+/// Records the key or label of the node being read, to report unknown labels.
+pub(crate) fn set_current_node(node: String) {
+    SCOPE.with(|current| {
+        if let Some(scope) = current.borrow_mut().as_mut() {
+            scope.current_node = Some(node);
+        }
+    })
+}
+
+/// Renumbers the `exit_state`s of the `node` found in `keys`.
+pub(crate) fn renumber_exits<C, E, F>(
+    node: &mut DialogNode<C, E, F>,
+    keys: &BTreeMap<usize, usize>,
+) {
+    match node.content_mut() {
+        Content::Monolog { exit_state, .. } => {
+            if let Some(key) = keys.get(exit_state) {
+                *exit_state = *key;
+            }
+        }
+        Content::Choices(choices) => {
+            for choice in choices {
+                if let Some(key) = keys.get(choice.exit_state()) {
+                    *choice.exit_state_mut() = *key;
+                }
+            }
+        }
+    }
+}
+
+/// A key or a label, as written in a dialog.
+#[derive(Clone, Eq, PartialEq, Ord, PartialOrd, Hash, Debug)]
+pub(crate) enum NodeKey {
+    Key(usize),
+    Label(String),
+}
+
+impl NodeKey {
+    /// Numeric strings are keys, formats like JSON only having string keys.
+    pub(crate) fn from_str(value: &str) -> Self {
+        value
+            .parse()
+            .map_or_else(|_| NodeKey::Label(value.to_string()), NodeKey::Key)
+    }
+}
+
+impl fmt::Display for NodeKey {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            NodeKey::Key(key) => write!(f, "{key}"),
+            NodeKey::Label(label) => write!(f, "{label}"),
+        }
+    }
+}
+
+impl Serialize for NodeKey {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        match self {
+            NodeKey::Key(key) => key.serialize(serializer),
+            NodeKey::Label(label) => label.serialize(serializer),
+        }
+    }
+}
+
+/// Returns the label of the `key` within the current scope, if any.
+pub(crate) fn written_key(key: usize) -> NodeKey {
+    SCOPE.with(|current| {
+        current
+            .borrow()
+            .as_ref()
+            .and_then(|scope| scope.labels.get(&key))
+            .map_or(NodeKey::Key(key), |label| NodeKey::Label(label.clone()))
+    })
+}
+
+/// An `exit_state` which may be written as a label.
+#[derive(Copy, Clone, Eq, PartialEq, Ord, PartialOrd, Hash, Debug)]
+pub(crate) struct ExitState(pub(crate) usize);
+
+impl ExitState {
+    /// Reads a key, refusing the ones which would collide with the provisional keys of labels.
+    fn from_key<E: de::Error>(key: usize) -> Result<Self, E> {
+        if key >= PROVISIONAL_KEY {
+            return Err(E::custom(format!("the node key `{key}` is too large")));
+        }
+        Ok(ExitState(key))
+    }
+}
+
+impl Serialize for ExitState {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        written_key(self.0).serialize(serializer)
+    }
+}
+
+impl<'de> Deserialize<'de> for ExitState {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        struct ExitStateVisitor;
+
+        impl<'de> Visitor<'de> for ExitStateVisitor {
+            type Value = ExitState;
+
+            fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+                formatter.write_str("a node key or label")
+            }
+
+            fn visit_u64<E: de::Error>(self, value: u64) -> Result<ExitState, E> {
+                usize::try_from(value)
+                    .map_err(|_| E::invalid_value(de::Unexpected::Unsigned(value), &self))
+                    .and_then(ExitState::from_key)
+            }
+
+            fn visit_i64<E: de::Error>(self, value: i64) -> Result<ExitState, E> {
+                usize::try_from(value)
+                    .map_err(|_| E::invalid_value(de::Unexpected::Signed(value), &self))
+                    .and_then(ExitState::from_key)
+            }
+
+            fn visit_str<E: de::Error>(self, value: &str) -> Result<ExitState, E> {
+                let label = match NodeKey::from_str(value) {
+                    NodeKey::Key(key) => return ExitState::from_key(key),
+                    NodeKey::Label(label) => label,
+                };
+                let key = provisional_key(&label).ok_or_else(|| {
+                    E::custom(format!(
+                        "the label `{label}` can only be resolved within a dialog"
+                    ))
+                })?;
+                SCOPE.with(|current| {
+                    if let Some(scope) = current.borrow_mut().as_mut() {
+                        let node = scope.current_node.clone().unwrap_or_default();
+                        scope.referenced_by.entry(label).or_insert(node);
+                    }
+                });
+                Ok(ExitState(key))
+            }
+        }
+
+        deserializer.deserialize_any(ExitStateVisitor)
+    }
+}
+
+/// `#[serde(with = "label::exit_state")]` for a `usize` `exit_state`.
+pub(crate) mod exit_state {
+    use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+    use super::ExitState;
+
+    pub(crate) fn serialize<S: Serializer>(key: &usize, serializer: S) -> Result<S::Ok, S::Error> {
+        ExitState(*key).serialize(serializer)
+    }
+
+    pub(crate) fn deserialize<'de, D: Deserializer<'de>>(
+        deserializer: D,
+    ) -> Result<usize, D::Error> {
+        ExitState::deserialize(deserializer).map(|exit_state| exit_state.0)
+    }
+}
+
+impl<'de> Deserialize<'de> for NodeKey {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        struct NodeKeyVisitor;
+
+        impl<'de> Visitor<'de> for NodeKeyVisitor {
+            type Value = NodeKey;
+
+            fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+                formatter.write_str("a node key or label")
+            }
+
+            fn visit_u64<E: de::Error>(self, value: u64) -> Result<NodeKey, E> {
+                usize::try_from(value)
+                    .map(NodeKey::Key)
+                    .map_err(|_| E::invalid_value(de::Unexpected::Unsigned(value), &self))
+            }
+
+            fn visit_i64<E: de::Error>(self, value: i64) -> Result<NodeKey, E> {
+                usize::try_from(value)
+                    .map(NodeKey::Key)
+                    .map_err(|_| E::invalid_value(de::Unexpected::Signed(value), &self))
+            }
+
+            fn visit_str<E: de::Error>(self, value: &str) -> Result<NodeKey, E> {
+                Ok(NodeKey::from_str(value))
+            }
+        }
+
+        deserializer.deserialize_any(NodeKeyVisitor)
+    }
+}
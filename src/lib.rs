@@ -15,30 +15,958 @@
 
 use serde::{
     // ser::{SerializeStruct, SerializeStructVariant, Serializer},
+    de::{self, MapAccess, SeqAccess, Visitor},
+    ser::SerializeMap,
     Deserialize,
+    Deserializer,
     Serialize,
+    Serializer,
 };
+use std::{
+    collections::{btree_map, BTreeMap},
+    fmt,
+    marker::PhantomData,
+    mem,
+};
+
+use label::{ExitState, LabelGuard, LabelScope, NodeKey, METADATA_FIELDS, PROVISIONAL_KEY};
+
+#[cfg(feature = "bevy_asset")]
+mod asset;
+mod effect;
+mod error;
+mod expression;
+#[cfg(feature = "fluent")]
+mod fluent;
+mod graph;
+mod import;
+mod ink;
+#[cfg(feature = "json")]
+mod json;
+mod label;
+mod localization;
+#[cfg(feature = "yaml")]
+mod migration;
+#[cfg(feature = "bevy")]
+mod plugin;
+mod po;
+#[cfg(feature = "ron")]
+mod ron;
+mod runner;
+#[cfg(feature = "toml")]
+mod toml;
+#[cfg(any(feature = "yaml", feature = "json", feature = "ron", feature = "toml"))]
+mod track;
+mod twee;
+mod validation;
+mod variables;
+#[cfg(feature = "xliff")]
+mod xliff;
+#[cfg(feature = "yaml")]
+mod yaml;
+mod yarn;
+
+#[cfg(feature = "bevy_asset")]
+pub use asset::*;
+pub use effect::*;
+pub use error::*;
+pub use expression::*;
+#[cfg(feature = "fluent")]
+pub use fluent::*;
+pub use import::*;
+pub use ink::*;
+pub use localization::*;
+#[cfg(feature = "yaml")]
+pub use migration::*;
+#[cfg(feature = "bevy")]
+pub use plugin::*;
+pub use runner::*;
+pub use twee::*;
+pub use validation::*;
+pub use variables::*;
+#[cfg(feature = "xliff")]
+pub use xliff::*;
+pub use yarn::*;
+
+/// The version of the dialog format read by this crate.
+///
+/// A dialog file may declare it under `version:`, next to its metadata:
+/// a dialog is written with it whenever it is written with its metadata.
+/// A file without it is read as the current version: older ones are migrated
+/// by `migrate_yaml`, with the `yaml` feature.
+pub const DIALOG_VERSION: u32 = 2;
+
+/// A whole dialog: the graph of [`DialogNode`]s, its entry node and some metadata.
+///
+/// It (de)serializes either from the bare map of nodes
+/// (the entry node then being the lowest key)
+/// or from a map with the metadata and a `nodes:` field.
+///
+/// A node can be keyed by a label rather than a number, see [`Dialog::label`].
+///
+/// A `version:` field other than [`DIALOG_VERSION`] is refused,
+/// and the metadata shape is written with the current one.
+///
+/// ```rust
+/// use yml_dialog::*;
+///
+/// let yaml = "name: Frog
+/// default_speaker: The Frog
+/// entry: 2
+/// nodes:
+///   1:
+///     content:
+///       text:
+///       - KeroKero
+///       exit_state: 3
+///   2:
+///     source: Random Frog
+///     content:
+///       text:
+///       - Yo Homie
+///       exit_state: 1\n";
+///
+/// let dialog: Dialog = serde_yaml::from_str(yaml).unwrap();
+///
+/// assert_eq!(dialog.name(), &Some("Frog".to_string()));
+/// assert_eq!(dialog.entry(), Some(2));
+/// assert_eq!(dialog.entry_node().unwrap().source(), "Random Frog");
+/// assert_eq!(dialog.speaker(dialog.get(&1).unwrap()), "The Frog");
+///
+/// let serialized = serde_yaml::to_string(&dialog).unwrap();
+/// assert!(serialized.starts_with("version: 2\nname: Frog\ndefault_speaker: The Frog\nentry: 2\nnodes:\n"));
+/// assert_eq!(serde_yaml::from_str::<Dialog>(&serialized).unwrap(), dialog);
+/// ```
+#[derive(Clone, Eq, PartialEq, Ord, PartialOrd, Hash, Debug)]
+pub struct Dialog<C = Condition, E = (), F = String> {
+    name: Option<String>,
+    default_speaker: Option<String>,
+    entry: Option<usize>,
+    nodes: BTreeMap<usize, DialogNode<C, E, F>>,
+    labels: BTreeMap<usize, String>,
+}
+
+impl<C, E, F> Default for Dialog<C, E, F> {
+    fn default() -> Self {
+        Dialog {
+            name: None,
+            default_speaker: None,
+            entry: None,
+            nodes: BTreeMap::new(),
+            labels: BTreeMap::new(),
+        }
+    }
+}
+
+impl<C, E, F> Dialog<C, E, F> {
+    /// Constructs a new Dialog with the given `nodes`, without metadata.
+    ///
+    /// The entry node is the lowest key of the map.
+    pub fn new(nodes: BTreeMap<usize, DialogNode<C, E, F>>) -> Self {
+        Dialog {
+            nodes,
+            ..Default::default()
+        }
+    }
+
+    /// Sets an explicit entry node.
+    pub fn with_entry(mut self, entry: usize) -> Self {
+        self.entry = Some(entry);
+        self
+    }
+
+    /// Sets the name of the dialog.
+    pub fn with_name(mut self, name: String) -> Self {
+        self.name = Some(name);
+        self
+    }
+
+    /// Sets the speaker used by the nodes without `source`.
+    pub fn with_default_speaker(mut self, default_speaker: String) -> Self {
+        self.default_speaker = Some(default_speaker);
+        self
+    }
+
+    /// Returns the read-only `name` of the dialog.
+    pub fn name(&self) -> &Option<String> {
+        &self.name
+    }
+
+    /// Returns the mutable `name` of the dialog.
+    pub fn name_mut(&mut self) -> &mut Option<String> {
+        &mut self.name
+    }
+
+    /// Returns the read-only `default_speaker` of the dialog.
+    pub fn default_speaker(&self) -> &Option<String> {
+        &self.default_speaker
+    }
+
+    /// Returns the mutable `default_speaker` of the dialog.
+    pub fn default_speaker_mut(&mut self) -> &mut Option<String> {
+        &mut self.default_speaker
+    }
+
+    /// Returns the key of the entry node:
+    /// the explicit one if any, the lowest key of the map otherwise.
+    ///
+    /// Returns `None` if the dialog has no explicit entry and no node.
+    pub fn entry(&self) -> Option<usize> {
+        self.entry
+            .or_else(|| self.nodes.first_key_value().map(|(key, _)| *key))
+    }
+
+    /// Sets the explicit entry node, `None` falls back to the lowest key.
+    pub fn set_entry(&mut self, entry: Option<usize>) {
+        self.entry = entry;
+    }
+
+    /// Returns the entry node, if it exists.
+    pub fn entry_node(&self) -> Option<&DialogNode<C, E, F>> {
+        self.entry().and_then(|entry| self.nodes.get(&entry))
+    }
+
+    /// Returns the speaker of the given `node`:
+    /// its `source`, or the `default_speaker` if the `source` is empty.
+    pub fn speaker<'a>(&'a self, node: &'a DialogNode<C, E, F>) -> &'a str {
+        match &self.default_speaker {
+            Some(default_speaker) if node.source().is_empty() => default_speaker,
+            _ => node.source(),
+        }
+    }
+
+    /// Returns the read-only map of nodes.
+    pub fn nodes(&self) -> &BTreeMap<usize, DialogNode<C, E, F>> {
+        &self.nodes
+    }
+
+    /// Returns the mutable map of nodes.
+    pub fn nodes_mut(&mut self) -> &mut BTreeMap<usize, DialogNode<C, E, F>> {
+        &mut self.nodes
+    }
+
+    /// Returns the node corresponding to the `key`.
+    pub fn get(&self, key: &usize) -> Option<&DialogNode<C, E, F>> {
+        self.nodes.get(key)
+    }
+
+    /// Returns the mutable node corresponding to the `key`.
+    pub fn get_mut(&mut self, key: &usize) -> Option<&mut DialogNode<C, E, F>> {
+        self.nodes.get_mut(key)
+    }
+
+    /// Returns `true` if the dialog contains a node for the `key`.
+    ///
+    /// An `exit_state` which is not contained is an end of the dialog.
+    pub fn contains(&self, key: &usize) -> bool {
+        self.nodes.contains_key(key)
+    }
+
+    /// Inserts a node, returning the previous node at this `key` if any.
+    pub fn insert(&mut self, key: usize, node: DialogNode<C, E, F>) -> Option<DialogNode<C, E, F>> {
+        self.nodes.insert(key, node)
+    }
+
+    /// Removes a node and its label, returning it if it was in the dialog.
+    ///
+    /// If it was the explicit entry, the entry falls back to the lowest key.
+    pub fn remove(&mut self, key: &usize) -> Option<DialogNode<C, E, F>> {
+        if self.entry == Some(*key) {
+            self.entry = None;
+        }
+        self.labels.remove(key);
+        self.nodes.remove(key)
+    }
+
+    /// Returns the lowest key above every node, `exit_state` and the entry.
+    ///
+    /// A node inserted there does not take the place of an end of the dialog.
+    ///
+    /// ```rust
+    /// use yml_dialog::*;
+    ///
+    /// let mut dialog: Dialog = serde_yaml::from_str(
+    ///     "1:
+    ///   source: Frog
+    ///   content:
+    ///     choices:
+    ///     - text: Hi
+    ///       exit_state: 2
+    ///     - text: Bye
+    ///       exit_state: 5
+    /// 2:
+    ///   source: Frog
+    ///   content:
+    ///     monolog:
+    ///       text: [Hello there!]
+    ///       exit_state: 1\n",
+    /// )
+    /// .unwrap();
+    ///
+    /// assert_eq!(dialog.free_key(), 6);
+    /// assert_eq!(dialog.references_to(&1), vec![2]);
+    /// assert_eq!(dialog.references_to(&5), vec![1]);
+    ///
+    /// dialog.remove(&2);
+    /// assert_eq!(dialog.free_key(), 6);
+    /// assert_eq!(Dialog::<Condition>::default().free_key(), 1);
+    /// ```
+    pub fn free_key(&self) -> usize {
+        self.nodes
+            .iter()
+            .flat_map(|(key, node)| node.exit_states().into_iter().chain([*key]))
+            .chain(self.entry)
+            .max()
+            .map_or(1, |key| key + 1)
+    }
+
+    /// Returns the keys of the nodes with an `exit_state` to `key`, sorted.
+    pub fn references_to(&self, key: &usize) -> Vec<usize> {
+        self.nodes
+            .iter()
+            .filter(|(_, node)| node.exit_states().contains(key))
+            .map(|(from, _)| *from)
+            .collect()
+    }
+
+    /// Returns the label of the node `key`, if any.
+    ///
+    /// A node written under a label, rather than a number, can be the `exit_state` or the `entry`
+    /// of the dialog by this label. The labeled nodes are given the keys following
+    /// the numeric keys and `exit_state`s, and are written back under their label.
+    /// A node labeled like a field of the dialog, such as `name`, can only be written
+    /// under `nodes:`, where it is written back.
+    ///
+    /// ```rust
+    /// use yml_dialog::*;
+    ///
+    /// let yaml = "1:
+    ///   source: Frog
+    ///   content:
+    ///     monolog:
+    ///       text:
+    ///       - KeroKero
+    ///       exit_state: greet_player
+    /// greet_player:
+    ///   source: Frog
+    ///   content:
+    ///     choices:
+    ///     - text: Hi
+    ///       exit_state: 1
+    ///     - text: Bye
+    ///       exit_state: 5\n";
+    ///
+    /// let dialog: Dialog = serde_yaml::from_str(yaml).unwrap();
+    ///
+    /// // After the key `1` and the end `5`
+    /// assert_eq!(dialog.key_of("greet_player"), Some(6));
+    /// assert_eq!(dialog.label(&6), Some(&"greet_player".to_string()));
+    /// assert_eq!(
+    ///     dialog.get(&1).unwrap().content(),
+    ///     &Content::Monolog {
+    ///         text: vec!["KeroKero".to_string()],
+    ///         exit_state: 6
+    ///     }
+    /// );
+    ///
+    /// let serialized = serde_yaml::to_string(&dialog).unwrap();
+    /// assert!(serialized.contains("exit_state: greet_player\n"));
+    /// assert!(serialized.contains("\ngreet_player:\n"));
+    /// assert_eq!(serde_yaml::from_str::<Dialog>(&serialized).unwrap(), dialog);
+    ///
+    /// let error = serde_yaml::from_str::<Dialog>(&yaml.replace("exit_state: greet_player", "exit_state: greet"))
+    ///     .unwrap_err()
+    ///     .to_string();
+    /// assert!(error.starts_with("unknown label `greet` in the node `1`"));
+    /// ```
+    pub fn label(&self, key: &usize) -> Option<&String> {
+        self.labels.get(key)
+    }
+
+    /// Returns the key of the node labeled `label`, if any.
+    pub fn key_of(&self, label: &str) -> Option<usize> {
+        self.labels
+            .iter()
+            .find(|(_, node_label)| *node_label == label)
+            .map(|(key, _)| *key)
+    }
+
+    /// Returns the read-only labels of the nodes, by key.
+    pub fn labels(&self) -> &BTreeMap<usize, String> {
+        &self.labels
+    }
+
+    /// Labels the node `key`, returning its previous label if any.
+    ///
+    /// The label is removed from any other node.
+    /// A number, or a field of the dialog like `name` or `entry`, is not a label.
+    pub fn set_label(&mut self, key: usize, label: String) -> Result<Option<String>, DialogError> {
+        if let NodeKey::Key(_) = NodeKey::from_str(&label) {
+            return Err(DialogError::new(format!("the label `{label}` is a number")));
+        }
+        if METADATA_FIELDS.contains(&label.as_str()) {
+            return Err(DialogError::new(format!(
+                "the label `{label}` is a field of the dialog"
+            )));
+        }
+        self.labels.retain(|_, node_label| *node_label != label);
+        Ok(self.labels.insert(key, label))
+    }
+
+    /// Removes the label of the node `key`, returning it if any.
+    pub fn remove_label(&mut self, key: &usize) -> Option<String> {
+        self.labels.remove(key)
+    }
+
+    /// Returns the number of nodes.
+    pub fn len(&self) -> usize {
+        self.nodes.len()
+    }
+
+    /// Returns `true` if the dialog has no node.
+    pub fn is_empty(&self) -> bool {
+        self.nodes.is_empty()
+    }
+
+    /// Iterates over the nodes, sorted by key.
+    pub fn iter(&self) -> btree_map::Iter<'_, usize, DialogNode<C, E, F>> {
+        self.nodes.iter()
+    }
+
+    /// Iterates over the mutable nodes, sorted by key.
+    pub fn iter_mut(&mut self) -> btree_map::IterMut<'_, usize, DialogNode<C, E, F>> {
+        self.nodes.iter_mut()
+    }
+}
+
+impl<C, E, F> From<BTreeMap<usize, DialogNode<C, E, F>>> for Dialog<C, E, F> {
+    fn from(nodes: BTreeMap<usize, DialogNode<C, E, F>>) -> Self {
+        Dialog::new(nodes)
+    }
+}
+
+impl<C, E, F> From<Dialog<C, E, F>> for BTreeMap<usize, DialogNode<C, E, F>> {
+    fn from(dialog: Dialog<C, E, F>) -> Self {
+        dialog.nodes
+    }
+}
+
+impl<C, E, F> FromIterator<(usize, DialogNode<C, E, F>)> for Dialog<C, E, F> {
+    fn from_iter<T: IntoIterator<Item = (usize, DialogNode<C, E, F>)>>(iter: T) -> Self {
+        Dialog::new(iter.into_iter().collect())
+    }
+}
+
+impl<C, E, F> Extend<(usize, DialogNode<C, E, F>)> for Dialog<C, E, F> {
+    fn extend<T: IntoIterator<Item = (usize, DialogNode<C, E, F>)>>(&mut self, iter: T) {
+        self.nodes.extend(iter)
+    }
+}
+
+impl<C, E, F> IntoIterator for Dialog<C, E, F> {
+    type Item = (usize, DialogNode<C, E, F>);
+    type IntoIter = btree_map::IntoIter<usize, DialogNode<C, E, F>>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.nodes.into_iter()
+    }
+}
+
+impl<'a, C, E, F> IntoIterator for &'a Dialog<C, E, F> {
+    type Item = (&'a usize, &'a DialogNode<C, E, F>);
+    type IntoIter = btree_map::Iter<'a, usize, DialogNode<C, E, F>>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.nodes.iter()
+    }
+}
+
+impl<'a, C, E, F> IntoIterator for &'a mut Dialog<C, E, F> {
+    type Item = (&'a usize, &'a mut DialogNode<C, E, F>);
+    type IntoIter = btree_map::IterMut<'a, usize, DialogNode<C, E, F>>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.nodes.iter_mut()
+    }
+}
+
+/// The metadata shape of a [`Dialog`], only used to serialize it.
+#[derive(Serialize)]
+struct DialogWithMetadata<'a, C, E, F> {
+    version: u32,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    name: &'a Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    default_speaker: &'a Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    entry: Option<ExitState>,
+    nodes: LabeledNodes<'a, C, E, F>,
+}
+
+/// The nodes of a [`Dialog`], written under their label if any.
+struct LabeledNodes<'a, C, E, F> {
+    nodes: &'a BTreeMap<usize, DialogNode<C, E, F>>,
+    /// Writes the numeric keys as strings, for the formats only keying maps by strings.
+    string_keys: bool,
+}
+
+impl<C: Serialize, E: Serialize, F: Serialize> Serialize for LabeledNodes<'_, C, E, F> {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        let mut state = serializer.serialize_map(Some(self.nodes.len()))?;
+        for (key, node) in self.nodes {
+            match label::written_key(*key) {
+                NodeKey::Key(key) if self.string_keys => {
+                    state.serialize_entry(&key.to_string(), node)?
+                }
+                key => state.serialize_entry(&key, node)?,
+            }
+        }
+        state.end()
+    }
+}
+
+impl<C: Serialize, E: Serialize, F: Serialize> Dialog<C, E, F> {
+    /// Writes the dialog, its numeric node keys as strings if `string_keys`.
+    pub(crate) fn serialize_keyed<S>(
+        &self,
+        serializer: S,
+        string_keys: bool,
+    ) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        // The `exit_state`s pointing to a labeled node are written as its label
+        let _labels = LabelGuard::enter(self.labels.clone());
+        let nodes = LabeledNodes {
+            nodes: &self.nodes,
+            string_keys,
+        };
+
+        // A node labeled like a field would be read back as the field
+        let reserved_label = self
+            .labels
+            .values()
+            .any(|label| METADATA_FIELDS.contains(&label.as_str()));
+        if self.name.is_none()
+            && self.default_speaker.is_none()
+            && self.entry.is_none()
+            && !reserved_label
+        {
+            nodes.serialize(serializer)
+        } else {
+            DialogWithMetadata {
+                version: DIALOG_VERSION,
+                name: &self.name,
+                default_speaker: &self.default_speaker,
+                entry: self.entry.map(ExitState),
+                nodes,
+            }
+            .serialize(serializer)
+        }
+    }
+}
+
+impl<C: Serialize, E: Serialize, F: Serialize> Serialize for Dialog<C, E, F> {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        self.serialize_keyed(serializer, false)
+    }
+}
+
+/// A key of the top-level map of a [`Dialog`]: either a metadata field or a node key or label.
+enum DialogKey {
+    Version,
+    Name,
+    DefaultSpeaker,
+    Entry,
+    Nodes,
+    Node(NodeKey),
+}
+
+impl<'de> Deserialize<'de> for DialogKey {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        struct DialogKeyVisitor;
+
+        impl<'de> Visitor<'de> for DialogKeyVisitor {
+            type Value = DialogKey;
+
+            fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+                formatter.write_str(
+                    "a node key or label, or one of `name`, `default_speaker`, `entry`, `nodes`, \
+                    `version`",
+                )
+            }
+
+            fn visit_u64<E: de::Error>(self, value: u64) -> Result<DialogKey, E> {
+                usize::try_from(value)
+                    .map(|key| DialogKey::Node(NodeKey::Key(key)))
+                    .map_err(|_| E::invalid_value(de::Unexpected::Unsigned(value), &self))
+            }
+
+            fn visit_i64<E: de::Error>(self, value: i64) -> Result<DialogKey, E> {
+                usize::try_from(value)
+                    .map(|key| DialogKey::Node(NodeKey::Key(key)))
+                    .map_err(|_| E::invalid_value(de::Unexpected::Signed(value), &self))
+            }
+
+            fn visit_str<E: de::Error>(self, value: &str) -> Result<DialogKey, E> {
+                match value {
+                    "name" => Ok(DialogKey::Name),
+                    "default_speaker" => Ok(DialogKey::DefaultSpeaker),
+                    "entry" => Ok(DialogKey::Entry),
+                    "nodes" => Ok(DialogKey::Nodes),
+                    "version" => Ok(DialogKey::Version),
+                    _ => Ok(DialogKey::Node(NodeKey::from_str(value))),
+                }
+            }
+        }
+
+        deserializer.deserialize_any(DialogKeyVisitor)
+    }
+}
+
+impl<'de, C, E, F> Deserialize<'de> for Dialog<C, E, F>
+where
+    C: Deserialize<'de>,
+    E: Deserialize<'de> + Default,
+    F: Deserialize<'de>,
+{
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        struct DialogVisitor<C, E, F>(PhantomData<(C, E, F)>);
+
+        impl<'de, C, E, F> Visitor<'de> for DialogVisitor<C, E, F>
+        where
+            C: Deserialize<'de>,
+            E: Deserialize<'de> + Default,
+            F: Deserialize<'de>,
+        {
+            type Value = Dialog<C, E, F>;
+
+            fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+                formatter.write_str("a map of dialog nodes")
+            }
+
+            fn visit_map<A>(self, mut map: A) -> Result<Dialog<C, E, F>, A::Error>
+            where
+                A: MapAccess<'de>,
+            {
+                let labels = LabelGuard::enter(BTreeMap::new());
+                let mut dialog = Dialog::default();
+                let mut defined = BTreeMap::new();
+                while let Some(key) = map.next_key()? {
+                    match key {
+                        DialogKey::Version => {
+                            let version: u32 = map.next_value()?;
+                            if version != DIALOG_VERSION {
+                                return Err(de::Error::custom(format!(
+                                    "the dialog is written in version {version}, \
+                                    expected version {DIALOG_VERSION}: migrate it first"
+                                )));
+                            }
+                        }
+                        DialogKey::Name => dialog.name = map.next_value()?,
+                        DialogKey::DefaultSpeaker => dialog.default_speaker = map.next_value()?,
+                        DialogKey::Entry => {
+                            dialog.entry =
+                                map.next_value::<Option<ExitState>>()?.map(|entry| entry.0)
+                        }
+                        DialogKey::Nodes => map.next_value_seed(NodesSeed {
+                            dialog: &mut dialog,
+                            defined: &mut defined,
+                        })?,
+                        DialogKey::Node(NodeKey::Label(label)) => {
+                            // Most likely a typo of a field if it is not a node
+                            let read = || {
+                                map.next_value().map_err(|error| {
+                                    de::Error::custom(format!(
+                                        "unknown field `{label}`, expected one of {}, \
+                                        or a node labeled `{label}`: {error}",
+                                        "`name`, `default_speaker`, `entry`, `nodes`, `version`"
+                                    ))
+                                })
+                            };
+                            dialog.insert_read_node(
+                                &mut defined,
+                                NodeKey::Label(label.clone()),
+                                read,
+                            )?
+                        }
+                        DialogKey::Node(key) => {
+                            dialog.insert_read_node(&mut defined, key, || map.next_value())?
+                        }
+                    }
+                }
+                dialog.resolve_labels(labels.finish(), defined)
+            }
+        }
+
+        // Any rather than a map: RON writes the metadata shape as a struct
+        deserializer.deserialize_any(DialogVisitor(PhantomData))
+    }
+}
+
+/// Reads the map under `nodes:` into the `dialog`.
+struct NodesSeed<'a, C, E, F> {
+    dialog: &'a mut Dialog<C, E, F>,
+    defined: &'a mut BTreeMap<usize, String>,
+}
+
+impl<'de, C, E, F> de::DeserializeSeed<'de> for NodesSeed<'_, C, E, F>
+where
+    C: Deserialize<'de>,
+    E: Deserialize<'de> + Default,
+    F: Deserialize<'de>,
+{
+    type Value = ();
+
+    fn deserialize<D>(self, deserializer: D) -> Result<(), D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        deserializer.deserialize_map(self)
+    }
+}
+
+impl<'de, C, E, F> Visitor<'de> for NodesSeed<'_, C, E, F>
+where
+    C: Deserialize<'de>,
+    E: Deserialize<'de> + Default,
+    F: Deserialize<'de>,
+{
+    type Value = ();
+
+    fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        formatter.write_str("a map of dialog nodes")
+    }
+
+    fn visit_map<A>(self, mut map: A) -> Result<(), A::Error>
+    where
+        A: MapAccess<'de>,
+    {
+        while let Some(key) = map.next_key()? {
+            self.dialog
+                .insert_read_node(self.defined, key, || map.next_value())?;
+        }
+        Ok(())
+    }
+}
+
+impl<C, E, F> Dialog<C, E, F> {
+    /// Inserts the node given by `read` at `key`, a label being given its provisional key.
+    fn insert_read_node<Err: de::Error>(
+        &mut self,
+        defined: &mut BTreeMap<usize, String>,
+        key: NodeKey,
+        read: impl FnOnce() -> Result<DialogNode<C, E, F>, Err>,
+    ) -> Result<(), Err> {
+        label::set_current_node(key.to_string());
+        let node = read()?;
+        let provisional = match &key {
+            NodeKey::Key(provisional) if *provisional >= PROVISIONAL_KEY => {
+                return Err(Err::custom(format!("the node key `{key}` is too large")));
+            }
+            NodeKey::Key(provisional) => *provisional,
+            NodeKey::Label(label) => {
+                let provisional =
+                    label::provisional_key(label).expect("nodes are read within a label scope");
+                defined.insert(provisional, label.clone());
+                provisional
+            }
+        };
+        if self.nodes.insert(provisional, node).is_some() {
+            return Err(Err::custom(format!("duplicate node key `{key}`")));
+        }
+        Ok(())
+    }
+
+    /// Reports the unknown labels and gives the labeled nodes the keys following the numeric ones.
+    fn resolve_labels<Err: de::Error>(
+        mut self,
+        scope: LabelScope,
+        defined: BTreeMap<usize, String>,
+    ) -> Result<Self, Err> {
+        for (label, provisional) in &scope.provisional {
+            if !defined.contains_key(provisional) {
+                return Err(Err::custom(match scope.referenced_by.get(label) {
+                    Some(node) if !node.is_empty() => {
+                        format!("unknown label `{label}` in the node `{node}`")
+                    }
+                    _ => format!("unknown label `{label}`"),
+                }));
+            }
+        }
+
+        // Above any numeric `exit_state` too: it would be an end of the dialog
+        let first = self
+            .nodes
+            .iter()
+            .flat_map(|(key, node)| node.exit_states().into_iter().chain([*key]))
+            .chain(self.entry)
+            .filter(|key| *key < PROVISIONAL_KEY)
+            .max()
+            .map_or(0, |key| key + 1);
+        let keys: BTreeMap<usize, usize> = defined
+            .keys()
+            .enumerate()
+            .map(|(index, provisional)| (*provisional, first + index))
+            .collect();
+
+        self.nodes = mem::take(&mut self.nodes)
+            .into_iter()
+            .map(|(key, mut node)| {
+                label::renumber_exits(&mut node, &keys);
+                (keys.get(&key).copied().unwrap_or(key), node)
+            })
+            .collect();
+        self.entry = self
+            .entry
+            .map(|entry| keys.get(&entry).copied().unwrap_or(entry));
+        self.labels = defined
+            .into_iter()
+            .map(|(provisional, label)| (keys[&provisional], label))
+            .collect();
+        Ok(self)
+    }
+}
 
 /// This correspond to a unique key
-#[derive(Serialize, Deserialize, Clone, Eq, PartialEq, Ord, PartialOrd, Hash, Debug, Default)]
-#[serde(default)]
-pub struct DialogNode {
+#[derive(Serialize, Deserialize, Clone, Eq, PartialEq, Ord, PartialOrd, Hash, Debug)]
+#[serde(
+    default,
+    bound(deserialize = "C: Deserialize<'de>, E: Deserialize<'de> + Default, F: Deserialize<'de>")
+)]
+pub struct DialogNode<C = Condition, E = (), F = String> {
     source: String,
-    content: Content,
-    /// REFACTOR: Turn this into a generic type `extra`
+    /// A note for the writers and translators, see [`Dialog::to_po_string`].
+    #[serde(skip_serializing_if = "Option::is_none")]
+    note: Option<String>,
+    content: Content<C, F>,
+    /// The string ids of the lines of its monolog, see [`Dialog::text_id`].
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    line_ids: Vec<Option<String>>,
     trigger_event: Vec<String>,
+    /// Applied when the node is entered.
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    effects: Vec<Effect<F>>,
+    /// Any data attached to the node: camera shot, portrait emotion, audio cue...
+    #[serde(skip_serializing_if = "is_zero_sized")]
+    extra: E,
+}
+
+/// A zero-sized `extra`, like the default `()`, carries nothing and is not written.
+fn is_zero_sized<E>(_: &E) -> bool {
+    std::mem::size_of::<E>() == 0
 }
 
-impl DialogNode {
+impl<C, E: Default, F> Default for DialogNode<C, E, F> {
+    fn default() -> Self {
+        DialogNode {
+            source: String::default(),
+            note: None,
+            content: Content::default(),
+            line_ids: Vec::default(),
+            trigger_event: Vec::default(),
+            effects: Vec::default(),
+            extra: E::default(),
+        }
+    }
+}
+
+impl<C> DialogNode<C> {
     /// Constructs a new DialogNode with the given
     /// - `source`,
     /// - `content`,
     /// - `trigger_event` vector
-    pub fn new(source: String, content: Content, trigger_event: Vec<String>) -> Self {
+    ///
+    /// and without `effects` nor `extra`,
+    /// see [`DialogNode::with_effects`] and [`DialogNode::with_extra`].
+    pub fn new(source: String, content: Content<C>, trigger_event: Vec<String>) -> Self {
         DialogNode {
             source,
+            note: None,
             content,
+            line_ids: Vec::new(),
             trigger_event,
+            effects: Vec::new(),
+            extra: (),
+        }
+    }
+}
+
+impl<C, E, F> DialogNode<C, E, F> {
+    /// Sets the `effects` applied when the node is entered.
+    pub fn with_effects(mut self, effects: Vec<Effect<F>>) -> Self {
+        self.effects = effects;
+        self
+    }
+
+    /// Sets the `note` for the writers and translators.
+    pub fn with_note(mut self, note: String) -> Self {
+        self.note = Some(note);
+        self
+    }
+
+    /// Sets the string ids of the lines of its monolog, in order.
+    ///
+    /// A line without id, or past the end of `line_ids`, gets a generated one:
+    /// see [`Dialog::text_id`].
+    pub fn with_line_ids(mut self, line_ids: Vec<Option<String>>) -> Self {
+        self.line_ids = line_ids;
+        self
+    }
+
+    /// Replaces the `extra` data of the node.
+    ///
+    /// ```rust
+    /// use serde::{Deserialize, Serialize};
+    /// use yml_dialog::*;
+    ///
+    /// #[derive(Serialize, Deserialize, Clone, PartialEq, Debug, Default)]
+    /// struct Staging {
+    ///     portrait: String,
+    ///     audio_cue: Option<String>,
+    /// }
+    ///
+    /// let node = DialogNode::new(
+    ///     "Narrator".to_string(),
+    ///     Content::Monolog {
+    ///         text: vec!["Deserved.".to_string()],
+    ///         exit_state: 7,
+    ///     },
+    ///     vec![],
+    /// )
+    /// .with_extra(Staging {
+    ///     portrait: "smirk".to_string(),
+    ///     audio_cue: None,
+    /// });
+    ///
+    /// let yaml = serde_yaml::to_string(&node).unwrap();
+    /// assert!(yaml.ends_with("extra:\n  portrait: smirk\n  audio_cue: null\n"));
+    /// assert_eq!(
+    ///     serde_yaml::from_str::<DialogNode<Condition, Staging>>(&yaml).unwrap(),
+    ///     node
+    /// );
+    /// ```
+    pub fn with_extra<X>(self, extra: X) -> DialogNode<C, X, F> {
+        DialogNode {
+            source: self.source,
+            note: self.note,
+            content: self.content,
+            line_ids: self.line_ids,
+            trigger_event: self.trigger_event,
+            effects: self.effects,
+            extra,
         }
     }
 
@@ -52,16 +980,36 @@ impl DialogNode {
         &mut self.source
     }
 
+    /// Give the read-only `note` of the node.
+    pub fn note(&self) -> &Option<String> {
+        &self.note
+    }
+
+    /// Give the mutable `note` of the node.
+    pub fn note_mut(&mut self) -> &mut Option<String> {
+        &mut self.note
+    }
+
     /// Give the read-only `content` of the node.
-    pub fn content(&self) -> &Content {
+    pub fn content(&self) -> &Content<C, F> {
         &self.content
     }
 
     /// Give the mutable `content` of the node.
-    pub fn content_mut(&mut self) -> &mut Content {
+    pub fn content_mut(&mut self) -> &mut Content<C, F> {
         &mut self.content
     }
 
+    /// Give the read-only string ids of the lines of its monolog.
+    pub fn line_ids(&self) -> &Vec<Option<String>> {
+        &self.line_ids
+    }
+
+    /// Give the mutable string ids of the lines of its monolog.
+    pub fn line_ids_mut(&mut self) -> &mut Vec<Option<String>> {
+        &mut self.line_ids
+    }
+
     /// Give the read-only `trigger_event` of the node.
     pub fn trigger_event(&self) -> &Vec<String> {
         &self.trigger_event
@@ -71,6 +1019,34 @@ impl DialogNode {
     pub fn trigger_event_mut(&mut self) -> &mut Vec<String> {
         &mut self.trigger_event
     }
+
+    /// Returns the `exit_state` of its monolog, or the ones of its choices in order.
+    pub fn exit_states(&self) -> Vec<usize> {
+        match &self.content {
+            Content::Monolog { exit_state, .. } => vec![*exit_state],
+            Content::Choices(choices) => choices.iter().map(|choice| choice.exit_state).collect(),
+        }
+    }
+
+    /// Give the read-only `effects` of the node.
+    pub fn effects(&self) -> &Vec<Effect<F>> {
+        &self.effects
+    }
+
+    /// Give the mutable `effects` of the node.
+    pub fn effects_mut(&mut self) -> &mut Vec<Effect<F>> {
+        &mut self.effects
+    }
+
+    /// Give the read-only `extra` data of the node.
+    pub fn extra(&self) -> &E {
+        &self.extra
+    }
+
+    /// Give the mutable `extra` data of the node.
+    pub fn extra_mut(&mut self) -> &mut E {
+        &mut self.extra
+    }
 }
 
 /// A Node is either a list of choice or a monolog
@@ -79,7 +1055,7 @@ impl DialogNode {
 /// use yml_dialog::*;
 /// use std::collections::BTreeMap;
 ///
-/// let mut map = BTreeMap::new();
+/// let mut map: BTreeMap<usize, DialogNode> = BTreeMap::new();
 /// map.insert(
 ///     1,
 ///     DialogNode::new(
@@ -120,26 +1096,29 @@ impl DialogNode {
 ///     "1:
 ///   source: The Frog
 ///   content:
-///   - text: Hello HomeGirl
-///     condition: null
-///     exit_state: 2
-///   - text: KeroKero
-///     condition: null
-///     exit_state: 3
+///     choices:
+///     - text: Hello HomeGirl
+///       condition: null
+///       exit_state: 2
+///     - text: KeroKero
+///       condition: null
+///       exit_state: 3
 ///   trigger_event: []
 /// 2:
 ///   source: Random Frog
 ///   content:
-///     text:
-///     - Yo Homie
-///     exit_state: 4
+///     monolog:
+///       text:
+///       - Yo Homie
+///       exit_state: 4
 ///   trigger_event: []
 /// 3:
 ///   source: Random Frog
 ///   content:
-///     text:
-///     - KeroKero
-///     exit_state: 4
+///     monolog:
+///       text:
+///       - KeroKero
+///       exit_state: 4
 ///   trigger_event: []\n"
 ///         .to_string()
 ///     )
@@ -147,12 +1126,38 @@ impl DialogNode {
 ///
 /// # Note
 ///
-/// TODO: Custom impl Serialization
-#[derive(Serialize, Deserialize, Clone, Eq, PartialEq, Ord, PartialOrd, Hash, Debug)]
-#[serde(untagged)]
-pub enum Content {
+/// The untagged shape, a list of choices or a `text` and an `exit_state`
+/// directly under `content:`, is still accepted.
+///
+/// ```rust
+/// use yml_dialog::*;
+///
+/// let tagged: Content = serde_yaml::from_str(
+///     "monolog:
+///   text:
+///   - Yo Homie
+///   exit_state: 4\n",
+/// )
+/// .unwrap();
+/// let untagged: Content = serde_yaml::from_str(
+///     "text:
+/// - Yo Homie
+/// exit_state: 4\n",
+/// )
+/// .unwrap();
+///
+/// assert_eq!(tagged, untagged);
+///
+/// let error = serde_yaml::from_str::<Content>("monolog:\n  text: Yo Homie\n")
+///     .unwrap_err()
+///     .to_string();
+/// assert!(error.starts_with("invalid monolog: "));
+/// assert!(error.contains("invalid type: string \"Yo Homie\", expected a sequence"));
+/// ```
+#[derive(Clone, Eq, PartialEq, Ord, PartialOrd, Hash, Debug)]
+pub enum Content<C = Condition, F = String> {
     /// A vector of Choice
-    Choices(Vec<Choice>),
+    Choices(Vec<Choice<C, F>>),
     /// A text block, containing a vector of text line
     /// and an exit state
     Monolog {
@@ -163,7 +1168,7 @@ pub enum Content {
     },
 }
 
-impl Default for Content {
+impl<C, F> Default for Content<C, F> {
     fn default() -> Self {
         Content::Monolog {
             text: vec![],
@@ -172,57 +1177,265 @@ impl Default for Content {
     }
 }
 
-// impl Serialize for Content {
-//     fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
-//     where
-//         S: Serializer,
-//     {
-//         match self {
-//             Content::Choices(choices) => {
-//                 let state = serializer.serialize_newtype_variant("", 0, "choices", choices)?;
-//                 Ok(state)
-//                 // state.end()
-//             }
-//             Content::Monolog { text, exit_state } => {
-//                 let mut state = serializer.serialize_struct_variant("", 1, "monolog", 2)?;
-//                 state.serialize_field("text", text)?;
-//                 state.serialize_field("state", exit_state)?;
-//                 state.end()
-//             }
-//         }
-//     }
-// }
+/// The fields of a `Content::Monolog`.
+#[derive(Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct Monolog<T> {
+    text: T,
+    #[serde(with = "label::exit_state")]
+    exit_state: usize,
+}
+
+impl<C: Serialize, F: Serialize> Serialize for Content<C, F> {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        // A map rather than an enum variant: some formats, like YAML, would write a `!tag`
+        let mut state = serializer.serialize_map(Some(1))?;
+        match self {
+            Content::Choices(choices) => state.serialize_entry("choices", choices)?,
+            Content::Monolog { text, exit_state } => state.serialize_entry(
+                "monolog",
+                &Monolog {
+                    text,
+                    exit_state: *exit_state,
+                },
+            )?,
+        }
+        state.end()
+    }
+}
+
+/// A key of the map of a [`Content`]: either a tag or a field of the untagged monolog.
+#[derive(Deserialize, PartialEq)]
+#[serde(field_identifier, rename_all = "snake_case")]
+enum ContentField {
+    Monolog,
+    Choices,
+    Text,
+    ExitState,
+}
+
+/// A map key naming a field, read from an identifier or a string.
+///
+/// Formats like RON only read identifiers as fields, but write the keys of a map as strings.
+pub(crate) struct FieldKey<T>(pub(crate) T);
+
+impl<'de, T: Deserialize<'de>> Deserialize<'de> for FieldKey<T> {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        T::deserialize(IdentifierAsStr(deserializer)).map(FieldKey)
+    }
+}
+
+/// Reads an identifier as a string.
+struct IdentifierAsStr<D>(D);
+
+impl<'de, D: Deserializer<'de>> Deserializer<'de> for IdentifierAsStr<D> {
+    type Error = D::Error;
+
+    fn deserialize_any<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, D::Error> {
+        self.0.deserialize_str(visitor)
+    }
+
+    serde::forward_to_deserialize_any! {
+        bool i8 i16 i32 i64 i128 u8 u16 u32 u64 u128 f32 f64 char str string
+        bytes byte_buf option unit unit_struct newtype_struct seq tuple
+        tuple_struct map struct enum identifier ignored_any
+    }
+}
+
+/// Prefixes the `error` with the variant which failed.
+fn variant_error<E: de::Error>(variant: &str, error: impl fmt::Display) -> E {
+    E::custom(format!("invalid {variant}: {error}"))
+}
+
+impl<'de, C: Deserialize<'de>, F: Deserialize<'de>> Deserialize<'de> for Content<C, F> {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        struct ContentVisitor<C, F>(PhantomData<(C, F)>);
+
+        impl<'de, C: Deserialize<'de>, F: Deserialize<'de>> Visitor<'de> for ContentVisitor<C, F> {
+            type Value = Content<C, F>;
+
+            fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+                formatter.write_str("`monolog:`, `choices:`, a list of choices or a monolog")
+            }
+
+            fn visit_seq<A>(self, seq: A) -> Result<Content<C, F>, A::Error>
+            where
+                A: SeqAccess<'de>,
+            {
+                Vec::deserialize(de::value::SeqAccessDeserializer::new(seq))
+                    .map(Content::Choices)
+                    .map_err(|error| variant_error("choices", error))
+            }
+
+            fn visit_map<A>(self, mut map: A) -> Result<Content<C, F>, A::Error>
+            where
+                A: MapAccess<'de>,
+            {
+                let mut tagged = None;
+                let mut text = None;
+                let mut exit_state = None;
+
+                while let Some(FieldKey(field)) = map.next_key()? {
+                    if tagged.is_some()
+                        || (text.is_some() || exit_state.is_some())
+                            && matches!(field, ContentField::Monolog | ContentField::Choices)
+                    {
+                        return Err(de::Error::custom(
+                            "`monolog` or `choices` must be the only field of the content",
+                        ));
+                    }
+                    match field {
+                        ContentField::Monolog => {
+                            let monolog: Monolog<Vec<String>> = map
+                                .next_value()
+                                .map_err(|error| variant_error("monolog", error))?;
+                            tagged = Some(Content::Monolog {
+                                text: monolog.text,
+                                exit_state: monolog.exit_state,
+                            });
+                        }
+                        ContentField::Choices => {
+                            tagged = Some(Content::Choices(
+                                map.next_value()
+                                    .map_err(|error| variant_error("choices", error))?,
+                            ));
+                        }
+                        ContentField::Text => {
+                            if text.is_some() {
+                                return Err(variant_error("monolog", "duplicate field `text`"));
+                            }
+                            text = Some(
+                                map.next_value()
+                                    .map_err(|error| variant_error("monolog", error))?,
+                            );
+                        }
+                        ContentField::ExitState => {
+                            if exit_state.is_some() {
+                                return Err(variant_error(
+                                    "monolog",
+                                    "duplicate field `exit_state`",
+                                ));
+                            }
+                            exit_state = Some(
+                                map.next_value::<ExitState>()
+                                    .map_err(|error| variant_error("monolog", error))?
+                                    .0,
+                            );
+                        }
+                    }
+                }
+
+                match (tagged, text, exit_state) {
+                    (Some(content), _, _) => Ok(content),
+                    (None, None, None) => Err(de::Error::custom(
+                        "expected `monolog:` or `choices:` in the content",
+                    )),
+                    (None, text, exit_state) => Ok(Content::Monolog {
+                        text: text
+                            .ok_or_else(|| variant_error("monolog", "missing field `text`"))?,
+                        exit_state: exit_state.ok_or_else(|| {
+                            variant_error("monolog", "missing field `exit_state`")
+                        })?,
+                    }),
+                }
+            }
+        }
+
+        deserializer.deserialize_any(ContentVisitor(PhantomData))
+    }
+}
 
 /// A Choice is composed of
+/// - an optional string `id`, see [`Dialog::text_id`],
 /// - a `text` line,
 /// - a `condition` and
-/// - an `exit_state` corresponding to the continue of this choice.
-#[derive(Serialize, Deserialize, Clone, Eq, PartialEq, Ord, PartialOrd, Hash, Debug, Default)]
+/// - an `exit_state` corresponding to the continue of this choice,
+/// - some `effects` applied when it is picked.
+///
+/// The `condition` can be any type implementing [`DialogCondition`],
+/// [`Condition`] by default.
+#[derive(Serialize, Deserialize, Clone, Eq, PartialEq, Ord, PartialOrd, Hash, Debug)]
 #[serde(default)]
-pub struct Choice {
+pub struct Choice<C = Condition, F = String> {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    id: Option<String>,
     text: String,
-    /// REFACTOR: Custom Generic Type
-    condition: Option<Condition>,
+    condition: Option<C>,
     /// if the `exit_state` is not a key from the Map, it's a end node.
+    #[serde(with = "label::exit_state")]
     exit_state: usize,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    effects: Vec<Effect<F>>,
+}
+
+impl<C, F> Default for Choice<C, F> {
+    fn default() -> Self {
+        Choice {
+            id: None,
+            text: String::default(),
+            condition: None,
+            exit_state: usize::default(),
+            effects: Vec::default(),
+        }
+    }
 }
 
-impl Choice {
+impl<C: DialogCondition, F> Choice<C, F> {
+    /// Retruns the bool: The choice's condition is verified with the given `context`.
+    ///
+    /// A choice without condition is always verified.
+    pub fn is_verified(&self, context: &C::Context) -> bool {
+        match &self.condition {
+            None => true,
+            Some(condition) => condition.is_verified(context),
+        }
+    }
+}
+
+impl<C> Choice<C> {
     /// Constructs a new Choice with the given `text`, `condition`, `exit_state`,
-    pub fn new(text: String, condition: Option<Condition>, exit_state: usize) -> Self {
+    /// and without `effects`, see [`Choice::with_effects`].
+    pub fn new(text: String, condition: Option<C>, exit_state: usize) -> Self {
         Choice {
+            id: None,
             text,
             condition,
             exit_state,
+            effects: Vec::new(),
         }
     }
+}
 
-    /// Retruns the bool: The choice's condition is verified with the given `karma` and the set of `active_event`
-    pub fn is_verified(&self, karma: Option<i32>, active_events: Vec<String>) -> bool {
-        match &self.condition {
-            None => self.condition.is_none(),
-            Some(condition) => condition.is_verified(karma, active_events),
-        }
+impl<C, F> Choice<C, F> {
+    /// Sets the `effects` applied when the choice is picked.
+    pub fn with_effects(mut self, effects: Vec<Effect<F>>) -> Self {
+        self.effects = effects;
+        self
+    }
+
+    /// Sets the string `id` of the choice.
+    pub fn with_id(mut self, id: String) -> Self {
+        self.id = Some(id);
+        self
+    }
+
+    /// Returns the read-only string `id` of the choice
+    pub fn id(&self) -> &Option<String> {
+        &self.id
+    }
+
+    /// Returns the mutable string `id` of the choice
+    pub fn id_mut(&mut self) -> &mut Option<String> {
+        &mut self.id
     }
 
     /// Returns the read-only `text` of the choice
@@ -236,12 +1449,12 @@ impl Choice {
     }
 
     /// Returns the read-only `condition` of the choice
-    pub fn condition(&self) -> &Option<Condition> {
+    pub fn condition(&self) -> &Option<C> {
         &self.condition
     }
 
     /// Returns the mutable `condition` of the choice
-    pub fn condition_mut(&mut self) -> &mut Option<Condition> {
+    pub fn condition_mut(&mut self) -> &mut Option<C> {
         &mut self.condition
     }
 
@@ -254,6 +1467,16 @@ impl Choice {
     pub fn exit_state_mut(&mut self) -> &mut usize {
         &mut self.exit_state
     }
+
+    /// Returns the read-only `effects` of the choice
+    pub fn effects(&self) -> &Vec<Effect<F>> {
+        &self.effects
+    }
+
+    /// Returns the mutable `effects` of the choice
+    pub fn effects_mut(&mut self) -> &mut Vec<Effect<F>> {
+        &mut self.effects
+    }
 }
 
 // impl Serialize for Choice {
@@ -270,11 +1493,239 @@ impl Choice {
 //     }
 // }
 
-/// REFACTOR: Turn this into a Generic Type
+/// A predicate deciding if a [`Choice`] is available.
+///
+/// Implement it to plug the state of your game into the dialog:
+///
+/// ```rust
+/// use serde::{Deserialize, Serialize};
+/// use yml_dialog::*;
+///
+/// struct Inventory {
+///     items: Vec<String>,
+/// }
+///
+/// #[derive(Serialize, Deserialize, Clone, PartialEq, Debug)]
+/// struct HasItem(String);
+///
+/// impl DialogCondition for HasItem {
+///     type Context = Inventory;
+///
+///     fn is_verified(&self, inventory: &Inventory) -> bool {
+///         inventory.items.contains(&self.0)
+///     }
+/// }
+///
+/// let choice: Choice<HasItem> =
+///     serde_yaml::from_str("text: Give the key\ncondition: Key\nexit_state: 2\n").unwrap();
+///
+/// assert!(choice.is_verified(&Inventory {
+///     items: vec!["Key".to_string()]
+/// }));
+/// assert!(!choice.is_verified(&Inventory { items: vec![] }));
+/// ```
+pub trait DialogCondition {
+    /// What the condition is verified against.
+    type Context: ?Sized;
+
+    /// Returns `true` if the condition is verified within the given `context`.
+    fn is_verified(&self, context: &Self::Context) -> bool;
+}
+
+/// The state of the game the default [`Condition`] is verified against.
+///
+/// It (de)serializes, to be stored in a save game.
 #[derive(Serialize, Deserialize, Clone, Eq, PartialEq, Ord, PartialOrd, Hash, Debug, Default)]
+pub struct ConditionContext {
+    karma: Option<i32>,
+    active_events: Vec<String>,
+    #[serde(default, skip_serializing_if = "Variables::is_empty")]
+    variables: Variables,
+}
+
+impl ConditionContext {
+    /// Constructs a new ConditionContext with the potential `karma` and the set of `active_events`.
+    pub fn new(karma: Option<i32>, active_events: Vec<String>) -> Self {
+        ConditionContext {
+            karma,
+            active_events,
+            variables: Variables::new(),
+        }
+    }
+
+    /// Sets the `variables` of the context.
+    pub fn with_variables(mut self, variables: Variables) -> Self {
+        self.variables = variables;
+        self
+    }
+
+    /// Returns the read-only `karma` of the context.
+    pub fn karma(&self) -> &Option<i32> {
+        &self.karma
+    }
+
+    /// Returns the mutable `karma` of the context.
+    pub fn karma_mut(&mut self) -> &mut Option<i32> {
+        &mut self.karma
+    }
+
+    /// Returns the read-only `active_events` of the context.
+    pub fn active_events(&self) -> &Vec<String> {
+        &self.active_events
+    }
+
+    /// Returns the mutable `active_events` of the context.
+    pub fn active_events_mut(&mut self) -> &mut Vec<String> {
+        &mut self.active_events
+    }
+
+    /// Returns the read-only `variables` of the context.
+    pub fn variables(&self) -> &Variables {
+        &self.variables
+    }
+
+    /// Returns the mutable `variables` of the context.
+    pub fn variables_mut(&mut self) -> &mut Variables {
+        &mut self.variables
+    }
+
+    /// Applies the `effect` on the context:
+    /// - `set_event` and `clear_event` on the `active_events`,
+    /// - `add_karma` on the `karma`, starting from 0 if there is none,
+    /// - `set_var` on the `variables`.
+    ///
+    /// Returns the payload of a `custom` effect, left to the game.
+    ///
+    /// ```rust
+    /// use yml_dialog::*;
+    ///
+    /// let mut context = ConditionContext::default();
+    /// let effects: Vec<Effect> = serde_yaml::from_str(
+    ///     "- set_event: FrogLove
+    /// - add_karma: 2
+    /// - set_var:
+    ///     name: fear
+    ///     value: 0.5
+    /// - custom: ShakeCamera\n",
+    /// )
+    /// .unwrap();
+    ///
+    /// let custom: Vec<&String> = effects
+    ///     .iter()
+    ///     .filter_map(|effect| context.apply(effect))
+    ///     .collect();
+    ///
+    /// assert_eq!(custom, vec!["ShakeCamera"]);
+    /// assert_eq!(context.karma(), &Some(2));
+    /// assert_eq!(context.variables().get("fear"), Some(&Value::Float(0.5)));
+    /// assert!(Condition::default()
+    ///     .with_expression("has(FrogLove) && fear < 1".parse().unwrap())
+    ///     .is_verified_within(&context));
+    /// ```
+    pub fn apply<'a, F>(&mut self, effect: &'a Effect<F>) -> Option<&'a F> {
+        match effect {
+            Effect::SetEvent(event) => {
+                if !self.active_events.contains(event) {
+                    self.active_events.push(event.clone());
+                }
+            }
+            Effect::ClearEvent(event) => self.active_events.retain(|active| active != event),
+            Effect::AddKarma(amount) => {
+                self.karma = Some(self.karma.unwrap_or_default().saturating_add(*amount))
+            }
+            Effect::SetVar { name, value } => {
+                self.variables.set(name.clone(), value.clone());
+            }
+            Effect::Custom(custom) => return Some(custom),
+        }
+        None
+    }
+}
+
+/// The default condition of a [`Choice`]: a karma range, a set of events which must be active,
+/// ranges of [`Variables`] and an optional [`Expression`].
+///
+/// It deserializes either from its fields or from an expression alone.
+///
+/// ```rust
+/// use yml_dialog::*;
+///
+/// let condition: Condition =
+///     serde_yaml::from_str("events: [FrogLove]\nexpression: karma >= 10 || has(FrogTalk)\n").unwrap();
+/// let shorthand: Condition = serde_yaml::from_str("'has(FrogLove) && !has(FrogHate)'").unwrap();
+///
+/// assert!(condition.is_verified(Some(12), vec!["FrogLove".to_string()]));
+/// assert!(!condition.is_verified(Some(3), vec!["FrogLove".to_string()]));
+/// assert!(shorthand.is_verified(None, vec!["FrogLove".to_string()]));
+/// assert_eq!(shorthand.expression().as_ref().unwrap().to_string(), "has(FrogLove) && !has(FrogHate)");
+///
+/// let trusted: Condition = serde_yaml::from_str("thresholds:\n  trust: [2, 10]\n").unwrap();
+/// let context = ConditionContext::default().with_variables(Variables::from_iter([("trust", 5)]));
+///
+/// assert!(trusted.is_verified_within(&context));
+/// assert!(!trusted.is_verified_within(&ConditionContext::default()));
+/// ```
+#[derive(Serialize, Clone, Eq, PartialEq, Ord, PartialOrd, Hash, Debug, Default)]
 pub struct Condition {
     events: Vec<String>,
     karma_threshold: Option<(i32, i32)>,
+    #[serde(skip_serializing_if = "BTreeMap::is_empty")]
+    thresholds: BTreeMap<String, (Value, Value)>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    expression: Option<Expression>,
+}
+
+/// The fields of a [`Condition`] written as a map.
+#[derive(Deserialize, Default)]
+#[serde(default)]
+struct ConditionFields {
+    events: Vec<String>,
+    karma_threshold: Option<(i32, i32)>,
+    thresholds: BTreeMap<String, (Value, Value)>,
+    expression: Option<Expression>,
+}
+
+impl<'de> Deserialize<'de> for Condition {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        struct ConditionVisitor;
+
+        impl<'de> Visitor<'de> for ConditionVisitor {
+            type Value = Condition;
+
+            fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+                formatter.write_str("a condition: its fields or an expression")
+            }
+
+            fn visit_str<Err>(self, input: &str) -> Result<Condition, Err>
+            where
+                Err: de::Error,
+            {
+                let expression = input.parse().map_err(|error| {
+                    de::Error::custom(format!("invalid expression `{input}`: {error}"))
+                })?;
+                Ok(Condition::default().with_expression(expression))
+            }
+
+            fn visit_map<M>(self, map: M) -> Result<Condition, M::Error>
+            where
+                M: MapAccess<'de>,
+            {
+                let fields =
+                    ConditionFields::deserialize(de::value::MapAccessDeserializer::new(map))?;
+                Ok(Condition {
+                    events: fields.events,
+                    karma_threshold: fields.karma_threshold,
+                    thresholds: fields.thresholds,
+                    expression: fields.expression,
+                })
+            }
+        }
+
+        deserializer.deserialize_any(ConditionVisitor)
+    }
 }
 
 impl Condition {
@@ -283,9 +1734,23 @@ impl Condition {
         Condition {
             karma_threshold,
             events,
+            thresholds: BTreeMap::new(),
+            expression: None,
         }
     }
 
+    /// Adds a range, bounds included, the variable `name` must be within.
+    pub fn with_threshold(mut self, name: String, threshold: (Value, Value)) -> Self {
+        self.thresholds.insert(name, threshold);
+        self
+    }
+
+    /// Adds an `expression` which must be verified too.
+    pub fn with_expression(mut self, expression: Expression) -> Self {
+        self.expression = Some(expression);
+        self
+    }
+
     /// Only check if the given `karma` is within the range of the condition
     ///
     /// # Note
@@ -315,14 +1780,36 @@ impl Condition {
     }
 
     /// Verify a Choice's condition with the potential tested karma and the set of active event
+    ///
+    /// There is no variable: use [`Condition::is_verified_within`] to check the `thresholds`.
     pub fn is_verified(&self, karma: Option<i32>, active_events: Vec<String>) -> bool {
-        let karma_verified = match karma {
+        self.is_verified_within(&ConditionContext::new(karma, active_events))
+    }
+
+    /// Verify a Choice's condition within the whole `context`, variables included.
+    ///
+    /// A threshold on a variable missing from the context is not verified.
+    pub fn is_verified_within(&self, context: &ConditionContext) -> bool {
+        let karma_verified = match context.karma {
             None => self.karma_threshold.is_none(),
             Some(karma) => self.is_karma_verified(karma),
         };
-        let events_verified = self.is_events_verified(active_events);
+        let events_verified = self
+            .events
+            .iter()
+            .all(|event| context.active_events.contains(event));
+        let thresholds_verified = self.thresholds.iter().all(|(name, (min, max))| {
+            context.variable(name).is_some_and(|value| {
+                Comparison::GreaterOrEqual.compare(&value, min)
+                    && Comparison::LessOrEqual.compare(&value, max)
+            })
+        });
+        let expression_verified = self
+            .expression
+            .as_ref()
+            .is_none_or(|expression| expression.evaluate(context));
 
-        karma_verified && events_verified
+        karma_verified && events_verified && thresholds_verified && expression_verified
     }
 
     /// Returns the read-only `karma_threshold` item of the `Condition`.
@@ -344,4 +1831,82 @@ impl Condition {
     pub fn events_mut(&mut self) -> &Vec<String> {
         &mut self.events
     }
+
+    /// Returns the read-only `thresholds` of the `Condition`: a range by variable.
+    pub fn thresholds(&self) -> &BTreeMap<String, (Value, Value)> {
+        &self.thresholds
+    }
+
+    /// Returns the mutable `thresholds` of the `Condition`.
+    pub fn thresholds_mut(&mut self) -> &mut BTreeMap<String, (Value, Value)> {
+        &mut self.thresholds
+    }
+
+    /// Returns the read-only `expression` of the `Condition`.
+    pub fn expression(&self) -> &Option<Expression> {
+        &self.expression
+    }
+
+    /// Returns the mutable `expression` of the `Condition`.
+    pub fn expression_mut(&mut self) -> &mut Option<Expression> {
+        &mut self.expression
+    }
+}
+
+impl DialogCondition for Condition {
+    type Context = ConditionContext;
+
+    fn is_verified(&self, context: &ConditionContext) -> bool {
+        self.is_verified_within(context)
+    }
+}
+
+impl fmt::Display for Condition {
+    /// Writes the condition in the [`Expression`] language, `true` if it always holds.
+    ///
+    /// ```rust
+    /// use yml_dialog::*;
+    ///
+    /// let condition = Condition::new(Some((0, 10)), vec!["FrogHate".to_string()])
+    ///     .with_expression("has(FrogLove) || fear < 0.5".parse().unwrap());
+    ///
+    /// assert_eq!(
+    ///     condition.to_string(),
+    ///     "has(FrogHate) && karma >= 0 && karma <= 10 && (has(FrogLove) || fear < 0.5)"
+    /// );
+    /// assert_eq!(Condition::default().to_string(), "true");
+    /// ```
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let mut first = true;
+        let mut and = |f: &mut fmt::Formatter| {
+            if !mem::take(&mut first) {
+                write!(f, " && ")?;
+            }
+            Ok(())
+        };
+        for event in &self.events {
+            and(f)?;
+            write!(f, "{}", Expression::Has(event.clone()))?;
+        }
+        let thresholds = self
+            .karma_threshold
+            .map(|(min, max)| ("karma", Value::Int(min), Value::Int(max)));
+        let thresholds = thresholds.into_iter().chain(
+            self.thresholds
+                .iter()
+                .map(|(name, (min, max))| (name.as_str(), min.clone(), max.clone())),
+        );
+        for (name, min, max) in thresholds {
+            and(f)?;
+            write!(f, "{name} >= {min} && {name} <= {max}")?;
+        }
+        if let Some(expression) = &self.expression {
+            and(f)?;
+            expression.fmt_with_precedence(f, 1)?;
+        }
+        if first {
+            write!(f, "true")?;
+        }
+        Ok(())
+    }
 }
@@ -0,0 +1,751 @@
+//! Translatable strings of a dialog, looked up by id in a [`Localizer`].
+
+use serde::{Deserialize, Serialize};
+use std::{
+    borrow::Cow,
+    collections::{btree_map, BTreeMap, BTreeSet, HashMap},
+    fmt,
+};
+
+use crate::{Choice, Content, Dialog, DialogCondition, DialogNode, DialogRunner, Variables};
+
+/// Where a translatable string is written within its node.
+#[derive(Copy, Clone, Eq, PartialEq, Ord, PartialOrd, Hash, Debug)]
+pub enum TextPosition {
+    /// The line at this index in a `Content::Monolog`
+    Line(usize),
+    /// The text of the choice at this index in a `Content::Choices`
+    Choice(usize),
+}
+
+/// A string to translate, found by [`Dialog::translatable_strings`].
+#[derive(Clone, Eq, PartialEq, Ord, PartialOrd, Hash, Debug)]
+pub struct TranslatableString {
+    id: String,
+    node: usize,
+    position: TextPosition,
+    speaker: String,
+    text: String,
+}
+
+impl TranslatableString {
+    /// Returns the string id, explicit or generated.
+    pub fn id(&self) -> &String {
+        &self.id
+    }
+
+    /// Returns the key of the node it is written in.
+    pub fn node(&self) -> usize {
+        self.node
+    }
+
+    /// Returns where it is written within its node.
+    pub fn position(&self) -> TextPosition {
+        self.position
+    }
+
+    /// Returns the speaker of its node, see [`Dialog::speaker`].
+    pub fn speaker(&self) -> &String {
+        &self.speaker
+    }
+
+    /// Returns the inline text, in the language the dialog is written in.
+    pub fn text(&self) -> &String {
+        &self.text
+    }
+}
+
+/// The strings of a dialog in one locale, by id.
+///
+/// It (de)serializes as its `locale` and the map of its `strings`,
+/// so each translation of a dialog can be shipped as its own file.
+///
+/// ```rust
+/// use yml_dialog::*;
+///
+/// let table: StringTable =
+///     serde_yaml::from_str("locale: fr\nstrings:\n  frog.greeting: Coucou\n").unwrap();
+///
+/// assert_eq!(table.locale(), "fr");
+/// assert_eq!(table.localize("frog.greeting").as_deref(), Some("Coucou"));
+/// assert_eq!(table.localize("frog.bye"), None);
+/// ```
+#[derive(Serialize, Deserialize, Clone, Eq, PartialEq, Ord, PartialOrd, Hash, Debug, Default)]
+#[serde(default)]
+pub struct StringTable {
+    locale: String,
+    strings: BTreeMap<String, String>,
+}
+
+impl StringTable {
+    /// Constructs a new empty StringTable for the given `locale`, like `fr` or `en-US`.
+    pub fn new(locale: String) -> Self {
+        StringTable {
+            locale,
+            strings: BTreeMap::new(),
+        }
+    }
+
+    /// Returns the read-only `locale` of the table.
+    pub fn locale(&self) -> &String {
+        &self.locale
+    }
+
+    /// Returns the mutable `locale` of the table.
+    pub fn locale_mut(&mut self) -> &mut String {
+        &mut self.locale
+    }
+
+    /// Returns the read-only `strings` of the table, by id.
+    pub fn strings(&self) -> &BTreeMap<String, String> {
+        &self.strings
+    }
+
+    /// Returns the mutable `strings` of the table, by id.
+    pub fn strings_mut(&mut self) -> &mut BTreeMap<String, String> {
+        &mut self.strings
+    }
+
+    /// Returns the string `id`, if the table has it.
+    pub fn get(&self, id: &str) -> Option<&String> {
+        self.strings.get(id)
+    }
+
+    /// Sets the string `id`, returning the previous one.
+    pub fn insert(&mut self, id: String, text: String) -> Option<String> {
+        self.strings.insert(id, text)
+    }
+
+    /// Removes the string `id`, returning it.
+    pub fn remove(&mut self, id: &str) -> Option<String> {
+        self.strings.remove(id)
+    }
+
+    /// Returns the number of strings.
+    pub fn len(&self) -> usize {
+        self.strings.len()
+    }
+
+    /// Returns `true` if the table has no string.
+    pub fn is_empty(&self) -> bool {
+        self.strings.is_empty()
+    }
+
+    /// Iterates over the strings, sorted by id.
+    pub fn iter(&self) -> btree_map::Iter<'_, String, String> {
+        self.strings.iter()
+    }
+}
+
+impl<'a> IntoIterator for &'a StringTable {
+    type Item = (&'a String, &'a String);
+    type IntoIter = btree_map::Iter<'a, String, String>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.strings.iter()
+    }
+}
+
+/// Finds the translation of a string from its id.
+///
+/// A string without translation falls back to the inline text of the dialog.
+/// A slice or a `Vec` of localizers is a chain of fallbacks, tried in order.
+///
+/// ```rust
+/// use std::collections::HashMap;
+/// use yml_dialog::*;
+///
+/// let mut french = StringTable::new("fr-CA".to_string());
+/// french.insert("frog.greeting".to_string(), "Allo".to_string());
+/// let mut fallback = StringTable::new("fr".to_string());
+/// fallback.insert("frog.greeting".to_string(), "Salut".to_string());
+/// fallback.insert("frog.bye".to_string(), "Au revoir".to_string());
+///
+/// let chain = [french, fallback];
+/// assert_eq!(chain.localize("frog.greeting").as_deref(), Some("Allo"));
+/// assert_eq!(chain.localize("frog.bye").as_deref(), Some("Au revoir"));
+///
+/// let strings: HashMap<String, String> = HashMap::new();
+/// assert_eq!(strings.localize("frog.bye"), None);
+/// ```
+pub trait Localizer {
+    /// Returns the translation of the string `id`, `None` if there is none.
+    fn localize(&self, id: &str) -> Option<Cow<'_, str>>;
+
+    /// Returns the translation of the string `id` formatted with the `variables`,
+    /// like a count of coins, `None` if there is none.
+    ///
+    /// The static tables ignore the `variables`, it is [`Localizer::localize`] by default.
+    fn localize_with(&self, id: &str, variables: &Variables) -> Option<Cow<'_, str>> {
+        let _ = variables;
+        self.localize(id)
+    }
+}
+
+impl Localizer for StringTable {
+    fn localize(&self, id: &str) -> Option<Cow<'_, str>> {
+        self.get(id).map(|text| Cow::Borrowed(text.as_str()))
+    }
+}
+
+impl Localizer for BTreeMap<String, String> {
+    fn localize(&self, id: &str) -> Option<Cow<'_, str>> {
+        self.get(id).map(|text| Cow::Borrowed(text.as_str()))
+    }
+}
+
+impl Localizer for HashMap<String, String> {
+    fn localize(&self, id: &str) -> Option<Cow<'_, str>> {
+        self.get(id).map(|text| Cow::Borrowed(text.as_str()))
+    }
+}
+
+impl<L: Localizer> Localizer for [L] {
+    fn localize(&self, id: &str) -> Option<Cow<'_, str>> {
+        self.iter().find_map(|localizer| localizer.localize(id))
+    }
+
+    fn localize_with(&self, id: &str, variables: &Variables) -> Option<Cow<'_, str>> {
+        self.iter()
+            .find_map(|localizer| localizer.localize_with(id, variables))
+    }
+}
+
+impl<L: Localizer, const N: usize> Localizer for [L; N] {
+    fn localize(&self, id: &str) -> Option<Cow<'_, str>> {
+        self.as_slice().localize(id)
+    }
+
+    fn localize_with(&self, id: &str, variables: &Variables) -> Option<Cow<'_, str>> {
+        self.as_slice().localize_with(id, variables)
+    }
+}
+
+impl<L: Localizer> Localizer for Vec<L> {
+    fn localize(&self, id: &str) -> Option<Cow<'_, str>> {
+        self.as_slice().localize(id)
+    }
+
+    fn localize_with(&self, id: &str, variables: &Variables) -> Option<Cow<'_, str>> {
+        self.as_slice().localize_with(id, variables)
+    }
+}
+
+impl<L: Localizer + ?Sized> Localizer for &L {
+    fn localize(&self, id: &str) -> Option<Cow<'_, str>> {
+        (**self).localize(id)
+    }
+
+    fn localize_with(&self, id: &str, variables: &Variables) -> Option<Cow<'_, str>> {
+        (**self).localize_with(id, variables)
+    }
+}
+
+/// What went wrong with a translation, see [`TranslationReport`].
+#[derive(Clone, Eq, PartialEq, Ord, PartialOrd, Hash, Debug)]
+pub enum TranslationIssueKind {
+    /// The string of the dialog has no translation.
+    Missing {
+        /// The string id
+        id: String,
+    },
+    /// The string was translated from a `source` text which changed since.
+    Stale {
+        /// The string id
+        id: String,
+        /// The text the translation was made from
+        source: String,
+    },
+    /// The translation is marked as needing a review.
+    Fuzzy {
+        /// The string id
+        id: String,
+    },
+    /// The translation is for a string the dialog does not have.
+    Unknown {
+        /// Where the string was, like `greet_player|Player|choice1`
+        context: String,
+    },
+    /// The file could not be read there.
+    Malformed {
+        /// What is wrong
+        message: String,
+    },
+}
+
+impl fmt::Display for TranslationIssueKind {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            TranslationIssueKind::Missing { id } => write!(f, "`{id}` is not translated"),
+            TranslationIssueKind::Stale { id, source } => write!(
+                f,
+                "`{id}` was translated from `{source}`, which changed since, left out"
+            ),
+            TranslationIssueKind::Fuzzy { id } => write!(f, "`{id}` needs a review, left out"),
+            TranslationIssueKind::Unknown { context } => {
+                write!(f, "`{context}` is no string of the dialog, left out")
+            }
+            TranslationIssueKind::Malformed { message } => write!(f, "{message}"),
+        }
+    }
+}
+
+/// An entry of a translation file which was not imported as is.
+#[derive(Clone, Eq, PartialEq, Ord, PartialOrd, Hash, Debug)]
+pub struct TranslationIssue {
+    line: Option<usize>,
+    kind: TranslationIssueKind,
+}
+
+impl TranslationIssue {
+    /// Returns the `line` of the entry in the file, starting at 1,
+    /// `None` for a string missing from the file.
+    pub fn line(&self) -> Option<usize> {
+        self.line
+    }
+
+    /// Returns the `kind` of the issue.
+    pub fn kind(&self) -> &TranslationIssueKind {
+        &self.kind
+    }
+}
+
+impl fmt::Display for TranslationIssue {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self.line {
+            Some(line) => write!(f, "line {line}: {}", self.kind),
+            None => write!(f, "{}", self.kind),
+        }
+    }
+}
+
+/// What the import of a translation went through,
+/// like [`Dialog::import_po`].
+#[derive(Clone, Eq, PartialEq, Ord, PartialOrd, Hash, Debug, Default)]
+pub struct TranslationReport {
+    issues: Vec<TranslationIssue>,
+}
+
+impl TranslationReport {
+    pub(crate) fn report(&mut self, line: Option<usize>, kind: TranslationIssueKind) {
+        self.issues.push(TranslationIssue { line, kind });
+    }
+
+    /// Returns the entries which were not imported as is, in the order of the file,
+    /// then the strings missing from it.
+    pub fn issues(&self) -> &Vec<TranslationIssue> {
+        &self.issues
+    }
+}
+
+/// Reads the entries of a translation file into a table, matching them with the strings of a dialog.
+pub(crate) struct TranslationImport {
+    /// The strings of the dialog by context, with their id and inline text.
+    strings: BTreeMap<String, (String, String)>,
+    ids: Vec<String>,
+    seen: BTreeSet<String>,
+    pub(crate) table: StringTable,
+    pub(crate) report: TranslationReport,
+}
+
+impl TranslationImport {
+    pub(crate) fn new<C, E, F>(dialog: &Dialog<C, E, F>) -> Self {
+        let translatable = dialog.translatable_strings();
+        TranslationImport {
+            ids: translatable
+                .iter()
+                .map(|string| string.id.clone())
+                .collect(),
+            strings: translatable
+                .into_iter()
+                .filter_map(|string| {
+                    let context = dialog.text_context(&string.node, string.position)?;
+                    Some((context_key(&context)?, (string.id, string.text)))
+                })
+                .collect(),
+            seen: BTreeSet::new(),
+            table: StringTable::default(),
+            report: TranslationReport::default(),
+        }
+    }
+
+    /// Imports the `target` translation of the `source` text, written at `context`.
+    ///
+    /// An empty `target` is not translated.
+    pub(crate) fn entry(
+        &mut self,
+        line: usize,
+        context: &str,
+        source: &str,
+        target: String,
+        fuzzy: bool,
+    ) {
+        let Some((id, text)) = context_key(context).and_then(|key| self.strings.get(&key)) else {
+            self.report(
+                line,
+                TranslationIssueKind::Unknown {
+                    context: context.to_string(),
+                },
+            );
+            return;
+        };
+        let id = id.clone();
+        self.seen.insert(id.clone());
+        if target.is_empty() {
+            self.report(line, TranslationIssueKind::Missing { id });
+        } else if fuzzy {
+            self.report(line, TranslationIssueKind::Fuzzy { id });
+        } else if source != text {
+            self.report(
+                line,
+                TranslationIssueKind::Stale {
+                    id,
+                    source: source.to_string(),
+                },
+            );
+        } else {
+            self.table.insert(id, target);
+        }
+    }
+
+    pub(crate) fn report(&mut self, line: usize, kind: TranslationIssueKind) {
+        self.report.report(Some(line), kind);
+    }
+
+    /// Reports the strings of the dialog which were not in the file.
+    pub(crate) fn finish(mut self) -> (StringTable, TranslationReport) {
+        self.report.issues.sort_by_key(|issue| issue.line);
+        for id in self.ids {
+            if !self.seen.contains(&id) {
+                self.report
+                    .report(None, TranslationIssueKind::Missing { id: id.clone() });
+            }
+        }
+        (self.table, self.report)
+    }
+}
+
+/// Returns the node and the position of a context, the speaker being informative only.
+fn context_key(context: &str) -> Option<String> {
+    let (node, _) = context.split_once('|')?;
+    let (_, position) = context.rsplit_once('|')?;
+    Some(format!("{node}|{position}"))
+}
+
+/// Returns the inline text at `position` in the `node`.
+fn inline_text<C, E, F>(node: &DialogNode<C, E, F>, position: TextPosition) -> Option<&String> {
+    match (node.content(), position) {
+        (Content::Monolog { text, .. }, TextPosition::Line(index)) => text.get(index),
+        (Content::Choices(choices), TextPosition::Choice(index)) => {
+            choices.get(index).map(Choice::text)
+        }
+        _ => None,
+    }
+}
+
+/// Returns the positions of the strings written in the `node`, in order.
+fn text_positions<C, E, F>(node: &DialogNode<C, E, F>) -> Vec<TextPosition> {
+    match node.content() {
+        Content::Monolog { text, .. } => (0..text.len()).map(TextPosition::Line).collect(),
+        Content::Choices(choices) => (0..choices.len()).map(TextPosition::Choice).collect(),
+    }
+}
+
+impl<C, E, F> Dialog<C, E, F> {
+    /// Returns the id of the string at `position` in the node `key`,
+    /// `None` if there is no such string.
+    ///
+    /// The explicit id is the one of the choice, or the one of the line in `line_ids`.
+    /// Otherwise, it is generated from the label of the node, or its key, and the position:
+    /// `greet_player.choice1` or `1.line0` for instance.
+    ///
+    /// # Note
+    ///
+    /// A generated id follows the string when the node is relabeled or the lines move:
+    /// write an explicit one to keep its translations.
+    ///
+    /// ```rust
+    /// use yml_dialog::*;
+    ///
+    /// let dialog: Dialog = serde_yaml::from_str(
+    ///     "1:
+    ///   source: Frog
+    ///   content:
+    ///     text:
+    ///     - KeroKero
+    ///     - Hello
+    ///     exit_state: 2
+    ///   line_ids: [frog.croak]
+    /// 2:
+    ///   source: Player
+    ///   content:
+    ///   - id: player.bye
+    ///     text: Bye
+    ///     exit_state: 3
+    ///   - text: Later
+    ///     exit_state: 3\n",
+    /// )
+    /// .unwrap();
+    ///
+    /// assert_eq!(dialog.text_id(&1, TextPosition::Line(0)), Some("frog.croak".to_string()));
+    /// assert_eq!(dialog.text_id(&1, TextPosition::Line(1)), Some("1.line1".to_string()));
+    /// assert_eq!(dialog.text_id(&2, TextPosition::Choice(0)), Some("player.bye".to_string()));
+    /// assert_eq!(dialog.text_id(&2, TextPosition::Choice(1)), Some("2.choice1".to_string()));
+    /// assert_eq!(dialog.text_id(&2, TextPosition::Line(0)), None);
+    /// ```
+    pub fn text_id(&self, key: &usize, position: TextPosition) -> Option<String> {
+        let node = self.get(key)?;
+        inline_text(node, position)?;
+        let explicit = match (node.content(), position) {
+            (Content::Choices(choices), TextPosition::Choice(index)) => choices[index].id().clone(),
+            (_, TextPosition::Line(index)) => node.line_ids().get(index).cloned().flatten(),
+            _ => None,
+        };
+        Some(explicit.unwrap_or_else(|| {
+            let node = match self.label(key) {
+                Some(label) => label.clone(),
+                None => key.to_string(),
+            };
+            match position {
+                TextPosition::Line(index) => format!("{node}.line{index}"),
+                TextPosition::Choice(index) => format!("{node}.choice{index}"),
+            }
+        }))
+    }
+
+    /// Returns where the string at `position` in the node `key` is, for the translators:
+    /// the label of the node or its key, its speaker and the position, like `greet_player|Player|choice1`.
+    pub(crate) fn text_context(&self, key: &usize, position: TextPosition) -> Option<String> {
+        let node = self.get(key)?;
+        inline_text(node, position)?;
+        let name = match self.label(key) {
+            Some(label) => label.clone(),
+            None => key.to_string(),
+        };
+        let position = match position {
+            TextPosition::Line(index) => format!("line{index}"),
+            TextPosition::Choice(index) => format!("choice{index}"),
+        };
+        Some(format!("{name}|{}|{position}", self.speaker(node)))
+    }
+
+    /// Returns every string to translate: the lines of the monologs and the texts of the choices,
+    /// sorted by node.
+    pub fn translatable_strings(&self) -> Vec<TranslatableString> {
+        self.iter()
+            .flat_map(|(key, node)| {
+                text_positions(node)
+                    .into_iter()
+                    .filter_map(move |position| {
+                        Some(TranslatableString {
+                            id: self.text_id(key, position)?,
+                            node: *key,
+                            position,
+                            speaker: self.speaker(node).to_string(),
+                            text: inline_text(node, position)?.clone(),
+                        })
+                    })
+            })
+            .collect()
+    }
+
+    /// Extracts every string to translate into a table for the given `locale`,
+    /// the one the dialog is written in.
+    ///
+    /// ```rust
+    /// use yml_dialog::*;
+    ///
+    /// let dialog: Dialog = serde_yaml::from_str(
+    ///     "greeting:
+    ///   source: Frog
+    ///   content:
+    ///     text: [KeroKero]
+    ///     exit_state: 2\n",
+    /// )
+    /// .unwrap();
+    ///
+    /// let table = dialog.string_table("en".to_string());
+    /// assert_eq!(
+    ///     serde_yaml::to_string(&table).unwrap(),
+    ///     "locale: en\nstrings:\n  greeting.line0: KeroKero\n"
+    /// );
+    /// ```
+    pub fn string_table(&self, locale: String) -> StringTable {
+        let mut table = StringTable::new(locale);
+        for string in self.translatable_strings() {
+            table.insert(string.id, string.text);
+        }
+        table
+    }
+
+    /// Returns the string at `position` in the node `key`, translated by the `localizer`,
+    /// or its inline text if it has no translation.
+    ///
+    /// `None` if there is no such string.
+    pub fn localized_text<'a>(
+        &'a self,
+        key: &usize,
+        position: TextPosition,
+        localizer: &'a (impl Localizer + ?Sized),
+    ) -> Option<Cow<'a, str>> {
+        let text = inline_text(self.get(key)?, position)?;
+        let id = self.text_id(key, position)?;
+        Some(
+            localizer
+                .localize(&id)
+                .unwrap_or(Cow::Borrowed(text.as_str())),
+        )
+    }
+
+    /// Returns the string at `position` in the node `key`, translated by the `localizer`
+    /// and formatted with the `variables`, or its inline text if it has no translation.
+    ///
+    /// `None` if there is no such string.
+    pub fn localized_text_with<'a>(
+        &'a self,
+        key: &usize,
+        position: TextPosition,
+        localizer: &'a (impl Localizer + ?Sized),
+        variables: &Variables,
+    ) -> Option<Cow<'a, str>> {
+        let text = inline_text(self.get(key)?, position)?;
+        let id = self.text_id(key, position)?;
+        Some(
+            localizer
+                .localize_with(&id, variables)
+                .unwrap_or(Cow::Borrowed(text.as_str())),
+        )
+    }
+
+    /// Returns a copy of the dialog with every string translated by the `localizer`,
+    /// the ones without translation keeping their inline text.
+    ///
+    /// The ids are kept, so the copy is translated again from the same tables.
+    ///
+    /// ```rust
+    /// use yml_dialog::*;
+    ///
+    /// let dialog: Dialog = serde_yaml::from_str(
+    ///     "1:
+    ///   source: Player
+    ///   content:
+    ///   - text: Hi
+    ///     exit_state: 2
+    ///   - text: Bye
+    ///     exit_state: 3\n",
+    /// )
+    /// .unwrap();
+    /// let mut french = StringTable::new("fr".to_string());
+    /// french.insert("1.choice0".to_string(), "Salut".to_string());
+    ///
+    /// let translated = dialog.translated(&french);
+    /// let Content::Choices(choices) = translated.get(&1).unwrap().content() else {
+    ///     panic!("expected choices");
+    /// };
+    /// assert_eq!(choices[0].text(), "Salut");
+    /// assert_eq!(choices[1].text(), "Bye");
+    /// ```
+    pub fn translated(&self, localizer: &(impl Localizer + ?Sized)) -> Self
+    where
+        C: Clone,
+        E: Clone,
+        F: Clone,
+    {
+        let mut translated = self.clone();
+        for string in self.translatable_strings() {
+            let Some(text) = localizer.localize(&string.id) else {
+                continue;
+            };
+            let Some(node) = translated.get_mut(&string.node) else {
+                continue;
+            };
+            match (node.content_mut(), string.position) {
+                (Content::Monolog { text: lines, .. }, TextPosition::Line(index)) => {
+                    lines[index] = text.into_owned();
+                }
+                (Content::Choices(choices), TextPosition::Choice(index)) => {
+                    *choices[index].text_mut() = text.into_owned();
+                }
+                _ => {}
+            }
+        }
+        translated
+    }
+}
+
+impl<C, E, F: Clone> DialogRunner<C, E, F> {
+    /// Returns the current line translated by the `localizer`,
+    /// or its inline text if it has no translation.
+    ///
+    /// `None` if the current node is not a monolog.
+    pub fn localized_line<'a>(
+        &'a self,
+        localizer: &'a (impl Localizer + ?Sized),
+    ) -> Option<Cow<'a, str>> {
+        self.dialog().localized_text(
+            &self.current()?,
+            TextPosition::Line(self.line_index()),
+            localizer,
+        )
+    }
+
+    /// Returns the current line translated by the `localizer` and formatted with the `variables`,
+    /// like the ones of the [`ConditionContext`](crate::ConditionContext),
+    /// or its inline text if it has no translation.
+    ///
+    /// `None` if the current node is not a monolog.
+    pub fn localized_line_with<'a>(
+        &'a self,
+        localizer: &'a (impl Localizer + ?Sized),
+        variables: &Variables,
+    ) -> Option<Cow<'a, str>> {
+        self.dialog().localized_text_with(
+            &self.current()?,
+            TextPosition::Line(self.line_index()),
+            localizer,
+            variables,
+        )
+    }
+}
+
+impl<C: DialogCondition, E, F: Clone> DialogRunner<C, E, F> {
+    /// Returns the texts of the [`DialogRunner::available_choices`]
+    /// translated by the `localizer`, or their inline text if they have no translation.
+    pub fn localized_choices<'a>(
+        &'a self,
+        context: &C::Context,
+        localizer: &'a (impl Localizer + ?Sized),
+    ) -> Vec<Cow<'a, str>> {
+        self.localized_choices_with(context, localizer, &Variables::new())
+    }
+
+    /// Returns the texts of the [`DialogRunner::available_choices`]
+    /// translated by the `localizer` and formatted with the `variables`,
+    /// or their inline text if they have no translation.
+    pub fn localized_choices_with<'a>(
+        &'a self,
+        context: &C::Context,
+        localizer: &'a (impl Localizer + ?Sized),
+        variables: &Variables,
+    ) -> Vec<Cow<'a, str>> {
+        let Some(key) = self.current() else {
+            return vec![];
+        };
+        let Some(Content::Choices(choices)) = self.current_node().map(DialogNode::content) else {
+            return vec![];
+        };
+        choices
+            .iter()
+            .enumerate()
+            .filter(|(_, choice)| choice.is_verified(context))
+            .filter_map(|(index, _)| {
+                self.dialog().localized_text_with(
+                    &key,
+                    TextPosition::Choice(index),
+                    localizer,
+                    variables,
+                )
+            })
+            .collect()
+    }
+}
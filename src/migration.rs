@@ -0,0 +1,465 @@
+//! Migrates dialog files written in an older version of the format, behind the `yaml` feature.
+//!
+//! Each version of the format comes with a step migrating its documents to the next one.
+//! A change of the format bumps [`DIALOG_VERSION`] and appends its step to `STEPS`.
+
+use serde::{de::IgnoredAny, Deserialize};
+use serde_yaml::{Mapping, Value};
+use std::{collections::BTreeMap, fmt};
+
+use crate::{Choice, Condition, Content, Dialog, DialogError, DialogNode, DIALOG_VERSION};
+
+/// Migrates a document from the version `index + 1` to the next one.
+type Step = fn(Value, &mut MigrationReport) -> Result<Value, DialogError>;
+
+const STEPS: [Step; DIALOG_VERSION as usize - 1] = [from_legacy];
+
+/// Something a migration could not convert as is, see [`MigrationReport`].
+#[derive(Clone, Eq, PartialEq, Ord, PartialOrd, Hash, Debug)]
+pub struct MigrationIssue {
+    node: Option<usize>,
+    path: String,
+    message: String,
+}
+
+impl MigrationIssue {
+    /// Returns the key of the node concerned, `None` if it concerns the whole dialog.
+    pub fn node(&self) -> Option<usize> {
+        self.node
+    }
+
+    /// Returns the path of the field concerned within the node, like `choices[1].karma_threshold`.
+    pub fn path(&self) -> &String {
+        &self.path
+    }
+
+    /// Returns the read-only `message` of the issue.
+    pub fn message(&self) -> &String {
+        &self.message
+    }
+}
+
+impl fmt::Display for MigrationIssue {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        if let Some(node) = self.node {
+            write!(f, "node {node}")?;
+            if !self.path.is_empty() {
+                write!(f, ", ")?;
+            }
+        }
+        if !self.path.is_empty() {
+            write!(f, "{}", self.path)?;
+        }
+        if self.node.is_some() || !self.path.is_empty() {
+            write!(f, ": ")?;
+        }
+        write!(f, "{}", self.message)
+    }
+}
+
+/// What a migration went through.
+#[derive(Clone, Eq, PartialEq, Ord, PartialOrd, Hash, Debug)]
+pub struct MigrationReport {
+    from_version: u32,
+    issues: Vec<MigrationIssue>,
+}
+
+impl MigrationReport {
+    fn new(from_version: u32) -> Self {
+        MigrationReport {
+            from_version,
+            issues: Vec::new(),
+        }
+    }
+
+    fn report(&mut self, node: Option<usize>, path: impl Into<String>, message: impl Into<String>) {
+        self.issues.push(MigrationIssue {
+            node,
+            path: path.into(),
+            message: message.into(),
+        });
+    }
+
+    /// Returns the version the dialog was written in.
+    pub fn from_version(&self) -> u32 {
+        self.from_version
+    }
+
+    /// Returns what could not be converted as is, sorted by node.
+    pub fn issues(&self) -> &Vec<MigrationIssue> {
+        &self.issues
+    }
+}
+
+/// Migrates a YAML dialog written in any version of the format to the current one.
+///
+/// Returns the migrated YAML, declaring `version:` [`DIALOG_VERSION`],
+/// and the report of what could not be converted as is.
+/// A file without `version:` is recognized as the legacy format, see [`LegacyDialog`],
+/// or as the current version.
+/// The legacy nodes may be written under a `nodes:` key.
+///
+/// The conditions, `extra:` payloads and custom effects are kept as written,
+/// whatever their types: the migrated file is read with the ones of the game.
+///
+/// ```rust
+/// use yml_dialog::*;
+///
+/// let (yaml, report) = migrate_yaml(
+///     "1:
+///   source: Frog
+///   monolog:
+///     - KeroKero
+///   state: 2
+/// 2:
+///   source: Player
+///   choices:
+///     - text: Hi
+///       karma_threshold: (0, 10)
+///       state: 1
+///     - text: Bye
+///       world_event: [FrogLove]
+///       state: 3\n",
+/// )
+/// .unwrap();
+///
+/// assert_eq!(report.from_version(), 1);
+/// assert!(report.issues().is_empty());
+/// assert!(yaml.starts_with("version: 2\nnodes:\n  1:\n"));
+///
+/// let dialog: Dialog = Dialog::from_yaml_str(&yaml).unwrap();
+/// assert_eq!(
+///     dialog.get(&2).unwrap().content(),
+///     &Content::Choices(vec![
+///         Choice::new("Hi".to_string(), Some(Condition::new(Some((0, 10)), vec![])), 1),
+///         Choice::new(
+///             "Bye".to_string(),
+///             Some(Condition::new(None, vec!["FrogLove".to_string()])),
+///             3,
+///         ),
+///     ])
+/// );
+/// ```
+pub fn migrate_yaml(yaml: &str) -> Result<(String, MigrationReport), DialogError> {
+    let mut document: Value = serde_yaml::from_str(yaml).map_err(yaml_error)?;
+    let version = take_version(&mut document)?;
+    if version == 0 || version > DIALOG_VERSION {
+        return Err(DialogError::new(format!(
+            "unknown version {version}, expected at most version {DIALOG_VERSION}"
+        )));
+    }
+
+    let mut report = MigrationReport::new(version);
+    for step in &STEPS[version as usize - 1..] {
+        document = step(document, &mut report)?;
+    }
+    report.issues.sort();
+
+    let mut versioned = Mapping::new();
+    versioned.insert("version".into(), DIALOG_VERSION.into());
+    match document {
+        Value::Mapping(metadata) if metadata.contains_key("nodes") => versioned.extend(metadata),
+        nodes => {
+            versioned.insert("nodes".into(), nodes);
+        }
+    }
+    let yaml = serde_yaml::to_string(&versioned).map_err(yaml_error)?;
+    // Checks the migrated document, whatever the types of its conditions, extras and custom effects
+    Dialog::<Value, Value, Value>::from_yaml_str(&yaml)?;
+    Ok((yaml, report))
+}
+
+fn yaml_error(error: serde_yaml::Error) -> DialogError {
+    DialogError::new(error.to_string())
+}
+
+/// Removes the `version:` of the `document`, guessing it if there is none.
+fn take_version(document: &mut Value) -> Result<u32, DialogError> {
+    let Value::Mapping(map) = document else {
+        return Err(DialogError::new("expected a map of dialog nodes"));
+    };
+    match map.remove("version") {
+        Some(version) => version
+            .as_u64()
+            .and_then(|version| u32::try_from(version).ok())
+            .ok_or_else(|| DialogError::new("the version must be a positive integer")),
+        None if is_legacy(map) => Ok(1),
+        None => Ok(DIALOG_VERSION),
+    }
+}
+
+/// Returns `true` if a node has a field of the [`LegacyDialog`] format.
+fn is_legacy(map: &Mapping) -> bool {
+    let nodes = match map.get("nodes") {
+        Some(Value::Mapping(nodes)) => nodes,
+        _ => map,
+    };
+    nodes.values().any(|node| {
+        node.as_mapping().is_some_and(|node| {
+            ["monolog", "choices", "state"]
+                .iter()
+                .any(|field| node.contains_key(*field))
+        })
+    })
+}
+
+fn from_legacy(mut document: Value, report: &mut MigrationReport) -> Result<Value, DialogError> {
+    // Legacy nodes under a `nodes:` key keep the fields around them, like a `name:`
+    let nodes = match &mut document {
+        Value::Mapping(map) if map.contains_key("nodes") => map.remove("nodes").unwrap_or_default(),
+        _ => std::mem::take(&mut document),
+    };
+    let legacy: LegacyDialog = serde_yaml::from_value(nodes).map_err(yaml_error)?;
+    let (dialog, legacy_report) = legacy.migrate();
+    report.issues.extend(legacy_report.issues);
+    let nodes = serde_yaml::to_value(&dialog).map_err(yaml_error)?;
+    match document {
+        Value::Mapping(mut metadata) => {
+            metadata.insert("nodes".into(), nodes);
+            Ok(Value::Mapping(metadata))
+        }
+        _ => Ok(nodes),
+    }
+}
+
+/// A dialog in the legacy format, the version 1, like `examples/test_dialog.yml`.
+///
+/// Its nodes have a top-level `monolog:` (a list of lines) or `choices:`,
+/// `state:` instead of `exit_state:`, and a single `trigger_event` may be a bare string.
+/// A choice has its own `state:`, `world_event:` (the active events it requires)
+/// and `karma_threshold:` written as a string like `(-10, 0)`.
+///
+/// See [`LegacyDialog::migrate`].
+#[derive(Deserialize, Clone, PartialEq, Debug, Default)]
+pub struct LegacyDialog(BTreeMap<usize, LegacyNode>);
+
+#[derive(Deserialize, Clone, PartialEq, Debug, Default)]
+struct LegacyNode {
+    #[serde(default)]
+    source: String,
+    monolog: Option<Vec<String>>,
+    choices: Option<Vec<LegacyChoice>>,
+    state: Option<usize>,
+    #[serde(default)]
+    trigger_event: OneOrMany,
+    #[serde(flatten)]
+    unknown: BTreeMap<String, IgnoredAny>,
+}
+
+#[derive(Deserialize, Clone, PartialEq, Debug, Default)]
+struct LegacyChoice {
+    #[serde(default)]
+    text: String,
+    state: Option<usize>,
+    #[serde(default)]
+    world_event: OneOrMany,
+    karma_threshold: Option<LegacyThreshold>,
+    #[serde(flatten)]
+    unknown: BTreeMap<String, IgnoredAny>,
+}
+
+#[derive(Deserialize, Clone, Eq, PartialEq, Debug)]
+#[serde(untagged)]
+enum OneOrMany {
+    One(String),
+    Many(Vec<String>),
+}
+
+impl Default for OneOrMany {
+    fn default() -> Self {
+        OneOrMany::Many(Vec::new())
+    }
+}
+
+impl From<OneOrMany> for Vec<String> {
+    fn from(value: OneOrMany) -> Self {
+        match value {
+            OneOrMany::One(value) => vec![value],
+            OneOrMany::Many(values) => values,
+        }
+    }
+}
+
+#[derive(Deserialize, Clone, Eq, PartialEq, Debug)]
+#[serde(untagged)]
+enum LegacyThreshold {
+    Pair((i32, i32)),
+    Written(String),
+}
+
+impl fmt::Display for LegacyThreshold {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            LegacyThreshold::Pair((min, max)) => write!(f, "({min}, {max})"),
+            LegacyThreshold::Written(written) => write!(f, "{written}"),
+        }
+    }
+}
+
+impl LegacyThreshold {
+    /// Reads `(-10, 0)`, parentheses being optional.
+    fn parse(self) -> Option<(i32, i32)> {
+        match self {
+            LegacyThreshold::Pair(threshold) => Some(threshold),
+            LegacyThreshold::Written(written) => {
+                let written = written.trim();
+                let written = written
+                    .strip_prefix('(')
+                    .and_then(|written| written.strip_suffix(')'))
+                    .unwrap_or(written);
+                let (min, max) = written.split_once(',')?;
+                Some((min.trim().parse().ok()?, max.trim().parse().ok()?))
+            }
+        }
+    }
+}
+
+impl LegacyDialog {
+    /// Converts the legacy dialog to the current layout.
+    ///
+    /// A monolog without `state:` ended the dialog: it is given an `exit_state` greater than all keys.
+    /// Anything which cannot be converted as is, like an unknown field or an unreadable
+    /// `karma_threshold`, is left out and reported.
+    ///
+    /// ```rust
+    /// use yml_dialog::*;
+    ///
+    /// let legacy: LegacyDialog = serde_yaml::from_str(
+    ///     "5:
+    ///   source: Narrator
+    ///   monolog:
+    ///     - There is no more bread...
+    /// 6:
+    ///   source: Narrator
+    ///   monolog:
+    ///     - Deserved
+    ///   trigger_event: DealHP(-30)\n",
+    /// )
+    /// .unwrap();
+    /// let (dialog, report) = legacy.migrate();
+    ///
+    /// assert_eq!(dialog.get(&6).unwrap().trigger_event(), &vec!["DealHP(-30)".to_string()]);
+    /// assert_eq!(
+    ///     report.issues()[0].to_string(),
+    ///     "node 5: no `state`, the monolog now ends the dialog with the exit_state `7`"
+    /// );
+    /// ```
+    pub fn migrate(self) -> (Dialog, MigrationReport) {
+        let mut report = MigrationReport::new(1);
+        let end = self.0.keys().max().map_or(1, |key| key + 1);
+        let mut nodes = BTreeMap::new();
+
+        for (key, node) in self.0 {
+            let node_report = |report: &mut MigrationReport, path: &str, message: String| {
+                report.report(Some(key), path, message)
+            };
+            for field in node.unknown.keys() {
+                node_report(
+                    &mut report,
+                    field,
+                    format!("unknown field `{field}`, left out"),
+                );
+            }
+
+            let content = match (node.monolog, node.choices) {
+                (Some(text), choices) => {
+                    if choices.is_some() {
+                        node_report(
+                            &mut report,
+                            "choices",
+                            "the node has a monolog too, the choices are left out".to_string(),
+                        );
+                    }
+                    let exit_state = node.state.unwrap_or_else(|| {
+                        node_report(
+                            &mut report,
+                            "",
+                            format!(
+                                "no `state`, the monolog now ends the dialog \
+                                with the exit_state `{end}`"
+                            ),
+                        );
+                        end
+                    });
+                    Content::Monolog { text, exit_state }
+                }
+                (None, Some(choices)) => {
+                    if node.state.is_some() {
+                        node_report(
+                            &mut report,
+                            "state",
+                            "the `state` of a node with choices is left out".to_string(),
+                        );
+                    }
+                    Content::Choices(
+                        choices
+                            .into_iter()
+                            .enumerate()
+                            .map(|(index, choice)| {
+                                migrate_choice(choice, key, index, end, &mut report)
+                            })
+                            .collect(),
+                    )
+                }
+                (None, None) => {
+                    node_report(
+                        &mut report,
+                        "",
+                        "no `monolog` nor `choices`, the node is an empty monolog".to_string(),
+                    );
+                    Content::Monolog {
+                        text: Vec::new(),
+                        exit_state: node.state.unwrap_or(end),
+                    }
+                }
+            };
+
+            nodes.insert(
+                key,
+                DialogNode::new(node.source, content, node.trigger_event.into()),
+            );
+        }
+
+        report.issues.sort();
+        (Dialog::new(nodes), report)
+    }
+}
+
+fn migrate_choice(
+    choice: LegacyChoice,
+    key: usize,
+    index: usize,
+    end: usize,
+    report: &mut MigrationReport,
+) -> Choice {
+    for field in choice.unknown.keys() {
+        report.report(
+            Some(key),
+            format!("choices[{index}].{field}"),
+            format!("unknown field `{field}`, left out"),
+        );
+    }
+    let karma_threshold = choice.karma_threshold.and_then(|threshold| {
+        let parsed = threshold.clone().parse();
+        if parsed.is_none() {
+            report.report(
+                Some(key),
+                format!("choices[{index}].karma_threshold"),
+                format!("unreadable threshold `{threshold}`, expected `(min, max)`, left out"),
+            );
+        }
+        parsed
+    });
+    let events: Vec<String> = choice.world_event.into();
+    let condition = (karma_threshold.is_some() || !events.is_empty())
+        .then(|| Condition::new(karma_threshold, events));
+    let exit_state = choice.state.unwrap_or_else(|| {
+        report.report(
+            Some(key),
+            format!("choices[{index}]"),
+            format!("no `state`, the choice now ends the dialog with the exit_state `{end}`"),
+        );
+        end
+    });
+    Choice::new(choice.text, condition, exit_state)
+}
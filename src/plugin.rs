@@ -0,0 +1,248 @@
+//! Runs dialogs as Bevy components, behind the `bevy` feature.
+//!
+//! Insert a [`DialogState`] on an entity, then send [`AdvanceDialogEvent`]s and [`ChooseDialogEvent`]s:
+//! the [`YmlDialogPlugin`] moves its [`DialogRunner`] and reports what happened
+//! as [`NodeEnteredEvent`]s, [`EffectsFiredEvent`]s and [`DialogEndedEvent`]s.
+
+use bevy::{ecs::system::SystemParam, prelude::*};
+
+use crate::{ConditionContext, Dialog, DialogRunner, Effect, Step};
+
+/// Adds the dialog events and the [`DialogSystems`] to the app.
+///
+/// The plugin applies no effect itself: read the [`EffectsFiredEvent`]s,
+/// for instance to [`ConditionContext::apply`] them on the [`DialogContext`].
+///
+/// ```rust
+/// use bevy::prelude::*;
+/// use yml_dialog::*;
+///
+/// let dialog: Dialog = serde_yaml::from_str(
+///     "1:
+///   source: Frog
+///   content:
+///     text:
+///     - KeroKero
+///     exit_state: 2
+///   effects:
+///   - set_event: FrogTalk\n",
+/// )
+/// .unwrap();
+///
+/// let mut app = App::new();
+/// app.add_plugin(YmlDialogPlugin);
+/// let frog = app.world.spawn(DialogState::new(dialog)).id();
+/// app.update();
+///
+/// app.world.send_event(AdvanceDialogEvent { entity: frog });
+/// app.update();
+///
+/// let mut ended = app
+///     .world
+///     .resource::<Events<DialogEndedEvent>>()
+///     .iter_current_update_events();
+/// assert_eq!(
+///     ended.next(),
+///     Some(&DialogEndedEvent {
+///         entity: frog,
+///         exit_state: 2
+///     })
+/// );
+/// assert!(app.world.get::<DialogState>(frog).unwrap().runner().is_ended());
+/// ```
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug, Default)]
+pub struct YmlDialogPlugin;
+
+impl Plugin for YmlDialogPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_event::<AdvanceDialogEvent>()
+            .add_event::<ChooseDialogEvent>()
+            .add_event::<NodeEnteredEvent>()
+            .add_event::<DialogEndedEvent>()
+            .add_event::<EffectsFiredEvent>()
+            .init_resource::<DialogContext>()
+            .add_systems(
+                (start_dialogs, advance_dialogs, choose_dialogs)
+                    .chain()
+                    .in_set(DialogSystems),
+            );
+    }
+}
+
+/// The systems of the [`YmlDialogPlugin`], to order your own ones around them.
+#[derive(SystemSet, Clone, PartialEq, Eq, Hash, Debug)]
+pub struct DialogSystems;
+
+/// The dialog run by an entity.
+///
+/// When inserted, the [`NodeEnteredEvent`] and [`EffectsFiredEvent`] of the entry node are sent.
+#[derive(Component, Clone, Debug)]
+pub struct DialogState {
+    runner: DialogRunner,
+}
+
+impl DialogState {
+    /// Constructs a new DialogState, standing on the entry node of the `dialog`.
+    pub fn new(dialog: Dialog) -> Self {
+        DialogState {
+            runner: DialogRunner::new(dialog),
+        }
+    }
+
+    /// Returns the read-only runner of the dialog.
+    pub fn runner(&self) -> &DialogRunner {
+        &self.runner
+    }
+
+    /// Returns the mutable runner of the dialog.
+    ///
+    /// # Note
+    ///
+    /// Moving the runner by hand sends no event.
+    pub fn runner_mut(&mut self) -> &mut DialogRunner {
+        &mut self.runner
+    }
+}
+
+/// The context verifying the conditions of the choices, see [`ChooseDialogEvent`].
+#[derive(Resource, Clone, PartialEq, Debug, Default)]
+pub struct DialogContext(pub ConditionContext);
+
+/// Asks the dialog of `entity` for its next line, see [`DialogRunner::advance`].
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
+pub struct AdvanceDialogEvent {
+    /// The entity holding the [`DialogState`]
+    pub entity: Entity,
+}
+
+/// Picks a choice in the dialog of `entity`, see [`DialogRunner::choose`].
+///
+/// The choices are verified within the [`DialogContext`].
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
+pub struct ChooseDialogEvent {
+    /// The entity holding the [`DialogState`]
+    pub entity: Entity,
+    /// The index of the choice among the available ones
+    pub index: usize,
+}
+
+/// The dialog of `entity` entered the node `key`.
+#[derive(Clone, PartialEq, Eq, Hash, Debug)]
+pub struct NodeEnteredEvent {
+    /// The entity holding the [`DialogState`]
+    pub entity: Entity,
+    /// The key of the node
+    pub key: usize,
+    /// The `trigger_event` of the node
+    pub trigger_event: Vec<String>,
+}
+
+/// The dialog of `entity` reached an `exit_state` which is not a node.
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
+pub struct DialogEndedEvent {
+    /// The entity holding the [`DialogState`]
+    pub entity: Entity,
+    /// The reached `exit_state`
+    pub exit_state: usize,
+}
+
+/// The effects of the picked choice, then of the entered node, in the dialog of `entity`.
+///
+/// It is sent before the matching [`NodeEnteredEvent`] or [`DialogEndedEvent`], and only if
+/// there is any effect.
+#[derive(Clone, PartialEq, Eq, Hash, Debug)]
+pub struct EffectsFiredEvent {
+    /// The entity holding the [`DialogState`]
+    pub entity: Entity,
+    /// The effects, to apply in this order
+    pub effects: Vec<Effect>,
+}
+
+/// The events reporting the [`Step`]s of the dialogs.
+#[derive(SystemParam)]
+pub(crate) struct StepWriters<'w> {
+    node_entered: EventWriter<'w, NodeEnteredEvent>,
+    dialog_ended: EventWriter<'w, DialogEndedEvent>,
+    effects_fired: EventWriter<'w, EffectsFiredEvent>,
+}
+
+impl StepWriters<'_> {
+    pub(crate) fn report(&mut self, entity: Entity, step: Step) {
+        match step {
+            Step::Line => {}
+            Step::Node {
+                key,
+                trigger_event,
+                effects,
+            } => {
+                self.fire(entity, effects);
+                self.node_entered.send(NodeEnteredEvent {
+                    entity,
+                    key,
+                    trigger_event,
+                });
+            }
+            Step::End {
+                exit_state,
+                effects,
+            } => {
+                self.fire(entity, effects);
+                self.dialog_ended
+                    .send(DialogEndedEvent { entity, exit_state });
+            }
+        }
+    }
+
+    fn fire(&mut self, entity: Entity, effects: Vec<Effect>) {
+        if !effects.is_empty() {
+            self.effects_fired
+                .send(EffectsFiredEvent { entity, effects });
+        }
+    }
+}
+
+/// Reports the entry node of the newly inserted dialogs.
+fn start_dialogs(
+    mut dialogs: Query<(Entity, &mut DialogState), Added<DialogState>>,
+    mut writers: StepWriters,
+) {
+    for (entity, mut state) in &mut dialogs {
+        let step = state.runner.restart();
+        writers.report(entity, step);
+    }
+}
+
+fn advance_dialogs(
+    mut advance_events: EventReader<AdvanceDialogEvent>,
+    mut dialogs: Query<&mut DialogState>,
+    mut writers: StepWriters,
+) {
+    for AdvanceDialogEvent { entity } in advance_events.iter() {
+        let Ok(mut state) = dialogs.get_mut(*entity) else {
+            warn!("cannot advance: {entity:?} has no DialogState");
+            continue;
+        };
+        match state.runner.advance() {
+            Ok(step) => writers.report(*entity, step),
+            Err(error) => warn!("cannot advance the dialog of {entity:?}: {error}"),
+        }
+    }
+}
+
+fn choose_dialogs(
+    mut choose_events: EventReader<ChooseDialogEvent>,
+    mut dialogs: Query<&mut DialogState>,
+    context: Res<DialogContext>,
+    mut writers: StepWriters,
+) {
+    for ChooseDialogEvent { entity, index } in choose_events.iter() {
+        let Ok(mut state) = dialogs.get_mut(*entity) else {
+            warn!("cannot choose: {entity:?} has no DialogState");
+            continue;
+        };
+        match state.runner.choose(*index, &context.0) {
+            Ok(step) => writers.report(*entity, step),
+            Err(error) => warn!("cannot choose in the dialog of {entity:?}: {error}"),
+        }
+    }
+}
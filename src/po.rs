@@ -0,0 +1,308 @@
+//! Exports the strings of a dialog to gettext PO files, and imports their translations.
+//!
+//! Each string is an entry whose `msgctxt` tells its node, speaker and position,
+//! like `greet_player|Player|choice1`, and whose `msgid` is its inline text.
+//! The `note` of the node is written as a translator comment.
+
+use std::fmt::Write;
+
+use crate::{Dialog, StringTable, TranslationImport, TranslationIssueKind, TranslationReport};
+
+/// Quotes a PO string, escaping it.
+fn quote(text: &str) -> String {
+    let mut quoted = String::with_capacity(text.len() + 2);
+    quoted.push('"');
+    for character in text.chars() {
+        match character {
+            '"' => quoted.push_str("\\\""),
+            '\\' => quoted.push_str("\\\\"),
+            '\n' => quoted.push_str("\\n"),
+            '\t' => quoted.push_str("\\t"),
+            '\r' => quoted.push_str("\\r"),
+            _ => quoted.push(character),
+        }
+    }
+    quoted.push('"');
+    quoted
+}
+
+/// Reads a quoted PO string, `None` if it is not one.
+fn unquote(text: &str) -> Option<String> {
+    let inner = text.strip_prefix('"')?.strip_suffix('"')?;
+    let mut unquoted = String::with_capacity(inner.len());
+    let mut characters = inner.chars();
+    while let Some(character) = characters.next() {
+        match character {
+            '\\' => match characters.next()? {
+                'n' => unquoted.push('\n'),
+                't' => unquoted.push('\t'),
+                'r' => unquoted.push('\r'),
+                escaped => unquoted.push(escaped),
+            },
+            '"' => return None,
+            _ => unquoted.push(character),
+        }
+    }
+    Some(unquoted)
+}
+
+/// The field a string continuation line goes to.
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum Field {
+    Context,
+    Id,
+    /// The plural `msgid_plural` or the `msgstr[n]` of another form than the first one.
+    Ignored,
+    Translation,
+}
+
+#[derive(Default)]
+struct Entry {
+    /// The line it starts at.
+    line: usize,
+    fuzzy: bool,
+    plural: bool,
+    context: Option<String>,
+    id: Option<String>,
+    translation: Option<String>,
+}
+
+/// Splits a PO file into its entries, reporting the lines which cannot be read.
+fn entries(po: &str, report: &mut TranslationReport) -> Vec<Entry> {
+    let mut entries = Vec::new();
+    let mut entry = Entry::default();
+    let mut field = None;
+
+    for (index, line) in po.lines().enumerate() {
+        let number = index + 1;
+        let line = line.trim();
+        // An entry is over at its first line after the `msgstr`
+        let starts_entry = line.is_empty()
+            || line.starts_with('#')
+            || line.starts_with("msgctxt")
+            || line.starts_with("msgid ");
+        if starts_entry && entry.translation.is_some() {
+            entries.push(std::mem::take(&mut entry));
+            field = None;
+        }
+        if line.is_empty() || line.starts_with("#~") {
+            continue;
+        }
+        if entry.line == 0 {
+            entry.line = number;
+        }
+        if let Some(flags) = line.strip_prefix("#,") {
+            entry.fuzzy |= flags.split(',').any(|flag| flag.trim() == "fuzzy");
+            continue;
+        }
+        if line.starts_with('#') {
+            continue;
+        }
+
+        let (keyword, value) = match line.find('"') {
+            Some(quote) => (line[..quote].trim(), &line[quote..]),
+            None => (line, ""),
+        };
+        let Some(value) = unquote(value) else {
+            report.report(
+                Some(number),
+                TranslationIssueKind::Malformed {
+                    message: format!("expected a quoted string in `{line}`"),
+                },
+            );
+            continue;
+        };
+        let target = match keyword {
+            "" => match field {
+                Some(field) => field,
+                None => {
+                    report.report(
+                        Some(number),
+                        TranslationIssueKind::Malformed {
+                            message: "a string continues no field".to_string(),
+                        },
+                    );
+                    continue;
+                }
+            },
+            "msgctxt" => Field::Context,
+            "msgid" => Field::Id,
+            "msgid_plural" => {
+                entry.plural = true;
+                Field::Ignored
+            }
+            "msgstr" | "msgstr[0]" => Field::Translation,
+            keyword if keyword.starts_with("msgstr[") => Field::Ignored,
+            keyword => {
+                report.report(
+                    Some(number),
+                    TranslationIssueKind::Malformed {
+                        message: format!("unknown keyword `{keyword}`"),
+                    },
+                );
+                continue;
+            }
+        };
+        field = Some(target);
+        let text = match target {
+            Field::Context => &mut entry.context,
+            Field::Id => &mut entry.id,
+            Field::Translation => &mut entry.translation,
+            Field::Ignored => continue,
+        };
+        text.get_or_insert_with(String::new).push_str(&value);
+    }
+    if entry.id.is_some() {
+        entries.push(entry);
+    }
+    entries
+}
+
+impl<C, E, F> Dialog<C, E, F> {
+    /// Writes every string of the dialog as a gettext PO file,
+    /// translated in the `translations` if they have it.
+    ///
+    /// The `locale` of the `translations` is the `Language` of the file:
+    /// an empty [`StringTable`] gives a template, a `.pot` file.
+    ///
+    /// ```rust
+    /// use yml_dialog::*;
+    ///
+    /// let dialog: Dialog = serde_yaml::from_str(
+    ///     "1:
+    ///   source: Frog
+    ///   note: The frog is grumpy
+    ///   content:
+    ///     text:
+    ///     - KeroKero
+    ///     exit_state: 2\n",
+    /// )
+    /// .unwrap();
+    ///
+    /// let mut french = StringTable::new("fr".to_string());
+    /// french.insert("1.line0".to_string(), "Crôa".to_string());
+    ///
+    /// assert_eq!(
+    ///     dialog.to_po_string(&french),
+    ///     r#"msgid ""
+    /// msgstr ""
+    /// "Language: fr\n"
+    /// "MIME-Version: 1.0\n"
+    /// "Content-Type: text/plain; charset=UTF-8\n"
+    /// "Content-Transfer-Encoding: 8bit\n"
+    ///
+    /// #. The frog is grumpy
+    /// msgctxt "1|Frog|line0"
+    /// msgid "KeroKero"
+    /// msgstr "Crôa"
+    /// "#
+    /// );
+    /// ```
+    pub fn to_po_string(&self, translations: &StringTable) -> String {
+        let mut po = String::from("msgid \"\"\nmsgstr \"\"\n");
+        if let Some(name) = self.name() {
+            po.push_str(&quote(&format!("Project-Id-Version: {name}\n")));
+            po.push('\n');
+        }
+        for header in [
+            format!("Language: {}\n", translations.locale()),
+            "MIME-Version: 1.0\n".to_string(),
+            "Content-Type: text/plain; charset=UTF-8\n".to_string(),
+            "Content-Transfer-Encoding: 8bit\n".to_string(),
+        ] {
+            po.push_str(&quote(&header));
+            po.push('\n');
+        }
+
+        for string in self.translatable_strings() {
+            po.push('\n');
+            let note = self
+                .get(&string.node())
+                .and_then(|node| node.note().as_ref());
+            for line in note.iter().flat_map(|note| note.lines()) {
+                let _ = writeln!(po, "#. {line}");
+            }
+            let context = self
+                .text_context(&string.node(), string.position())
+                .unwrap_or_default();
+            let translation = translations.get(string.id()).cloned().unwrap_or_default();
+            let _ = writeln!(po, "msgctxt {}", quote(&context));
+            let _ = writeln!(po, "msgid {}", quote(string.text()));
+            let _ = writeln!(po, "msgstr {}", quote(&translation));
+        }
+        po
+    }
+
+    /// Reads the translations of a gettext PO file into a table,
+    /// whose locale is the `Language` of the file.
+    ///
+    /// The [`TranslationReport`] lists the entries left out: the ones marked `fuzzy`,
+    /// the stale ones, whose `msgid` is not the text of the dialog anymore,
+    /// and the ones for strings the dialog does not have.
+    /// It lists the strings of the dialog without translation too.
+    ///
+    /// ```rust
+    /// use yml_dialog::*;
+    ///
+    /// let dialog: Dialog = serde_yaml::from_str(
+    ///     "1:
+    ///   source: Player
+    ///   content:
+    ///   - text: Hi
+    ///     exit_state: 2
+    ///   - text: Bye
+    ///     exit_state: 2\n",
+    /// )
+    /// .unwrap();
+    ///
+    /// let (french, report) = dialog.import_po(
+    ///     r#"msgid ""
+    /// msgstr "Language: fr\n"
+    ///
+    /// msgctxt "1|Player|choice0"
+    /// msgid "Hi"
+    /// msgstr "Salut"
+    ///
+    /// msgctxt "1|Player|choice1"
+    /// msgid "Goodbye"
+    /// msgstr "Au revoir"
+    /// "#,
+    /// );
+    ///
+    /// assert_eq!(french.locale(), "fr");
+    /// assert_eq!(french.get("1.choice0"), Some(&"Salut".to_string()));
+    /// assert_eq!(french.len(), 1);
+    /// assert_eq!(
+    ///     report.issues()[0].to_string(),
+    ///     "line 8: `1.choice1` was translated from `Goodbye`, which changed since, left out"
+    /// );
+    /// ```
+    pub fn import_po(&self, po: &str) -> (StringTable, TranslationReport) {
+        let mut import = TranslationImport::new(self);
+        for entry in entries(po, &mut import.report) {
+            let id = entry.id.unwrap_or_default();
+            let translation = entry.translation.unwrap_or_default();
+            if id.is_empty() && entry.context.is_none() {
+                let language = translation
+                    .lines()
+                    .find_map(|header| header.strip_prefix("Language:"));
+                if let Some(language) = language {
+                    *import.table.locale_mut() = language.trim().to_string();
+                }
+                continue;
+            }
+            if entry.plural {
+                import.report(
+                    entry.line,
+                    TranslationIssueKind::Malformed {
+                        message: "the dialogs have no plural forms, left out".to_string(),
+                    },
+                );
+                continue;
+            }
+            let context = entry.context.unwrap_or_default();
+            import.entry(entry.line, &context, &id, translation, entry.fuzzy);
+        }
+        import.finish()
+    }
+}
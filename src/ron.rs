@@ -0,0 +1,86 @@
+//! Reads and writes dialogs as RON, behind the `ron` feature.
+
+use ron::{de::SpannedError, extensions::Extensions, ser::PrettyConfig, Deserializer, Options};
+use serde::{de::DeserializeOwned, Serialize};
+use std::io;
+
+use crate::{
+    track::{deserialize_tracked, read_checked},
+    Dialog, DialogError,
+};
+
+impl<C, E, F> Dialog<C, E, F> {
+    /// Reads a dialog from a RON string.
+    ///
+    /// The dialog has the same shape as in YAML: the metadata as a struct,
+    /// the nodes as a map, and `monolog` or `choices` as the single key of a `content` map.
+    /// The optional fields may be written without `Some(...)`.
+    /// The error tells the node, the field and the position in the file of the faulty value,
+    /// see [`DialogError`].
+    ///
+    /// ```rust
+    /// use yml_dialog::*;
+    ///
+    /// let dialog: Dialog = Dialog::from_ron_str(
+    ///     r#"(
+    ///     name: "Frog",
+    ///     nodes: {
+    ///         1: (
+    ///             source: "Frog",
+    ///             content: { "monolog": (text: ["KeroKero"], exit_state: 2) },
+    ///         ),
+    ///     },
+    /// )"#,
+    /// )
+    /// .unwrap();
+    ///
+    /// assert_eq!(dialog.name(), &Some("Frog".to_string()));
+    /// assert_eq!(Dialog::from_ron_str(&dialog.to_ron_string().unwrap()), Ok(dialog));
+    /// ```
+    pub fn from_ron_str(ron: &str) -> Result<Self, DialogError>
+    where
+        Self: DeserializeOwned,
+    {
+        let options = Options::default().with_default_extension(Extensions::IMPLICIT_SOME);
+        read_checked(
+            || options.from_str(ron).map_err(spanned),
+            || {
+                let mut deserializer =
+                    Deserializer::from_str_with_options(ron, options.clone()).map_err(spanned)?;
+                deserialize_tracked(&mut deserializer).map_err(|(error, ron_error)| {
+                    let position = deserializer.span_error(ron_error).position;
+                    error.at_position(Some((position.line, position.col)))
+                })
+            },
+        )
+    }
+
+    /// Reads a dialog from a RON `reader`, like a file.
+    ///
+    /// See [`Dialog::from_ron_str`].
+    pub fn from_ron_reader<R: io::Read>(mut reader: R) -> Result<Self, DialogError>
+    where
+        Self: DeserializeOwned,
+    {
+        let mut ron = String::new();
+        reader
+            .read_to_string(&mut ron)
+            .map_err(|error| DialogError::new(error.to_string()))?;
+        Self::from_ron_str(&ron)
+    }
+
+    /// Writes the dialog as pretty-printed RON, the optional fields without `Some(...)`.
+    pub fn to_ron_string(&self) -> Result<String, DialogError>
+    where
+        Self: Serialize,
+    {
+        let config = PrettyConfig::default().extensions(Extensions::IMPLICIT_SOME);
+        ron::ser::to_string_pretty(self, config)
+            .map_err(|error| DialogError::new(error.to_string()))
+    }
+}
+
+fn spanned(error: SpannedError) -> DialogError {
+    DialogError::new(error.code.to_string())
+        .at_position(Some((error.position.line, error.position.col)))
+}
@@ -0,0 +1,300 @@
+//! Engine-agnostic traversal of a [`Dialog`].
+
+use std::fmt;
+
+use crate::{Choice, Condition, Content, Dialog, DialogCondition, DialogNode, Effect};
+
+/// Walks through a [`Dialog`], one line or one choice at a time.
+///
+/// The runner starts on the entry node of the dialog.
+/// The `trigger_event` and `effects` of this first node can be read
+/// from [`DialogRunner::current_node`] or from the [`Step`] returned by [`DialogRunner::restart`],
+/// the next ones are reported by [`Step::Node`].
+/// The runner applies no effect itself: hand them to your [`EffectHandler`](crate::EffectHandler).
+///
+/// ```rust
+/// use yml_dialog::*;
+///
+/// let dialog: Dialog = serde_yaml::from_str(
+///     "1:
+///   source: Frog
+///   content:
+///     text:
+///     - KeroKero
+///     - Hello
+///     exit_state: 2
+/// 2:
+///   source: Player
+///   content:
+///   - text: Bye
+///     exit_state: 3
+///     effects:
+///     - add_karma: -1
+///   trigger_event:
+///   - FrogTalk
+///   effects:
+///   - set_event: FrogTalk\n",
+/// )
+/// .unwrap();
+///
+/// let mut runner = DialogRunner::new(dialog);
+/// assert_eq!(runner.current_line(), Some(&"KeroKero".to_string()));
+/// assert_eq!(runner.advance(), Ok(Step::Line));
+/// assert_eq!(runner.current_line(), Some(&"Hello".to_string()));
+/// assert_eq!(
+///     runner.advance(),
+///     Ok(Step::Node {
+///         key: 2,
+///         trigger_event: vec!["FrogTalk".to_string()],
+///         effects: vec![Effect::SetEvent("FrogTalk".to_string())],
+///     })
+/// );
+/// let context = ConditionContext::default();
+/// assert_eq!(runner.available_choices(&context).len(), 1);
+/// assert_eq!(
+///     runner.choose(0, &context),
+///     Ok(Step::End {
+///         exit_state: 3,
+///         effects: vec![Effect::AddKarma(-1)],
+///     })
+/// );
+/// assert!(runner.is_ended());
+/// ```
+#[derive(Clone, Eq, PartialEq, Ord, PartialOrd, Hash, Debug)]
+pub struct DialogRunner<C = Condition, E = (), F = String> {
+    dialog: Dialog<C, E, F>,
+    /// `None` when the dialog is over.
+    current: Option<usize>,
+    /// The index of the current line within a `Content::Monolog`.
+    line: usize,
+    /// The `exit_state` which ended the dialog.
+    exit_state: Option<usize>,
+}
+
+/// What happened after a [`DialogRunner::advance`] or a [`DialogRunner::choose`].
+#[derive(Clone, Eq, PartialEq, Ord, PartialOrd, Hash, Debug)]
+pub enum Step<F = String> {
+    /// Moved to the next line of the current monolog.
+    Line,
+    /// Entered the node `key`, which throws its `trigger_event`.
+    Node {
+        /// The key of the new current node
+        key: usize,
+        /// The `trigger_event` of the new current node
+        trigger_event: Vec<String>,
+        /// The `effects` of the picked choice, if any, then the ones of the new current node
+        effects: Vec<Effect<F>>,
+    },
+    /// Reached an `exit_state` which is not a key of the dialog.
+    End {
+        /// The `exit_state` which ended the dialog
+        exit_state: usize,
+        /// The `effects` of the picked choice, if any
+        effects: Vec<Effect<F>>,
+    },
+}
+
+/// Why the [`DialogRunner`] could not advance.
+#[derive(Clone, Eq, PartialEq, Ord, PartialOrd, Hash, Debug)]
+pub enum RunnerError {
+    /// The dialog is already over.
+    Ended,
+    /// `advance` was asked on a `Content::Choices` node.
+    ExpectedChoice,
+    /// `choose` was asked on a `Content::Monolog` node.
+    ExpectedAdvance,
+    /// The asked choice is not among the available ones.
+    ChoiceOutOfRange {
+        /// The asked index
+        index: usize,
+        /// The number of available choices
+        available: usize,
+    },
+}
+
+impl fmt::Display for RunnerError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            RunnerError::Ended => write!(f, "the dialog is over"),
+            RunnerError::ExpectedChoice => write!(f, "the current node expects a choice"),
+            RunnerError::ExpectedAdvance => write!(f, "the current node is not a set of choices"),
+            RunnerError::ChoiceOutOfRange { index, available } => write!(
+                f,
+                "the choice {index} is out of range: {available} choice(s) available"
+            ),
+        }
+    }
+}
+
+impl std::error::Error for RunnerError {}
+
+impl<C, E, F: Clone> DialogRunner<C, E, F> {
+    /// Constructs a new DialogRunner, standing on the entry node of the `dialog`.
+    pub fn new(dialog: Dialog<C, E, F>) -> Self {
+        let mut runner = DialogRunner {
+            dialog,
+            current: None,
+            line: 0,
+            exit_state: None,
+        };
+        runner.restart();
+        runner
+    }
+
+    /// Goes back to the entry node of the dialog.
+    ///
+    /// Returns the [`Step`] entering this node.
+    pub fn restart(&mut self) -> Step<F> {
+        // A dialog without any node ends right away
+        let entry = self.dialog.entry().unwrap_or_default();
+        self.transit(entry, vec![])
+    }
+
+    /// Returns the read-only dialog being run.
+    pub fn dialog(&self) -> &Dialog<C, E, F> {
+        &self.dialog
+    }
+
+    /// Returns the mutable dialog being run.
+    ///
+    /// # Note
+    ///
+    /// The runner stays on the same key and line, even if they do not exist anymore.
+    pub fn dialog_mut(&mut self) -> &mut Dialog<C, E, F> {
+        &mut self.dialog
+    }
+
+    /// Replaces the dialog being run, when its file was edited for instance.
+    ///
+    /// The runner stays on the same key and line if the new dialog still has them,
+    /// otherwise it restarts and returns the [`Step`] entering the entry node.
+    /// A dialog over stays over.
+    pub fn reload(&mut self, dialog: Dialog<C, E, F>) -> Option<Step<F>> {
+        self.dialog = dialog;
+        self.current?;
+        let still_there = match self.current_node().map(DialogNode::content) {
+            None => false,
+            Some(Content::Monolog { text, .. }) => self.line < text.len(),
+            Some(Content::Choices(_)) => self.line == 0,
+        };
+        if still_there {
+            None
+        } else {
+            Some(self.restart())
+        }
+    }
+
+    /// Returns the key of the current node, `None` if the dialog is over.
+    pub fn current(&self) -> Option<usize> {
+        self.current
+    }
+
+    /// Returns the current node, `None` if the dialog is over.
+    pub fn current_node(&self) -> Option<&DialogNode<C, E, F>> {
+        self.current.and_then(|key| self.dialog.get(&key))
+    }
+
+    /// Returns the index of the current line within the current monolog.
+    pub fn line_index(&self) -> usize {
+        self.line
+    }
+
+    /// Returns the current line if the current node is a monolog.
+    pub fn current_line(&self) -> Option<&String> {
+        match self.current_node()?.content() {
+            Content::Monolog { text, .. } => text.get(self.line),
+            Content::Choices(_) => None,
+        }
+    }
+
+    /// Returns the speaker of the current node, see [`Dialog::speaker`].
+    pub fn current_speaker(&self) -> Option<&str> {
+        self.current_node().map(|node| self.dialog.speaker(node))
+    }
+
+    /// Returns `true` if the dialog is over.
+    pub fn is_ended(&self) -> bool {
+        self.current_node().is_none()
+    }
+
+    /// Returns the `exit_state` which ended the dialog, if it is over.
+    pub fn exit_state(&self) -> Option<usize> {
+        self.exit_state
+    }
+
+    /// Moves to the next line of the current monolog,
+    /// or to its `exit_state` if it was the last line.
+    pub fn advance(&mut self) -> Result<Step<F>, RunnerError> {
+        let node = self.current_node().ok_or(RunnerError::Ended)?;
+        match node.content() {
+            Content::Choices(_) => Err(RunnerError::ExpectedChoice),
+            Content::Monolog { text, exit_state } => {
+                if self.line + 1 < text.len() {
+                    self.line += 1;
+                    Ok(Step::Line)
+                } else {
+                    let exit_state = *exit_state;
+                    Ok(self.transit(exit_state, vec![]))
+                }
+            }
+        }
+    }
+
+    /// Moves to `exit_state`, reporting the `effects` of the picked choice first.
+    fn transit(&mut self, exit_state: usize, mut effects: Vec<Effect<F>>) -> Step<F> {
+        self.line = 0;
+        match self.dialog.get(&exit_state) {
+            None => {
+                self.current = None;
+                self.exit_state = Some(exit_state);
+                Step::End {
+                    exit_state,
+                    effects,
+                }
+            }
+            Some(node) => {
+                self.current = Some(exit_state);
+                self.exit_state = None;
+                effects.extend(node.effects().iter().cloned());
+                Step::Node {
+                    key: exit_state,
+                    trigger_event: node.trigger_event().clone(),
+                    effects,
+                }
+            }
+        }
+    }
+}
+
+impl<C: DialogCondition, E, F: Clone> DialogRunner<C, E, F> {
+    /// Returns the choices of the current node verified within the given `context`.
+    ///
+    /// Empty if the current node is not a set of choices.
+    pub fn available_choices(&self, context: &C::Context) -> Vec<&Choice<C, F>> {
+        match self.current_node().map(DialogNode::content) {
+            Some(Content::Choices(choices)) => choices
+                .iter()
+                .filter(|choice| choice.is_verified(context))
+                .collect(),
+            _ => vec![],
+        }
+    }
+
+    /// Picks the choice at `index` among the [`DialogRunner::available_choices`]
+    /// and moves to its `exit_state`.
+    pub fn choose(&mut self, index: usize, context: &C::Context) -> Result<Step<F>, RunnerError> {
+        let node = self.current_node().ok_or(RunnerError::Ended)?;
+        if let Content::Monolog { .. } = node.content() {
+            return Err(RunnerError::ExpectedAdvance);
+        }
+
+        let choices = self.available_choices(context);
+        let choice = choices.get(index).ok_or(RunnerError::ChoiceOutOfRange {
+            index,
+            available: choices.len(),
+        })?;
+        let exit_state = *choice.exit_state();
+        let effects = choice.effects().clone();
+        Ok(self.transit(exit_state, effects))
+    }
+}
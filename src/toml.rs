@@ -0,0 +1,98 @@
+//! Reads and writes dialogs as TOML, behind the `toml` feature.
+
+use serde::{de::DeserializeOwned, Serialize, Serializer};
+use std::{io, ops::Range};
+
+use crate::{
+    track::{deserialize_tracked, read_checked},
+    Dialog, DialogError,
+};
+
+impl<C, E, F> Dialog<C, E, F> {
+    /// Reads a dialog from a TOML string.
+    ///
+    /// The dialog has the same shape as in YAML, its numeric node keys being strings
+    /// like `[nodes.12]` or `[nodes."12"]`: TOML only keys its tables by strings.
+    /// The error tells the node, the field and the position in the file of the faulty value,
+    /// see [`DialogError`].
+    ///
+    /// ```rust
+    /// use yml_dialog::*;
+    ///
+    /// let dialog: Dialog = Dialog::from_toml_str(
+    ///     r#"name = "Frog"
+    ///
+    /// [nodes.1]
+    /// source = "Frog"
+    /// content.monolog = { text = ["KeroKero"], exit_state = 2 }
+    /// "#,
+    /// )
+    /// .unwrap();
+    ///
+    /// assert_eq!(dialog.name(), &Some("Frog".to_string()));
+    /// assert_eq!(Dialog::from_toml_str(&dialog.to_toml_string().unwrap()), Ok(dialog));
+    /// ```
+    pub fn from_toml_str(toml: &str) -> Result<Self, DialogError>
+    where
+        Self: DeserializeOwned,
+    {
+        read_checked(
+            || {
+                ::toml::from_str(toml).map_err(|error| {
+                    DialogError::new(error.message()).at_position(position(toml, error.span()))
+                })
+            },
+            || {
+                deserialize_tracked(::toml::Deserializer::new(toml)).map_err(
+                    |(error, toml_error)| error.at_position(position(toml, toml_error.span())),
+                )
+            },
+        )
+    }
+
+    /// Reads a dialog from a TOML `reader`, like a file.
+    ///
+    /// See [`Dialog::from_toml_str`].
+    pub fn from_toml_reader<R: io::Read>(mut reader: R) -> Result<Self, DialogError>
+    where
+        Self: DeserializeOwned,
+    {
+        let mut toml = String::new();
+        reader
+            .read_to_string(&mut toml)
+            .map_err(|error| DialogError::new(error.to_string()))?;
+        Self::from_toml_str(&toml)
+    }
+
+    /// Writes the dialog as TOML, its numeric node keys as strings.
+    pub fn to_toml_string(&self) -> Result<String, DialogError>
+    where
+        C: Serialize,
+        E: Serialize,
+        F: Serialize,
+    {
+        ::toml::to_string(&StringKeyed(self)).map_err(|error| DialogError::new(error.to_string()))
+    }
+}
+
+/// A [`Dialog`] written with its numeric node keys as strings.
+struct StringKeyed<'a, C, E, F>(&'a Dialog<C, E, F>);
+
+impl<C: Serialize, E: Serialize, F: Serialize> Serialize for StringKeyed<'_, C, E, F> {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        self.0.serialize_keyed(serializer, true)
+    }
+}
+
+/// Returns the line and column of the start of the `span`, both starting at 1.
+fn position(toml: &str, span: Option<Range<usize>>) -> Option<(usize, usize)> {
+    let before = toml.get(..span?.start)?;
+    let line_start = before.rfind('\n').map_or(0, |newline| newline + 1);
+    Some((
+        before.matches('\n').count() + 1,
+        before[line_start..].chars().count() + 1,
+    ))
+}
@@ -0,0 +1,592 @@
+//! Tracks the path of the value being read, to tell where a deserialization error comes from.
+//!
+//! A [`TrackedDeserializer`] wraps the deserializer of a format,
+//! and every map, sequence and enum met through it, recording the path of the deepest error.
+
+use serde::de::{
+    self, Deserialize, DeserializeSeed, Deserializer, EnumAccess, IgnoredAny, MapAccess, SeqAccess,
+    VariantAccess, Visitor,
+};
+use std::{cell::RefCell, fmt};
+
+use crate::DialogError;
+
+/// A step of the path to a value.
+#[derive(Clone, Eq, PartialEq, Ord, PartialOrd, Hash, Debug)]
+pub(crate) enum Segment {
+    /// The value of a map, or of an enum variant.
+    Key(String),
+    /// An element of a sequence.
+    Index(usize),
+    /// The value of a key which could not be read as a string.
+    Unknown,
+}
+
+/// Writes the `path` like `nodes.12.content.choices[1].exit_state`.
+pub(crate) fn display_path(path: &[Segment]) -> String {
+    let mut written = String::new();
+    for segment in path {
+        match segment {
+            Segment::Key(key) => {
+                if !written.is_empty() {
+                    written.push('.');
+                }
+                written.push_str(key);
+            }
+            Segment::Index(index) => written.push_str(&format!("[{index}]")),
+            Segment::Unknown => {
+                if !written.is_empty() {
+                    written.push('.');
+                }
+                written.push('?');
+            }
+        }
+    }
+    written
+}
+
+/// The deepest error met, with the path of the value which failed.
+#[derive(Clone, Eq, PartialEq, Debug)]
+pub(crate) struct Failure {
+    pub(crate) path: Vec<Segment>,
+    /// The error as written by the format.
+    pub(crate) message: String,
+}
+
+/// Records the [`Failure`] while a value is read.
+#[derive(Default)]
+pub(crate) struct Track {
+    failure: RefCell<Option<Failure>>,
+    /// The last map key or enum variant read.
+    key: RefCell<Option<String>>,
+}
+
+impl Track {
+    /// Returns the deepest error met, if any.
+    pub(crate) fn into_failure(self) -> Option<Failure> {
+        self.failure.into_inner()
+    }
+
+    /// Records the `error` of the value at `path`, unless it is raised again by a parent value.
+    fn trigger<E: de::Error>(&self, path: &[Segment], error: E) -> E {
+        let mut failure = self.failure.borrow_mut();
+        if failure
+            .as_ref()
+            .is_none_or(|failure| !failure.path.starts_with(path))
+        {
+            *failure = Some(Failure {
+                path: path.to_vec(),
+                message: error.to_string(),
+            });
+        }
+        error
+    }
+
+    fn capture(&self, key: String) {
+        self.key.replace(Some(key));
+    }
+
+    fn take_key(&self) -> Segment {
+        self.key.take().map_or(Segment::Unknown, Segment::Key)
+    }
+}
+
+/// Reads a `T` from the `deserializer` of a whole document.
+///
+/// The [`DialogError`] tells the node and the path of the deepest error,
+/// and its position if the format wrote it in the message.
+/// The error of the format is given back too, for its own position.
+pub(crate) fn deserialize_tracked<'de, T, D>(deserializer: D) -> Result<T, (DialogError, D::Error)>
+where
+    T: Deserialize<'de>,
+    D: Deserializer<'de>,
+{
+    let track = Track::default();
+    T::deserialize(TrackedDeserializer::new(deserializer, &track)).map_err(|error| {
+        let located = match track.into_failure() {
+            // The deepest error, before any parent value wrapped it
+            Some(failure) => {
+                let (message, position) = split_position(&failure.message);
+                DialogError::new(strip_path(message.trim_end()))
+                    .at_path(&failure.path)
+                    .at_position(position)
+            }
+            None => {
+                let (message, position) = split_position(&error.to_string());
+                DialogError::new(message.trim_end()).at_position(position)
+            }
+        };
+        (located, error)
+    })
+}
+
+/// Reads a whole document in two passes: `check_syntax` skims it, then `read` reads its values,
+/// usually through [`deserialize_tracked`].
+///
+/// The syntax errors are looked for first: met while reading the values,
+/// they would be reported within the last value read.
+pub(crate) fn read_checked<T>(
+    check_syntax: impl FnOnce() -> Result<IgnoredAny, DialogError>,
+    read: impl FnOnce() -> Result<T, DialogError>,
+) -> Result<T, DialogError> {
+    check_syntax()?;
+    read()
+}
+
+/// Takes the first ` at line 48 column 7` out of an error written by `serde_yaml` or `serde_json`.
+pub(crate) fn split_position(message: &str) -> (String, Option<(usize, usize)>) {
+    let mut searched = 0;
+    while let Some(found) = message[searched..].find(" at line ") {
+        let start = searched + found;
+        let rest = &message[start + " at line ".len()..];
+        let line_end = rest
+            .find(|c: char| !c.is_ascii_digit())
+            .unwrap_or(rest.len());
+        if let Some(column) = rest[line_end..].strip_prefix(" column ") {
+            let column_end = column
+                .find(|c: char| !c.is_ascii_digit())
+                .unwrap_or(column.len());
+            if let (Ok(line), Ok(column_number)) =
+                (rest[..line_end].parse(), column[..column_end].parse())
+            {
+                let message = format!("{}{}", &message[..start], &column[column_end..]);
+                return (message, Some((line, column_number)));
+            }
+        }
+        searched = start + 1;
+    }
+    (message.to_string(), None)
+}
+
+/// Removes the `nodes.12.content: ` path written by `serde_yaml` before the message.
+fn strip_path(message: &str) -> &str {
+    match message.split_once(": ") {
+        Some((path, rest)) if !path.is_empty() && !path.contains(char::is_whitespace) => rest,
+        _ => message,
+    }
+}
+
+/// Where a wrapped value stands.
+#[derive(Clone)]
+struct Position<'t> {
+    path: Vec<Segment>,
+    track: &'t Track,
+}
+
+impl<'t> Position<'t> {
+    fn child(&self, segment: Segment) -> Self {
+        let mut path = self.path.clone();
+        path.push(segment);
+        Position {
+            path,
+            track: self.track,
+        }
+    }
+
+    fn trigger<E: de::Error>(&self, error: E) -> E {
+        self.track.trigger(&self.path, error)
+    }
+}
+
+/// Wraps a [`Deserializer`], tracking the path of the values read through it.
+pub(crate) struct TrackedDeserializer<'t, D> {
+    inner: D,
+    at: Position<'t>,
+    /// Records the value read as the key of a map or as an enum variant.
+    capture: bool,
+}
+
+impl<'t, D> TrackedDeserializer<'t, D> {
+    /// Wraps the `deserializer` of a whole document, recording its errors in the `track`.
+    pub(crate) fn new(deserializer: D, track: &'t Track) -> Self {
+        TrackedDeserializer {
+            inner: deserializer,
+            at: Position {
+                path: Vec::new(),
+                track,
+            },
+            capture: false,
+        }
+    }
+
+    fn visitor<V>(&self, visitor: V) -> TrackedVisitor<'t, V> {
+        TrackedVisitor {
+            inner: visitor,
+            at: self.at.clone(),
+            capture: self.capture,
+        }
+    }
+}
+
+macro_rules! forward_deserialize {
+    ($($method:ident)*) => {
+        $(
+            fn $method<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Self::Error> {
+                let visitor = self.visitor(visitor);
+                self.inner.$method(visitor).map_err(|error| self.at.trigger(error))
+            }
+        )*
+    };
+}
+
+impl<'de, D: Deserializer<'de>> Deserializer<'de> for TrackedDeserializer<'_, D> {
+    type Error = D::Error;
+
+    forward_deserialize! {
+        deserialize_any deserialize_bool
+        deserialize_i8 deserialize_i16 deserialize_i32 deserialize_i64 deserialize_i128
+        deserialize_u8 deserialize_u16 deserialize_u32 deserialize_u64 deserialize_u128
+        deserialize_f32 deserialize_f64 deserialize_char deserialize_str deserialize_string
+        deserialize_bytes deserialize_byte_buf deserialize_option deserialize_unit
+        deserialize_seq deserialize_map deserialize_identifier deserialize_ignored_any
+    }
+
+    fn deserialize_unit_struct<V: Visitor<'de>>(
+        self,
+        name: &'static str,
+        visitor: V,
+    ) -> Result<V::Value, Self::Error> {
+        let visitor = self.visitor(visitor);
+        self.inner
+            .deserialize_unit_struct(name, visitor)
+            .map_err(|error| self.at.trigger(error))
+    }
+
+    fn deserialize_newtype_struct<V: Visitor<'de>>(
+        self,
+        name: &'static str,
+        visitor: V,
+    ) -> Result<V::Value, Self::Error> {
+        let visitor = self.visitor(visitor);
+        self.inner
+            .deserialize_newtype_struct(name, visitor)
+            .map_err(|error| self.at.trigger(error))
+    }
+
+    fn deserialize_tuple<V: Visitor<'de>>(
+        self,
+        len: usize,
+        visitor: V,
+    ) -> Result<V::Value, Self::Error> {
+        let visitor = self.visitor(visitor);
+        self.inner
+            .deserialize_tuple(len, visitor)
+            .map_err(|error| self.at.trigger(error))
+    }
+
+    fn deserialize_tuple_struct<V: Visitor<'de>>(
+        self,
+        name: &'static str,
+        len: usize,
+        visitor: V,
+    ) -> Result<V::Value, Self::Error> {
+        let visitor = self.visitor(visitor);
+        self.inner
+            .deserialize_tuple_struct(name, len, visitor)
+            .map_err(|error| self.at.trigger(error))
+    }
+
+    fn deserialize_struct<V: Visitor<'de>>(
+        self,
+        name: &'static str,
+        fields: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value, Self::Error> {
+        let visitor = self.visitor(visitor);
+        self.inner
+            .deserialize_struct(name, fields, visitor)
+            .map_err(|error| self.at.trigger(error))
+    }
+
+    fn deserialize_enum<V: Visitor<'de>>(
+        self,
+        name: &'static str,
+        variants: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value, Self::Error> {
+        let visitor = self.visitor(visitor);
+        self.inner
+            .deserialize_enum(name, variants, visitor)
+            .map_err(|error| self.at.trigger(error))
+    }
+
+    fn is_human_readable(&self) -> bool {
+        self.inner.is_human_readable()
+    }
+}
+
+/// Wraps the [`Visitor`] of a tracked value, to wrap the maps, sequences and enums it visits.
+struct TrackedVisitor<'t, V> {
+    inner: V,
+    at: Position<'t>,
+    capture: bool,
+}
+
+impl<'t, V> TrackedVisitor<'t, V> {
+    fn capture(&self, key: impl fmt::Display) {
+        if self.capture {
+            self.at.track.capture(key.to_string());
+        }
+    }
+
+    fn deserializer<D>(&self, deserializer: D) -> TrackedDeserializer<'t, D> {
+        TrackedDeserializer {
+            inner: deserializer,
+            at: self.at.clone(),
+            capture: self.capture,
+        }
+    }
+}
+
+macro_rules! forward_visit {
+    ($($method:ident: $value:ty)*) => {
+        $(
+            fn $method<E: de::Error>(self, value: $value) -> Result<V::Value, E> {
+                self.capture(&value);
+                self.inner.$method(value)
+            }
+        )*
+    };
+}
+
+impl<'de, V: Visitor<'de>> Visitor<'de> for TrackedVisitor<'_, V> {
+    type Value = V::Value;
+
+    fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        self.inner.expecting(formatter)
+    }
+
+    forward_visit! {
+        visit_bool: bool
+        visit_i8: i8 visit_i16: i16 visit_i32: i32 visit_i64: i64 visit_i128: i128
+        visit_u8: u8 visit_u16: u16 visit_u32: u32 visit_u64: u64 visit_u128: u128
+        visit_f32: f32 visit_f64: f64 visit_char: char
+        visit_str: &str visit_borrowed_str: &'de str visit_string: String
+    }
+
+    fn visit_bytes<E: de::Error>(self, value: &[u8]) -> Result<V::Value, E> {
+        self.inner.visit_bytes(value)
+    }
+
+    fn visit_borrowed_bytes<E: de::Error>(self, value: &'de [u8]) -> Result<V::Value, E> {
+        self.inner.visit_borrowed_bytes(value)
+    }
+
+    fn visit_byte_buf<E: de::Error>(self, value: Vec<u8>) -> Result<V::Value, E> {
+        self.inner.visit_byte_buf(value)
+    }
+
+    fn visit_none<E: de::Error>(self) -> Result<V::Value, E> {
+        self.inner.visit_none()
+    }
+
+    fn visit_unit<E: de::Error>(self) -> Result<V::Value, E> {
+        self.inner.visit_unit()
+    }
+
+    fn visit_some<D: Deserializer<'de>>(self, deserializer: D) -> Result<V::Value, D::Error> {
+        let deserializer = self.deserializer(deserializer);
+        self.inner.visit_some(deserializer)
+    }
+
+    fn visit_newtype_struct<D: Deserializer<'de>>(
+        self,
+        deserializer: D,
+    ) -> Result<V::Value, D::Error> {
+        let deserializer = self.deserializer(deserializer);
+        self.inner.visit_newtype_struct(deserializer)
+    }
+
+    fn visit_seq<A: SeqAccess<'de>>(self, seq: A) -> Result<V::Value, A::Error> {
+        self.inner.visit_seq(TrackedSeq {
+            inner: seq,
+            at: self.at,
+            index: 0,
+        })
+    }
+
+    fn visit_map<A: MapAccess<'de>>(self, map: A) -> Result<V::Value, A::Error> {
+        self.inner.visit_map(TrackedMap {
+            inner: map,
+            at: self.at,
+            key: None,
+        })
+    }
+
+    fn visit_enum<A: EnumAccess<'de>>(self, data: A) -> Result<V::Value, A::Error> {
+        self.inner.visit_enum(TrackedEnum {
+            inner: data,
+            at: self.at,
+        })
+    }
+}
+
+/// Wraps the seed of a tracked value.
+struct TrackedSeed<'t, S> {
+    inner: S,
+    at: Position<'t>,
+    capture: bool,
+}
+
+impl<'de, S: DeserializeSeed<'de>> DeserializeSeed<'de> for TrackedSeed<'_, S> {
+    type Value = S::Value;
+
+    fn deserialize<D: Deserializer<'de>>(self, deserializer: D) -> Result<S::Value, D::Error> {
+        self.inner.deserialize(TrackedDeserializer {
+            inner: deserializer,
+            at: self.at,
+            capture: self.capture,
+        })
+    }
+}
+
+struct TrackedSeq<'t, A> {
+    inner: A,
+    at: Position<'t>,
+    index: usize,
+}
+
+impl<'de, A: SeqAccess<'de>> SeqAccess<'de> for TrackedSeq<'_, A> {
+    type Error = A::Error;
+
+    fn next_element_seed<S: DeserializeSeed<'de>>(
+        &mut self,
+        seed: S,
+    ) -> Result<Option<S::Value>, A::Error> {
+        let at = self.at.child(Segment::Index(self.index));
+        self.index += 1;
+        self.inner
+            .next_element_seed(TrackedSeed {
+                inner: seed,
+                at: at.clone(),
+                capture: false,
+            })
+            .map_err(|error| at.trigger(error))
+    }
+
+    fn size_hint(&self) -> Option<usize> {
+        self.inner.size_hint()
+    }
+}
+
+struct TrackedMap<'t, A> {
+    inner: A,
+    at: Position<'t>,
+    /// The key of the value to read.
+    key: Option<Segment>,
+}
+
+impl<'de, A: MapAccess<'de>> MapAccess<'de> for TrackedMap<'_, A> {
+    type Error = A::Error;
+
+    fn next_key_seed<K: DeserializeSeed<'de>>(
+        &mut self,
+        seed: K,
+    ) -> Result<Option<K::Value>, A::Error> {
+        self.at.track.key.replace(None);
+        let key = self
+            .inner
+            .next_key_seed(TrackedSeed {
+                inner: seed,
+                at: self.at.clone(),
+                capture: true,
+            })
+            .map_err(|error| self.at.trigger(error))?;
+        self.key = Some(self.at.track.take_key());
+        Ok(key)
+    }
+
+    fn next_value_seed<S: DeserializeSeed<'de>>(&mut self, seed: S) -> Result<S::Value, A::Error> {
+        let at = self.at.child(self.key.take().unwrap_or(Segment::Unknown));
+        self.inner
+            .next_value_seed(TrackedSeed {
+                inner: seed,
+                at: at.clone(),
+                capture: false,
+            })
+            .map_err(|error| at.trigger(error))
+    }
+
+    fn size_hint(&self) -> Option<usize> {
+        self.inner.size_hint()
+    }
+}
+
+struct TrackedEnum<'t, A> {
+    inner: A,
+    at: Position<'t>,
+}
+
+impl<'de, 't, A: EnumAccess<'de>> EnumAccess<'de> for TrackedEnum<'t, A> {
+    type Error = A::Error;
+    type Variant = TrackedVariant<'t, A::Variant>;
+
+    fn variant_seed<S: DeserializeSeed<'de>>(
+        self,
+        seed: S,
+    ) -> Result<(S::Value, Self::Variant), A::Error> {
+        self.at.track.key.replace(None);
+        let (value, variant) = self
+            .inner
+            .variant_seed(TrackedSeed {
+                inner: seed,
+                at: self.at.clone(),
+                capture: true,
+            })
+            .map_err(|error| self.at.trigger(error))?;
+        let at = self.at.child(self.at.track.take_key());
+        Ok((value, TrackedVariant { inner: variant, at }))
+    }
+}
+
+struct TrackedVariant<'t, A> {
+    inner: A,
+    at: Position<'t>,
+}
+
+impl<'de, A: VariantAccess<'de>> VariantAccess<'de> for TrackedVariant<'_, A> {
+    type Error = A::Error;
+
+    fn unit_variant(self) -> Result<(), A::Error> {
+        self.inner
+            .unit_variant()
+            .map_err(|error| self.at.trigger(error))
+    }
+
+    fn newtype_variant_seed<S: DeserializeSeed<'de>>(self, seed: S) -> Result<S::Value, A::Error> {
+        let seed = TrackedSeed {
+            inner: seed,
+            at: self.at.clone(),
+            capture: false,
+        };
+        self.inner
+            .newtype_variant_seed(seed)
+            .map_err(|error| self.at.trigger(error))
+    }
+
+    fn tuple_variant<V: Visitor<'de>>(self, len: usize, visitor: V) -> Result<V::Value, A::Error> {
+        let visitor = TrackedVisitor {
+            inner: visitor,
+            at: self.at.clone(),
+            capture: false,
+        };
+        self.inner
+            .tuple_variant(len, visitor)
+            .map_err(|error| self.at.trigger(error))
+    }
+
+    fn struct_variant<V: Visitor<'de>>(
+        self,
+        fields: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value, A::Error> {
+        let visitor = TrackedVisitor {
+            inner: visitor,
+            at: self.at.clone(),
+            capture: false,
+        };
+        self.inner
+            .struct_variant(fields, visitor)
+            .map_err(|error| self.at.trigger(error))
+    }
+}
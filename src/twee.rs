@@ -0,0 +1,500 @@
+//! Imports and exports Twine stories written in Twee 3, see [`import_twee`] and [`Dialog::to_twee_string`].
+//!
+//! A passage is read as a node of its lines, leading to a node of its links if any.
+//! Its name becomes the label of its first node.
+
+use std::collections::{BTreeMap, BTreeSet};
+
+use crate::{
+    import::{
+        collapse, export_names, Imported, ImportedChoice, ImportedContent, ImportedFlow, Target,
+    },
+    Content, Dialog, ImportReport,
+};
+
+/// Converts a [Twine](https://twinery.org) story written in
+/// [Twee 3](https://github.com/iftechfoundation/twine-specs/blob/master/twee-3-specification.md)
+/// into a [`Dialog`].
+///
+/// Each passage `:: Name` becomes a [`Content::Monolog`] of its lines, labeled `Name`,
+/// leading to a [`Content::Choices`] of its links `[[Target]]`, `[[text->Target]]`,
+/// `[[Target<-text]]` or `[[text|Target]]`, if any.
+/// A passage without lines is only its choices, and a passage without links ends the dialog,
+/// right away if it is empty.
+/// The links within a line are kept there as their text.
+///
+/// The keys are given in the order of the passages, starting at 1,
+/// the lines of a passage before its links: a same story is always converted into the same keys.
+/// The end of the dialog is the key after the last node.
+///
+/// The `StoryTitle` passage becomes the name of the dialog,
+/// and the `start` passage of `StoryData` its entry, `Start` or else the first passage by default.
+/// Anything else, like the tags or the macros of the story formats,
+/// is reported in the [`ImportReport`] with its line, and left out or kept as written.
+///
+/// ```rust
+/// use yml_dialog::*;
+///
+/// let (dialog, report) = import_twee(
+///     ":: StoryTitle
+/// Frog Pond
+///
+/// :: Start {\"position\":\"100,100\"}
+/// KeroKero
+/// [[Hi->Greet]]
+/// [[Bye]]
+///
+/// :: Greet [happy]
+/// Hello there!
+///
+/// :: Bye
+/// Bye bye.
+/// ",
+/// );
+///
+/// assert_eq!(report.issues().len(), 1);
+/// assert_eq!(report.issues()[0].to_string(), "line 9: tags `happy` left out");
+///
+/// assert_eq!(dialog.name(), &Some("Frog Pond".to_string()));
+/// assert_eq!(dialog.entry(), Some(1));
+/// assert_eq!(dialog.label(&1), Some(&"Start".to_string()));
+/// assert_eq!(
+///     dialog.get(&1).unwrap().content(),
+///     &Content::Monolog { text: vec!["KeroKero".to_string()], exit_state: 2 }
+/// );
+/// assert_eq!(
+///     dialog.get(&2).unwrap().content(),
+///     &Content::Choices(vec![
+///         Choice::new("Hi".to_string(), None, 3),
+///         Choice::new("Bye".to_string(), None, 4),
+///     ])
+/// );
+/// assert_eq!(dialog.key_of("Greet"), Some(3));
+/// assert_eq!(dialog.key_of("Bye"), Some(4));
+///
+/// // And back
+/// assert_eq!(import_twee(&dialog.to_twee_string()).0, dialog);
+/// ```
+pub fn import_twee(twee: &str) -> (Dialog, ImportReport) {
+    let mut report = ImportReport::default();
+    let story = parse(twee, &mut report);
+    let dialog = compile(&story, &mut report);
+    (dialog, report.finish())
+}
+
+#[derive(Default)]
+struct Story {
+    title: Option<String>,
+    /// The `start` of `StoryData`, and its line.
+    start: Option<(String, usize)>,
+    passages: Vec<Passage>,
+}
+
+struct Passage {
+    name: String,
+    number: usize,
+    lines: Vec<(usize, String)>,
+}
+
+/// Splits the story into its passages, reporting what is left out.
+fn parse(twee: &str, report: &mut ImportReport) -> Story {
+    let mut story = Story::default();
+    let mut current: Option<Passage> = None;
+    // Within a script or a stylesheet
+    let mut skipped = false;
+
+    let finish = |passage: Passage, story: &mut Story, report: &mut ImportReport| {
+        let text = || {
+            passage
+                .lines
+                .iter()
+                .map(|(_, line)| line.as_str())
+                .collect::<Vec<_>>()
+                .join("\n")
+        };
+        match passage.name.as_str() {
+            "StoryTitle" => story.title = Some(text().trim().to_string()),
+            "StoryData" => story.start = story_start(&text()).map(|start| (start, passage.number)),
+            _ if story
+                .passages
+                .iter()
+                .any(|other| other.name == passage.name) =>
+            {
+                report.report(
+                    passage.number,
+                    format!("passage `{}` defined again, left out", passage.name),
+                );
+            }
+            _ => story.passages.push(passage),
+        }
+    };
+
+    for (index, line) in twee.lines().enumerate() {
+        let number = index + 1;
+        if let Some(header) = line.strip_prefix("::") {
+            if let Some(passage) = current.take() {
+                finish(passage, &mut story, report);
+            }
+            let (name, tags) = passage_header(header);
+            match tags
+                .iter()
+                .find(|tag| *tag == "script" || *tag == "stylesheet")
+            {
+                Some(tag) => {
+                    report.report(number, format!("{tag} passage `{name}` left out"));
+                    skipped = true;
+                    continue;
+                }
+                None if !tags.is_empty() => {
+                    report.report(number, format!("tags `{}` left out", tags.join(" ")))
+                }
+                None => {}
+            }
+            skipped = false;
+            current = Some(Passage {
+                name,
+                number,
+                lines: Vec::new(),
+            });
+            continue;
+        }
+        match &mut current {
+            Some(passage) => passage.lines.push((number, line.to_string())),
+            None if !skipped && !line.trim().is_empty() => report.report(
+                number,
+                format!("`{}` out of a passage, left out", line.trim()),
+            ),
+            None => {}
+        }
+    }
+    if let Some(passage) = current {
+        finish(passage, &mut story, report);
+    }
+    story
+}
+
+/// Reads `Name [tags] {metadata}`, the metadata being left out.
+fn passage_header(header: &str) -> (String, Vec<String>) {
+    let mut name = String::new();
+    let mut tags = String::new();
+    let mut chars = header.trim().chars();
+    while let Some(c) = chars.next() {
+        match c {
+            '\\' => name.extend(chars.next()),
+            '[' => {
+                tags.extend(chars.by_ref().take_while(|c| *c != ']'));
+                break;
+            }
+            '{' => break,
+            c => name.push(c),
+        }
+    }
+    (
+        name.trim().to_string(),
+        tags.split_whitespace().map(str::to_string).collect(),
+    )
+}
+
+/// Finds the `"start": "Name"` of the `StoryData` JSON.
+fn story_start(data: &str) -> Option<String> {
+    let (_, rest) = data.split_once("\"start\"")?;
+    let rest = rest.trim_start().strip_prefix(':')?.trim_start();
+    let mut chars = rest.strip_prefix('"')?.chars();
+    let mut start = String::new();
+    loop {
+        match chars.next()? {
+            '"' => return Some(start),
+            '\\' => start.push(chars.next()?),
+            c => start.push(c),
+        }
+    }
+}
+
+/// A `[[link]]` of a passage.
+struct Link {
+    number: usize,
+    text: String,
+    target: String,
+}
+
+/// Splits the links off a line, keeping their text in the line unless it is made of links alone.
+fn split_links(line: &str, number: usize, links: &mut Vec<Link>) -> String {
+    let mut text = String::new();
+    let mut outside = String::new();
+    let mut rest = line;
+    while let Some((before, after)) = rest.split_once("[[") {
+        let Some((link, after)) = after.split_once("]]") else {
+            break;
+        };
+        let (shown, target) = if let Some((shown, target)) = link.rsplit_once("->") {
+            (shown, target)
+        } else if let Some((target, shown)) = link.split_once("<-") {
+            (shown, target)
+        } else if let Some((shown, target)) = link.split_once('|') {
+            (shown, target)
+        } else {
+            (link, link)
+        };
+        links.push(Link {
+            number,
+            text: collapse(shown),
+            target: target.trim().to_string(),
+        });
+        text.push_str(before);
+        text.push_str(shown);
+        outside.push_str(before);
+        rest = after;
+    }
+    text.push_str(rest);
+    outside.push_str(rest);
+    if outside.trim().is_empty() {
+        String::new()
+    } else {
+        collapse(&text)
+    }
+}
+
+fn compile(story: &Story, report: &mut ImportReport) -> Dialog {
+    if story.passages.is_empty() {
+        return Dialog::default();
+    }
+    let find = |name: &str| {
+        story
+            .passages
+            .iter()
+            .position(|passage| passage.name == name)
+    };
+
+    let mut imported = Imported::default();
+    for passage in &story.passages {
+        let mut text = Vec::new();
+        let mut links = Vec::new();
+        for (number, line) in &passage.lines {
+            let line = split_links(line, *number, &mut links);
+            if has_macro(&line) {
+                report.report(*number, format!("macro kept as written: `{line}`"));
+            }
+            if !line.is_empty() {
+                text.push((*number, line));
+            }
+        }
+
+        let mut entry = Target::End;
+        if let Some(first) = links.first() {
+            let choices = links
+                .iter()
+                .map(|link| ImportedChoice {
+                    text: link.text.clone(),
+                    condition: None,
+                    effects: Vec::new(),
+                    exit_state: match find(&link.target) {
+                        Some(passage) => Target::Flow(passage),
+                        None => {
+                            report.report(
+                                link.number,
+                                format!(
+                                    "unknown link target `{}`, the dialog ends there",
+                                    link.target
+                                ),
+                            );
+                            Target::End
+                        }
+                    },
+                })
+                .collect();
+            entry = Target::Node(imported.push(first.number, ImportedContent::Choices(choices)));
+        }
+        if !text.is_empty() {
+            let lines = text.into_iter().map(|(_, line)| line).collect();
+            entry =
+                Target::Node(imported.push(passage.number, ImportedContent::Monolog(lines, entry)));
+        }
+        imported.flows.push(ImportedFlow {
+            label: passage.name.clone(),
+            number: passage.number,
+            entry,
+        });
+    }
+
+    let start = match &story.start {
+        Some((start, number)) => find(start).or_else(|| {
+            report.report(*number, format!("unknown start passage `{start}`"));
+            None
+        }),
+        None => None,
+    };
+    let entries = imported.flow_entries("links", report);
+    let mut dialog = imported.into_dialog(&entries, start.or_else(|| find("Start")).unwrap_or(0));
+    *dialog.name_mut() = story.title.clone();
+    dialog
+}
+
+impl<C, E, F> Dialog<C, E, F> {
+    /// Writes the dialog as a [Twine](https://twinery.org) story in Twee 3, see [`import_twee`].
+    ///
+    /// Each node is written as a passage, named by its label, `Start` for an unlabeled entry,
+    /// or `node_` and its key, in the order of the keys: importing the story gives them back.
+    /// A monolog is written as its lines, followed by the links of the choices it leads to
+    /// if they are not labeled and reached from there only, or else by a `[[Continue->...]]` link.
+    /// The `exit_state`s which are not nodes are written as empty passages, ending the story.
+    /// The name of the dialog becomes the `StoryTitle`, and the entry the `start` of `StoryData`.
+    ///
+    /// Twee has no speaker, condition or effect: the `source`, the `condition`s, the `trigger_event`s,
+    /// the `effects` and the `extra` data of the nodes are left out.
+    /// No IFID is written in `StoryData`: Twine gives one to the story when it is imported.
+    ///
+    /// ```rust
+    /// use yml_dialog::*;
+    ///
+    /// let dialog: Dialog = serde_yaml::from_str(
+    ///     "1:
+    ///   source: Frog
+    ///   content:
+    ///     monolog:
+    ///       text: [KeroKero]
+    ///       exit_state: 2
+    /// 2:
+    ///   source: Player
+    ///   content:
+    ///     choices:
+    ///     - text: Hi
+    ///       exit_state: greet
+    ///     - text: Bye
+    ///       exit_state: 4
+    /// greet:
+    ///   source: Frog
+    ///   content:
+    ///     monolog:
+    ///       text: [Hello there!]
+    ///       exit_state: 1\n",
+    /// )
+    /// .unwrap();
+    ///
+    /// assert_eq!(
+    ///     dialog.to_twee_string(),
+    ///     ":: StoryData
+    /// {
+    ///   \"start\": \"Start\"
+    /// }
+    ///
+    /// :: Start
+    /// KeroKero
+    /// [[Hi->greet]]
+    /// [[Bye->node_4]]
+    ///
+    /// :: greet
+    /// Hello there!
+    /// [[Continue->Start]]
+    ///
+    /// :: node_4
+    /// "
+    /// );
+    /// ```
+    pub fn to_twee_string(&self) -> String {
+        let (mut names, _) = export_names(self);
+        let entry = self.entry().filter(|entry| self.contains(entry));
+        let mut references: BTreeMap<usize, usize> = BTreeMap::new();
+        let mut after_monolog = BTreeSet::new();
+        for node in self.nodes().values() {
+            let exit_states: Vec<&usize> = match node.content() {
+                Content::Monolog { exit_state, .. } => {
+                    after_monolog.insert(*exit_state);
+                    vec![exit_state]
+                }
+                Content::Choices(choices) => {
+                    choices.iter().map(|choice| choice.exit_state()).collect()
+                }
+            };
+            for exit_state in exit_states {
+                *references.entry(*exit_state).or_default() += 1;
+                if !self.contains(exit_state) {
+                    names
+                        .entry(*exit_state)
+                        .or_insert_with(|| format!("node_{exit_state}"));
+                }
+            }
+        }
+        // The choices written within the passage of the monolog leading to them
+        let inlined = |key: &usize| {
+            Some(*key) != entry
+                && self.label(key).is_none()
+                && references.get(key) == Some(&1)
+                && after_monolog.contains(key)
+                && matches!(
+                    self.get(key).map(|node| node.content()),
+                    Some(Content::Choices(_))
+                )
+        };
+
+        let mut passages = Vec::new();
+        if let Some(name) = self.name() {
+            passages.push(format!(":: StoryTitle\n{name}\n"));
+        }
+        if let Some(entry) = &entry {
+            passages.push(format!(
+                ":: StoryData\n{{\n  \"start\": \"{}\"\n}}\n",
+                names[entry].replace('\\', "\\\\").replace('"', "\\\"")
+            ));
+        }
+
+        let link = |text: &String, key: &usize| {
+            let target = &names[key];
+            if text == target {
+                format!("[[{target}]]")
+            } else {
+                format!("[[{text}->{target}]]")
+            }
+        };
+        for key in self.nodes().keys().filter(|key| !inlined(key)) {
+            let mut lines = vec![format!(":: {}", escape_name(&names[key]))];
+            let mut choices = None;
+            match self.nodes()[key].content() {
+                Content::Monolog { text, exit_state } => {
+                    lines.extend(text.iter().cloned());
+                    match self.get(exit_state).map(|node| node.content()) {
+                        Some(Content::Choices(inlined_choices)) if inlined(exit_state) => {
+                            choices = Some(inlined_choices)
+                        }
+                        Some(_) => lines.push(link(&"Continue".to_string(), exit_state)),
+                        None => {}
+                    }
+                }
+                Content::Choices(own_choices) => choices = Some(own_choices),
+            }
+            for choice in choices.into_iter().flatten() {
+                lines.push(link(choice.text(), choice.exit_state()));
+            }
+            lines.push(String::new());
+            passages.push(lines.join("\n"));
+        }
+        // The ends, as empty passages
+        for (key, name) in &names {
+            if !self.contains(key) {
+                passages.push(format!(":: {}\n", escape_name(name)));
+            }
+        }
+        passages.join("\n")
+    }
+}
+
+/// Returns `true` if the line holds a SugarCube `<<macro>>` or a Harlowe `(macro:)`.
+fn has_macro(line: &str) -> bool {
+    line.contains("<<")
+        || line.split('(').skip(1).any(|after| {
+            after.split_once(':').is_some_and(|(name, _)| {
+                !name.is_empty() && name.chars().all(|c| c.is_alphanumeric() || c == '-')
+            })
+        })
+}
+
+/// Escapes the characters of a passage name which start its tags or metadata.
+fn escape_name(name: &str) -> String {
+    let mut escaped = String::with_capacity(name.len());
+    for c in name.chars() {
+        if matches!(c, '\\' | '[' | ']' | '{' | '}') {
+            escaped.push('\\');
+        }
+        escaped.push(c);
+    }
+    escaped
+}
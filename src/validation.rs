@@ -0,0 +1,382 @@
+//! Checks on the graph of a [`Dialog`].
+
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    fmt,
+};
+
+use crate::{Content, Dialog, DialogNode, TextPosition};
+
+/// How bad a [`Diagnostic`] is.
+#[derive(Copy, Clone, Eq, PartialEq, Ord, PartialOrd, Hash, Debug)]
+pub enum Severity {
+    /// The dialog is broken.
+    Error,
+    /// The dialog works but is probably not what the writer intended.
+    Warning,
+}
+
+impl fmt::Display for Severity {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Severity::Error => write!(f, "error"),
+            Severity::Warning => write!(f, "warning"),
+        }
+    }
+}
+
+/// What a [`Diagnostic`] is about.
+#[derive(Clone, Eq, PartialEq, Ord, PartialOrd, Hash, Debug)]
+pub enum DiagnosticKind {
+    /// The explicit entry is not a node of the dialog.
+    MissingEntry {
+        /// The explicit entry
+        entry: usize,
+    },
+    /// An `exit_state` points to no node while being lower than the greatest key without label.
+    ///
+    /// An `exit_state` greater than all keys is an end of the dialog.
+    DanglingExit {
+        /// The `exit_state` pointing to no node
+        exit_state: usize,
+    },
+    /// The node cannot be reached from the entry node.
+    UnreachableNode,
+    /// No end of the dialog can be reached from this node.
+    NoReachableEnd,
+    /// A `Content::Monolog` without any line.
+    EmptyText,
+    /// A `Content::Choices` without any choice.
+    EmptyChoices,
+    /// A choice without text.
+    EmptyChoiceText,
+    /// A choice with the same text and `exit_state` than a previous one.
+    DuplicateChoice {
+        /// The index of the first occurence of this choice
+        first: usize,
+    },
+    /// A string with the same id than a previous one, see [`Dialog::text_id`].
+    DuplicateStringId {
+        /// The duplicated id
+        id: String,
+        /// The key of the node of its first occurence
+        first: usize,
+    },
+}
+
+impl DiagnosticKind {
+    /// Returns the default severity of this kind of diagnostic.
+    pub fn severity(&self) -> Severity {
+        match self {
+            DiagnosticKind::MissingEntry { .. }
+            | DiagnosticKind::DanglingExit { .. }
+            | DiagnosticKind::EmptyChoices
+            | DiagnosticKind::DuplicateStringId { .. } => Severity::Error,
+            DiagnosticKind::UnreachableNode
+            | DiagnosticKind::NoReachableEnd
+            | DiagnosticKind::EmptyText
+            | DiagnosticKind::EmptyChoiceText
+            | DiagnosticKind::DuplicateChoice { .. } => Severity::Warning,
+        }
+    }
+}
+
+impl fmt::Display for DiagnosticKind {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            DiagnosticKind::MissingEntry { entry } => {
+                write!(f, "the entry `{entry}` is not a node of the dialog")
+            }
+            DiagnosticKind::DanglingExit { exit_state } => {
+                write!(f, "the exit_state `{exit_state}` points to no node")
+            }
+            DiagnosticKind::UnreachableNode => write!(f, "unreachable from the entry node"),
+            DiagnosticKind::NoReachableEnd => write!(f, "no end of the dialog can be reached"),
+            DiagnosticKind::EmptyText => write!(f, "the monolog has no line"),
+            DiagnosticKind::EmptyChoices => write!(f, "the node has no choice"),
+            DiagnosticKind::EmptyChoiceText => write!(f, "the choice has no text"),
+            DiagnosticKind::DuplicateChoice { first } => {
+                write!(f, "the choice duplicates `content[{first}]`")
+            }
+            DiagnosticKind::DuplicateStringId { id, first } => {
+                write!(
+                    f,
+                    "the string id `{id}` is already used in the node {first}"
+                )
+            }
+        }
+    }
+}
+
+/// A problem found by [`Dialog::validate`].
+#[derive(Clone, Eq, PartialEq, Ord, PartialOrd, Hash, Debug)]
+pub struct Diagnostic {
+    severity: Severity,
+    node: Option<usize>,
+    path: String,
+    kind: DiagnosticKind,
+}
+
+impl Diagnostic {
+    /// Constructs a new Diagnostic with the default severity of its `kind`.
+    pub fn new(node: Option<usize>, path: String, kind: DiagnosticKind) -> Self {
+        Diagnostic {
+            severity: kind.severity(),
+            node,
+            path,
+            kind,
+        }
+    }
+
+    /// Returns the `severity` of the diagnostic.
+    pub fn severity(&self) -> Severity {
+        self.severity
+    }
+
+    /// Returns `true` if the diagnostic is an error.
+    pub fn is_error(&self) -> bool {
+        self.severity == Severity::Error
+    }
+
+    /// Returns the key of the node concerned, `None` if it concerns the whole dialog.
+    pub fn node(&self) -> Option<usize> {
+        self.node
+    }
+
+    /// Returns the path of the field concerned within the node, like `content[1].exit_state`.
+    pub fn path(&self) -> &String {
+        &self.path
+    }
+
+    /// Returns the `kind` of the diagnostic.
+    pub fn kind(&self) -> &DiagnosticKind {
+        &self.kind
+    }
+}
+
+impl fmt::Display for Diagnostic {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}: ", self.severity)?;
+        if let Some(node) = self.node {
+            write!(f, "node {node}")?;
+            if !self.path.is_empty() {
+                write!(f, ", ")?;
+            }
+        }
+        if !self.path.is_empty() {
+            write!(f, "{}", self.path)?;
+        }
+        write!(f, ": {}", self.kind)
+    }
+}
+
+/// Returns the `exit_state`s of the node with their field path.
+fn exits<C, E, F>(node: &DialogNode<C, E, F>) -> Vec<(String, usize)> {
+    match node.content() {
+        Content::Monolog { exit_state, .. } => {
+            vec![("content.exit_state".to_string(), *exit_state)]
+        }
+        Content::Choices(choices) => choices
+            .iter()
+            .enumerate()
+            .map(|(index, choice)| (format!("content[{index}].exit_state"), *choice.exit_state()))
+            .collect(),
+    }
+}
+
+impl<C, E, F> Dialog<C, E, F> {
+    /// Checks the graph and the content of the dialog.
+    ///
+    /// Returns all the problems found, sorted by node.
+    ///
+    /// # Note
+    ///
+    /// An `exit_state` which is not a key is an end of the dialog,
+    /// so only the ones lower than the greatest key without label are reported as dangling:
+    /// they are most likely a typo.
+    ///
+    /// ```rust
+    /// use yml_dialog::*;
+    ///
+    /// let dialog: Dialog = serde_yaml::from_str(
+    ///     "1:
+    ///   source: Frog
+    ///   content:
+    ///     text:
+    ///     - KeroKero
+    ///     exit_state: 2
+    /// 3:
+    ///   source: Frog
+    ///   content: []\n",
+    /// )
+    /// .unwrap();
+    ///
+    /// let diagnostics: Vec<String> = dialog.validate().iter().map(ToString::to_string).collect();
+    ///
+    /// assert_eq!(
+    ///     diagnostics,
+    ///     vec![
+    ///         "error: node 1, content.exit_state: the exit_state `2` points to no node",
+    ///         "error: node 3, content: the node has no choice",
+    ///         "warning: node 3: unreachable from the entry node",
+    ///         "warning: node 3: no end of the dialog can be reached",
+    ///     ]
+    /// );
+    /// ```
+    pub fn validate(&self) -> Vec<Diagnostic> {
+        let mut diagnostics = Vec::new();
+        // The labeled nodes are written by label: a numeric typo can only concern the others
+        let last_key = self
+            .nodes()
+            .keys()
+            .rev()
+            .find(|key| self.label(key).is_none())
+            .copied();
+
+        if let Some(entry) = self.entry() {
+            if !self.contains(&entry) {
+                diagnostics.push(Diagnostic::new(
+                    None,
+                    "entry".to_string(),
+                    DiagnosticKind::MissingEntry { entry },
+                ));
+            }
+        }
+
+        let reachable = self.reachable_from_entry();
+        let reaching_end = self.reaching_end();
+
+        let mut string_ids = BTreeMap::new();
+        let mut duplicate_ids: BTreeMap<usize, Vec<(String, DiagnosticKind)>> = BTreeMap::new();
+        for string in self.translatable_strings() {
+            match string_ids.get(string.id()) {
+                None => {
+                    string_ids.insert(string.id().clone(), string.node());
+                }
+                Some(first) => {
+                    let path = match string.position() {
+                        TextPosition::Line(index) => format!("content.text[{index}]"),
+                        TextPosition::Choice(index) => format!("content[{index}].id"),
+                    };
+                    duplicate_ids.entry(string.node()).or_default().push((
+                        path,
+                        DiagnosticKind::DuplicateStringId {
+                            id: string.id().clone(),
+                            first: *first,
+                        },
+                    ));
+                }
+            }
+        }
+
+        for (key, node) in self.iter() {
+            let node_diagnostic =
+                |path: String, kind: DiagnosticKind| Diagnostic::new(Some(*key), path, kind);
+
+            match node.content() {
+                Content::Monolog { text, .. } => {
+                    if text.is_empty() {
+                        diagnostics.push(node_diagnostic(
+                            "content.text".to_string(),
+                            DiagnosticKind::EmptyText,
+                        ));
+                    }
+                }
+                Content::Choices(choices) => {
+                    if choices.is_empty() {
+                        diagnostics.push(node_diagnostic(
+                            "content".to_string(),
+                            DiagnosticKind::EmptyChoices,
+                        ));
+                    }
+                    for (index, choice) in choices.iter().enumerate() {
+                        if choice.text().is_empty() {
+                            diagnostics.push(node_diagnostic(
+                                format!("content[{index}].text"),
+                                DiagnosticKind::EmptyChoiceText,
+                            ));
+                        }
+                        if let Some(first) = choices[..index].iter().position(|previous| {
+                            previous.text() == choice.text()
+                                && previous.exit_state() == choice.exit_state()
+                        }) {
+                            diagnostics.push(node_diagnostic(
+                                format!("content[{index}]"),
+                                DiagnosticKind::DuplicateChoice { first },
+                            ));
+                        }
+                    }
+                }
+            }
+
+            for (path, kind) in duplicate_ids.remove(key).into_iter().flatten() {
+                diagnostics.push(node_diagnostic(path, kind));
+            }
+
+            for (path, exit_state) in exits(node) {
+                if !self.contains(&exit_state) && Some(exit_state) < last_key {
+                    diagnostics.push(node_diagnostic(
+                        path,
+                        DiagnosticKind::DanglingExit { exit_state },
+                    ));
+                }
+            }
+
+            if !reachable.contains(key) {
+                diagnostics.push(node_diagnostic(
+                    String::new(),
+                    DiagnosticKind::UnreachableNode,
+                ));
+            }
+            if !reaching_end.contains(key) {
+                diagnostics.push(node_diagnostic(
+                    String::new(),
+                    DiagnosticKind::NoReachableEnd,
+                ));
+            }
+        }
+
+        diagnostics
+    }
+
+    /// Returns the keys of the nodes reachable from the entry node, conditions ignored.
+    fn reachable_from_entry(&self) -> BTreeSet<usize> {
+        let mut reachable = BTreeSet::new();
+        let mut to_visit: Vec<usize> = self.entry().into_iter().collect();
+
+        while let Some(key) = to_visit.pop() {
+            if let Some(node) = self.get(&key) {
+                if reachable.insert(key) {
+                    to_visit.extend(exits(node).into_iter().map(|(_, exit_state)| exit_state));
+                }
+            }
+        }
+
+        reachable
+    }
+
+    /// Returns the keys of the nodes from which an end can be reached, conditions ignored.
+    fn reaching_end(&self) -> BTreeSet<usize> {
+        let mut predecessors: BTreeMap<usize, Vec<usize>> = BTreeMap::new();
+        let mut to_visit = Vec::new();
+
+        for (key, node) in self.iter() {
+            for (_, exit_state) in exits(node) {
+                if self.contains(&exit_state) {
+                    predecessors.entry(exit_state).or_default().push(*key);
+                } else {
+                    to_visit.push(*key);
+                }
+            }
+        }
+
+        let mut reaching_end = BTreeSet::new();
+        while let Some(key) = to_visit.pop() {
+            if reaching_end.insert(key) {
+                to_visit.extend(predecessors.get(&key).into_iter().flatten());
+            }
+        }
+
+        reaching_end
+    }
+}
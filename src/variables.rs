@@ -0,0 +1,357 @@
+//! Named and typed state of the game, read by conditions and written by effects.
+
+use serde::{
+    de::{self, Visitor},
+    Deserialize, Deserializer, Serialize,
+};
+use std::{
+    cmp::Ordering,
+    collections::{btree_map, BTreeMap},
+    fmt,
+    hash::{Hash, Hasher},
+};
+
+/// The value of a variable.
+///
+/// It is written as a bare YAML value: `3`, `0.5`, `true` or `"gold"`.
+///
+/// # Note
+///
+/// `Eq`, `Ord` and `Hash` rely on [`f64::total_cmp`] for floats,
+/// and order values by type first: see [`Value::compare`] to compare numbers together.
+#[derive(Serialize, Clone, Debug)]
+#[serde(untagged)]
+pub enum Value {
+    /// An integer, like a karma
+    Int(i32),
+    /// A float, like a fear ratio
+    Float(f64),
+    /// A flag
+    Bool(bool),
+    /// A string, like the name of the last visited place
+    String(String),
+}
+
+impl Value {
+    /// Compares two values of the same type, an integer and a float being compared as floats.
+    ///
+    /// Returns `None` for values of different types.
+    ///
+    /// ```rust
+    /// use std::cmp::Ordering;
+    /// use yml_dialog::Value;
+    ///
+    /// assert_eq!(Value::Int(2).compare(&Value::Float(2.5)), Some(Ordering::Less));
+    /// assert_eq!(Value::Int(2).compare(&Value::Bool(true)), None);
+    /// ```
+    pub fn compare(&self, other: &Value) -> Option<Ordering> {
+        match (self, other) {
+            (Value::Int(left), Value::Int(right)) => Some(left.cmp(right)),
+            (Value::Int(left), Value::Float(right)) => f64::from(*left).partial_cmp(right),
+            (Value::Float(left), Value::Int(right)) => left.partial_cmp(&f64::from(*right)),
+            (Value::Float(left), Value::Float(right)) => left.partial_cmp(right),
+            (Value::Bool(left), Value::Bool(right)) => Some(left.cmp(right)),
+            (Value::String(left), Value::String(right)) => Some(left.cmp(right)),
+            _ => None,
+        }
+    }
+
+    /// Returns the integer, if it is one.
+    pub fn as_int(&self) -> Option<i32> {
+        match self {
+            Value::Int(value) => Some(*value),
+            _ => None,
+        }
+    }
+
+    /// Returns the number as a float, if it is an integer or a float.
+    pub fn as_float(&self) -> Option<f64> {
+        match self {
+            Value::Int(value) => Some(f64::from(*value)),
+            Value::Float(value) => Some(*value),
+            _ => None,
+        }
+    }
+
+    /// Returns the flag, if it is one.
+    pub fn as_bool(&self) -> Option<bool> {
+        match self {
+            Value::Bool(value) => Some(*value),
+            _ => None,
+        }
+    }
+
+    /// Returns the string, if it is one.
+    pub fn as_str(&self) -> Option<&str> {
+        match self {
+            Value::String(value) => Some(value),
+            _ => None,
+        }
+    }
+
+    /// The rank of the type, to order values of different types.
+    fn rank(&self) -> u8 {
+        match self {
+            Value::Int(_) => 0,
+            Value::Float(_) => 1,
+            Value::Bool(_) => 2,
+            Value::String(_) => 3,
+        }
+    }
+}
+
+impl Default for Value {
+    fn default() -> Self {
+        Value::Int(0)
+    }
+}
+
+impl PartialEq for Value {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
+
+impl Eq for Value {}
+
+impl PartialOrd for Value {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for Value {
+    fn cmp(&self, other: &Self) -> Ordering {
+        match (self, other) {
+            (Value::Int(left), Value::Int(right)) => left.cmp(right),
+            (Value::Float(left), Value::Float(right)) => left.total_cmp(right),
+            (Value::Bool(left), Value::Bool(right)) => left.cmp(right),
+            (Value::String(left), Value::String(right)) => left.cmp(right),
+            _ => self.rank().cmp(&other.rank()),
+        }
+    }
+}
+
+impl Hash for Value {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.rank().hash(state);
+        match self {
+            Value::Int(value) => value.hash(state),
+            Value::Float(value) => value.to_bits().hash(state),
+            Value::Bool(value) => value.hash(state),
+            Value::String(value) => value.hash(state),
+        }
+    }
+}
+
+impl fmt::Display for Value {
+    /// Writes the value as a literal of the [`Expression`](crate::Expression) language.
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Value::Int(value) => write!(f, "{value}"),
+            // `{:?}` keeps the `.0` of round floats
+            Value::Float(value) => write!(f, "{value:?}"),
+            Value::Bool(value) => write!(f, "{value}"),
+            Value::String(value) => {
+                write!(f, "\"")?;
+                for c in value.chars() {
+                    if c == '"' || c == '\\' {
+                        write!(f, "\\")?;
+                    }
+                    write!(f, "{c}")?;
+                }
+                write!(f, "\"")
+            }
+        }
+    }
+}
+
+impl From<i32> for Value {
+    fn from(value: i32) -> Self {
+        Value::Int(value)
+    }
+}
+
+impl From<f64> for Value {
+    fn from(value: f64) -> Self {
+        Value::Float(value)
+    }
+}
+
+impl From<bool> for Value {
+    fn from(value: bool) -> Self {
+        Value::Bool(value)
+    }
+}
+
+impl From<String> for Value {
+    fn from(value: String) -> Self {
+        Value::String(value)
+    }
+}
+
+impl From<&str> for Value {
+    fn from(value: &str) -> Self {
+        Value::String(value.to_string())
+    }
+}
+
+impl<'de> Deserialize<'de> for Value {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        struct ValueVisitor;
+
+        impl<'de> Visitor<'de> for ValueVisitor {
+            type Value = Value;
+
+            fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+                formatter.write_str("an integer, a float, a boolean or a string")
+            }
+
+            fn visit_i64<E: de::Error>(self, value: i64) -> Result<Value, E> {
+                i32::try_from(value)
+                    .map(Value::Int)
+                    .map_err(|_| E::invalid_value(de::Unexpected::Signed(value), &"an i32"))
+            }
+
+            fn visit_u64<E: de::Error>(self, value: u64) -> Result<Value, E> {
+                i32::try_from(value)
+                    .map(Value::Int)
+                    .map_err(|_| E::invalid_value(de::Unexpected::Unsigned(value), &"an i32"))
+            }
+
+            fn visit_f64<E: de::Error>(self, value: f64) -> Result<Value, E> {
+                Ok(Value::Float(value))
+            }
+
+            fn visit_bool<E: de::Error>(self, value: bool) -> Result<Value, E> {
+                Ok(Value::Bool(value))
+            }
+
+            fn visit_str<E: de::Error>(self, value: &str) -> Result<Value, E> {
+                Ok(Value::String(value.to_string()))
+            }
+
+            fn visit_string<E: de::Error>(self, value: String) -> Result<Value, E> {
+                Ok(Value::String(value))
+            }
+        }
+
+        deserializer.deserialize_any(ValueVisitor)
+    }
+}
+
+/// The variables of the game, by name.
+///
+/// It (de)serializes as a map, to be stored in a save game.
+///
+/// ```rust
+/// use yml_dialog::*;
+///
+/// let mut variables = Variables::new();
+/// variables.set("trust", 3);
+/// variables.set("fear", 0.25);
+/// variables.set("met_the_frog", true);
+///
+/// assert_eq!(variables.get("trust"), Some(&Value::Int(3)));
+/// assert_eq!(variables.get("gold"), None);
+///
+/// let save = serde_yaml::to_string(&variables).unwrap();
+/// assert_eq!(save, "fear: 0.25\nmet_the_frog: true\ntrust: 3\n");
+/// assert_eq!(serde_yaml::from_str::<Variables>(&save).unwrap(), variables);
+/// ```
+#[derive(Serialize, Deserialize, Clone, Eq, PartialEq, Ord, PartialOrd, Hash, Debug, Default)]
+#[serde(transparent)]
+pub struct Variables(BTreeMap<String, Value>);
+
+impl Variables {
+    /// Constructs a new Variables store, without any variable.
+    pub fn new() -> Self {
+        Variables::default()
+    }
+
+    /// Returns the value of the variable `name`.
+    pub fn get(&self, name: &str) -> Option<&Value> {
+        self.0.get(name)
+    }
+
+    /// Returns the mutable value of the variable `name`.
+    pub fn get_mut(&mut self, name: &str) -> Option<&mut Value> {
+        self.0.get_mut(name)
+    }
+
+    /// Sets the variable `name`, returning its previous value if any.
+    pub fn set(&mut self, name: impl Into<String>, value: impl Into<Value>) -> Option<Value> {
+        self.0.insert(name.into(), value.into())
+    }
+
+    /// Removes the variable `name`, returning its value if any.
+    pub fn remove(&mut self, name: &str) -> Option<Value> {
+        self.0.remove(name)
+    }
+
+    /// Returns `true` if the variable `name` is set.
+    pub fn contains(&self, name: &str) -> bool {
+        self.0.contains_key(name)
+    }
+
+    /// Returns the number of variables.
+    pub fn len(&self) -> usize {
+        self.0.len()
+    }
+
+    /// Returns `true` if no variable is set.
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    /// Iterates over the variables, sorted by name.
+    pub fn iter(&self) -> btree_map::Iter<'_, String, Value> {
+        self.0.iter()
+    }
+}
+
+impl From<BTreeMap<String, Value>> for Variables {
+    fn from(variables: BTreeMap<String, Value>) -> Self {
+        Variables(variables)
+    }
+}
+
+impl<K: Into<String>, V: Into<Value>> FromIterator<(K, V)> for Variables {
+    fn from_iter<T: IntoIterator<Item = (K, V)>>(iter: T) -> Self {
+        Variables(
+            iter.into_iter()
+                .map(|(name, value)| (name.into(), value.into()))
+                .collect(),
+        )
+    }
+}
+
+impl<K: Into<String>, V: Into<Value>> Extend<(K, V)> for Variables {
+    fn extend<T: IntoIterator<Item = (K, V)>>(&mut self, iter: T) {
+        self.0.extend(
+            iter.into_iter()
+                .map(|(name, value)| (name.into(), value.into())),
+        )
+    }
+}
+
+impl IntoIterator for Variables {
+    type Item = (String, Value);
+    type IntoIter = btree_map::IntoIter<String, Value>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.0.into_iter()
+    }
+}
+
+impl<'a> IntoIterator for &'a Variables {
+    type Item = (&'a String, &'a Value);
+    type IntoIter = btree_map::Iter<'a, String, Value>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.0.iter()
+    }
+}
@@ -0,0 +1,308 @@
+//! Exports the strings of a dialog to XLIFF 1.2 and 2.0 files, and imports their translations,
+//! with the `xliff` feature.
+//!
+//! Each string is a unit identified by its string id, and named by its node, speaker and position,
+//! like `greet_player|Player|choice1`.
+//! The `note` of the node is written as a note of the unit.
+
+use std::fmt::{self, Write};
+
+use roxmltree::{Document, Node};
+
+use crate::{Dialog, StringTable, TranslationImport, TranslationIssueKind, TranslationReport};
+
+/// The version of an XLIFF file.
+#[derive(Copy, Clone, Eq, PartialEq, Ord, PartialOrd, Hash, Debug)]
+pub enum XliffVersion {
+    /// XLIFF 1.2, with `trans-unit`s
+    V1_2,
+    /// XLIFF 2.0, with `unit`s and `segment`s
+    V2_0,
+}
+
+impl fmt::Display for XliffVersion {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            XliffVersion::V1_2 => write!(f, "1.2"),
+            XliffVersion::V2_0 => write!(f, "2.0"),
+        }
+    }
+}
+
+/// Escapes a text or an attribute value.
+fn escape(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for character in text.chars() {
+        match character {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\'' => escaped.push_str("&apos;"),
+            _ => escaped.push(character),
+        }
+    }
+    escaped
+}
+
+/// Returns the text of an element, inline elements included.
+fn text_of(node: Node) -> String {
+    node.descendants()
+        .filter(Node::is_text)
+        .filter_map(|text| text.text())
+        .collect()
+}
+
+fn child<'a, 'input>(node: Node<'a, 'input>, name: &str) -> Option<Node<'a, 'input>> {
+    node.children()
+        .find(|child| child.tag_name().name() == name)
+}
+
+impl<C, E, F> Dialog<C, E, F> {
+    /// Writes every string of the dialog as an XLIFF file of the given `version`,
+    /// from the `source_locale` the dialog is written in
+    /// to the locale of the `translations`, translated in them if they have it.
+    ///
+    /// ```rust
+    /// use yml_dialog::*;
+    ///
+    /// let dialog: Dialog = serde_yaml::from_str(
+    ///     "1:
+    ///   source: Player
+    ///   content:
+    ///   - id: player.hi
+    ///     text: Hi
+    ///     exit_state: 2\n",
+    /// )
+    /// .unwrap();
+    ///
+    /// let mut french = StringTable::new("fr".to_string());
+    /// french.insert("player.hi".to_string(), "Salut".to_string());
+    ///
+    /// assert_eq!(
+    ///     dialog.to_xliff_string(XliffVersion::V2_0, "en", &french),
+    ///     r#"<?xml version="1.0" encoding="UTF-8"?>
+    /// <xliff xmlns="urn:oasis:names:tc:xliff:document:2.0" version="2.0" srcLang="en" trgLang="fr">
+    ///   <file id="dialog">
+    ///     <unit id="player.hi" name="1|Player|choice0">
+    ///       <segment state="translated">
+    ///         <source>Hi</source>
+    ///         <target>Salut</target>
+    ///       </segment>
+    ///     </unit>
+    ///   </file>
+    /// </xliff>
+    /// "#
+    /// );
+    /// ```
+    pub fn to_xliff_string(
+        &self,
+        version: XliffVersion,
+        source_locale: &str,
+        translations: &StringTable,
+    ) -> String {
+        let source_locale = escape(source_locale);
+        let target_locale = escape(translations.locale());
+        let name = escape(self.name().as_deref().unwrap_or("dialog"));
+        let mut xliff = String::from("<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n");
+        let _ = match version {
+            XliffVersion::V1_2 => writeln!(
+                xliff,
+                "<xliff xmlns=\"urn:oasis:names:tc:xliff:document:1.2\" version=\"1.2\">\n  \
+                 <file original=\"{name}\" datatype=\"plaintext\" source-language=\"{source_locale}\" \
+                 target-language=\"{target_locale}\">\n    <body>"
+            ),
+            XliffVersion::V2_0 => writeln!(
+                xliff,
+                "<xliff xmlns=\"urn:oasis:names:tc:xliff:document:2.0\" version=\"2.0\" \
+                 srcLang=\"{source_locale}\" trgLang=\"{target_locale}\">\n  <file id=\"{name}\">"
+            ),
+        };
+
+        for string in self.translatable_strings() {
+            let id = escape(string.id());
+            let context = escape(
+                &self
+                    .text_context(&string.node(), string.position())
+                    .unwrap_or_default(),
+            );
+            let source = escape(string.text());
+            let target = translations.get(string.id()).map(|target| escape(target));
+            let note = self
+                .get(&string.node())
+                .and_then(|node| node.note().as_ref())
+                .map(|note| escape(note));
+            match version {
+                XliffVersion::V1_2 => {
+                    let _ = writeln!(
+                        xliff,
+                        "      <trans-unit id=\"{id}\" resname=\"{context}\">\n        \
+                         <source>{source}</source>"
+                    );
+                    if let Some(target) = target {
+                        let _ = writeln!(
+                            xliff,
+                            "        <target state=\"translated\">{target}</target>"
+                        );
+                    }
+                    if let Some(note) = note {
+                        let _ = writeln!(xliff, "        <note>{note}</note>");
+                    }
+                    let _ = writeln!(xliff, "      </trans-unit>");
+                }
+                XliffVersion::V2_0 => {
+                    let _ = writeln!(xliff, "    <unit id=\"{id}\" name=\"{context}\">");
+                    if let Some(note) = note {
+                        let _ = writeln!(
+                            xliff,
+                            "      <notes>\n        <note>{note}</note>\n      </notes>"
+                        );
+                    }
+                    let state = if target.is_some() {
+                        "translated"
+                    } else {
+                        "initial"
+                    };
+                    let _ = writeln!(
+                        xliff,
+                        "      <segment state=\"{state}\">\n        <source>{source}</source>"
+                    );
+                    if let Some(target) = target {
+                        let _ = writeln!(xliff, "        <target>{target}</target>");
+                    }
+                    let _ = writeln!(xliff, "      </segment>\n    </unit>");
+                }
+            }
+        }
+
+        xliff.push_str(match version {
+            XliffVersion::V1_2 => "    </body>\n  </file>\n</xliff>\n",
+            XliffVersion::V2_0 => "  </file>\n</xliff>\n",
+        });
+        xliff
+    }
+
+    /// Reads the translations of an XLIFF 1.2 or 2.0 file into a table,
+    /// whose locale is the target language of the file.
+    ///
+    /// The units are matched with the strings of the dialog by their `resname`, or `name` in XLIFF 2.0.
+    /// The [`TranslationReport`] lists the units left out: the ones in a state needing a translation
+    /// or a review, the stale ones, whose source is not the text of the dialog anymore,
+    /// and the ones for strings the dialog does not have.
+    /// It lists the strings of the dialog without translation too.
+    ///
+    /// ```rust
+    /// use yml_dialog::*;
+    ///
+    /// let dialog: Dialog = serde_yaml::from_str(
+    ///     "1:
+    ///   source: Frog
+    ///   content:
+    ///     text: [KeroKero, Bye]
+    ///     exit_state: 2\n",
+    /// )
+    /// .unwrap();
+    ///
+    /// let (french, report) = dialog.import_xliff(
+    ///     r#"<xliff version="1.2" xmlns="urn:oasis:names:tc:xliff:document:1.2">
+    ///   <file original="dialog" source-language="en" target-language="fr" datatype="plaintext">
+    ///     <body>
+    ///       <trans-unit id="1.line0" resname="1|Frog|line0">
+    ///         <source>KeroKero</source>
+    ///         <target state="translated">Crôa</target>
+    ///       </trans-unit>
+    ///     </body>
+    ///   </file>
+    /// </xliff>"#,
+    /// );
+    ///
+    /// assert_eq!(french.locale(), "fr");
+    /// assert_eq!(french.get("1.line0"), Some(&"Crôa".to_string()));
+    /// assert_eq!(report.issues()[0].to_string(), "`1.line1` is not translated");
+    /// ```
+    pub fn import_xliff(&self, xliff: &str) -> (StringTable, TranslationReport) {
+        let mut import = TranslationImport::new(self);
+        let document = match Document::parse(xliff) {
+            Ok(document) => document,
+            Err(error) => {
+                import.report(
+                    error.pos().row as usize,
+                    TranslationIssueKind::Malformed {
+                        message: error.to_string(),
+                    },
+                );
+                return (import.table, import.report);
+            }
+        };
+        let line = |node: Node| document.text_pos_at(node.range().start).row as usize;
+        let root = document.root_element();
+        let version = match root.attribute("version") {
+            Some("1.2") => XliffVersion::V1_2,
+            Some(version) if version.starts_with("2.") => XliffVersion::V2_0,
+            version => {
+                import.report(
+                    line(root),
+                    TranslationIssueKind::Malformed {
+                        message: format!(
+                            "unsupported XLIFF version `{}`",
+                            version.unwrap_or_default()
+                        ),
+                    },
+                );
+                return (import.table, import.report);
+            }
+        };
+
+        let locale = match version {
+            XliffVersion::V1_2 => root
+                .descendants()
+                .find(|node| node.tag_name().name() == "file")
+                .and_then(|file| file.attribute("target-language")),
+            XliffVersion::V2_0 => root.attribute("trgLang"),
+        };
+        *import.table.locale_mut() = locale.unwrap_or_default().to_string();
+
+        for node in root.descendants() {
+            let unit = match (version, node.tag_name().name()) {
+                (XliffVersion::V1_2, "trans-unit") => {
+                    let target = child(node, "target");
+                    let state = target.and_then(|target| target.attribute("state"));
+                    Some((
+                        node.attribute("resname"),
+                        child(node, "source").map(text_of).unwrap_or_default(),
+                        target.map(text_of).unwrap_or_default(),
+                        state.is_some_and(|state| state == "new" || state.starts_with("needs-")),
+                    ))
+                }
+                (XliffVersion::V2_0, "unit") => {
+                    let segments: Vec<Node> = node
+                        .children()
+                        .filter(|child| child.tag_name().name() == "segment")
+                        .collect();
+                    let texts = |name: &str| -> String {
+                        segments
+                            .iter()
+                            .filter_map(|segment| child(*segment, name))
+                            .map(text_of)
+                            .collect()
+                    };
+                    Some((
+                        node.attribute("name"),
+                        texts("source"),
+                        texts("target"),
+                        segments
+                            .iter()
+                            .any(|segment| segment.attribute("state") == Some("initial")),
+                    ))
+                }
+                _ => None,
+            };
+            let Some((context, source, target, review)) = unit else {
+                continue;
+            };
+            let context = context.or(node.attribute("id")).unwrap_or_default();
+            import.entry(line(node), context, &source, target, review);
+        }
+        import.finish()
+    }
+}
@@ -0,0 +1,75 @@
+//! Reads and writes dialogs as YAML, behind the default `yaml` feature.
+
+use serde::{de::DeserializeOwned, Serialize};
+use std::io;
+
+use crate::{
+    track::{deserialize_tracked, read_checked, split_position},
+    Dialog, DialogError,
+};
+
+impl<C, E, F> Dialog<C, E, F> {
+    /// Reads a dialog from a YAML string.
+    ///
+    /// The error tells the node, the field and the position in the file of the faulty value,
+    /// see [`DialogError`].
+    ///
+    /// ```rust
+    /// use yml_dialog::*;
+    ///
+    /// let dialog: Dialog = Dialog::from_yaml_str(
+    ///     "name: Frog
+    /// nodes:
+    ///   1:
+    ///     source: Frog
+    ///     content:
+    ///       monolog:
+    ///         text:
+    ///         - KeroKero
+    ///         exit_state: 2\n",
+    /// )
+    /// .unwrap();
+    ///
+    /// assert_eq!(dialog.name(), &Some("Frog".to_string()));
+    /// assert_eq!(Dialog::from_yaml_str(&dialog.to_yaml_string().unwrap()), Ok(dialog));
+    /// ```
+    pub fn from_yaml_str(yaml: &str) -> Result<Self, DialogError>
+    where
+        Self: DeserializeOwned,
+    {
+        read_checked(
+            || {
+                serde_yaml::from_str(yaml).map_err(|error| {
+                    let (message, position) = split_position(&error.to_string());
+                    DialogError::new(message).at_position(position)
+                })
+            },
+            || {
+                deserialize_tracked(serde_yaml::Deserializer::from_str(yaml))
+                    .map_err(|(error, _)| error)
+            },
+        )
+    }
+
+    /// Reads a dialog from a YAML `reader`, like a file.
+    ///
+    /// See [`Dialog::from_yaml_str`].
+    pub fn from_yaml_reader<R: io::Read>(mut reader: R) -> Result<Self, DialogError>
+    where
+        Self: DeserializeOwned,
+    {
+        let mut yaml = String::new();
+        reader
+            .read_to_string(&mut yaml)
+            .map_err(|error| DialogError::new(error.to_string()))?;
+        Self::from_yaml_str(&yaml)
+    }
+
+    /// Writes the dialog as YAML.
+    pub fn to_yaml_string(&self) -> Result<String, DialogError>
+    where
+        Self: Serialize,
+    {
+        serde_yaml::to_string(self).map_err(|error| DialogError::new(error.to_string()))
+    }
+}
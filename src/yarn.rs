@@ -0,0 +1,1075 @@
+//! Imports and exports Yarn Spinner scripts, see [`import_yarn`] and [`Dialog::to_yarn_string`].
+//!
+//! A script is a list of nodes, each made of `key: value` headers up to `---` and of a body up to `===`.
+//! A body is parsed into statements, an option holding the statements indented under it,
+//! then compiled backwards like the Ink weaves, each statement knowing where the dialog goes next.
+
+use std::fmt;
+
+use crate::{
+    import::{
+        collapse, export_names, is_name, literal, strip_comments, strip_tags, Assignment, Imported,
+        ImportedChoice, ImportedContent, ImportedFlow, Target,
+    },
+    Comparison, Condition, Content, Dialog, Effect, Expression, ImportReport, Value,
+};
+
+/// Converts a [Yarn Spinner](https://yarnspinner.dev) script into a [`Dialog`].
+///
+/// The subset of Yarn understood is:
+///
+/// - the nodes `title: Node`, `---`, `===`, becoming labeled nodes, the entry being `Start`
+///   or else the first one;
+/// - the lines `Speaker: text`, grouped into [`Content::Monolog`]s by speaker,
+///   the speaker becoming the `source` of the node;
+/// - the options `-> text`, with the statements indented under them, becoming [`Content::Choices`],
+///   the dialog going on after the options once their statements are over;
+/// - the conditions `-> text <<if $flag>>`, and the `<<if>>`, `<<elseif>>`, `<<else>>`
+///   and `<<endif>>` around options, becoming a [`Condition`]: a `$flag` alone is an event,
+///   and the comparisons like `$karma >= 10` or `$fear lt 0.5` are read as [`Expression`]s;
+/// - the commands `<<jump Node>>` and `<<stop>>`;
+/// - the commands `<<set $flag to true>>`, adding `flag` to the `trigger_event` of the node,
+///   `<<set $flag to false>>`, `<<set $variable to 5>>` and `<<set $karma to $karma + 2>>`,
+///   becoming [`Effect`]s, on the choice when they start the statements of an option.
+///   Any other command, like `<<shake_camera 2>>`, becomes an [`Effect::Custom`].
+///
+/// Anything else, like the tags, the inline expressions or the `<<if>>` around lines,
+/// is reported in the [`ImportReport`] with its line, and left out or kept as written.
+///
+/// ```rust
+/// use yml_dialog::*;
+///
+/// let (dialog, report) = import_yarn(
+///     "title: Start
+/// ---
+/// Frog: KeroKero
+/// -> Hi
+///     <<jump Greet>>
+/// -> Bye <<if $FrogLove>>
+///     <<set $FrogHate to true>>
+/// Frog: Bye bye. #sad
+/// ===
+/// title: Greet
+/// ---
+/// Frog: Hello there!
+/// ===
+/// ",
+/// );
+///
+/// assert_eq!(report.issues().len(), 1);
+/// assert_eq!(report.issues()[0].to_string(), "line 8: tag `sad` left out");
+///
+/// let start = dialog.key_of("Start").unwrap();
+/// assert_eq!(dialog.entry(), Some(start));
+/// assert_eq!(dialog.get(&start).unwrap().source(), "Frog");
+/// let Content::Monolog { exit_state: choices, .. } = dialog.get(&start).unwrap().content() else {
+///     panic!("the node starts with a monolog");
+/// };
+/// let Content::Choices(choices) = dialog.get(choices).unwrap().content() else {
+///     panic!("the monolog leads to the options");
+/// };
+/// assert_eq!(choices[0].exit_state(), &dialog.key_of("Greet").unwrap());
+/// assert_eq!(
+///     choices[1].condition(),
+///     &Some(Condition::new(None, vec!["FrogLove".to_string()]))
+/// );
+/// assert_eq!(choices[1].effects(), &vec![Effect::SetEvent("FrogHate".to_string())]);
+///
+/// // And back, the inner nodes being labeled by their Yarn title
+/// let (imported, report) = import_yarn(&dialog.to_yarn_string());
+/// assert!(report.issues().is_empty());
+/// assert_eq!(imported.nodes(), dialog.nodes());
+/// ```
+pub fn import_yarn(yarn: &str) -> (Dialog, ImportReport) {
+    let mut report = ImportReport::default();
+    let nodes = parse(yarn, &mut report);
+    let dialog = Compiler {
+        nodes: &nodes,
+        report: &mut report,
+        imported: Imported::default(),
+    }
+    .compile();
+    (dialog, report.finish())
+}
+
+/// A node of the script.
+struct YarnNode {
+    title: String,
+    number: usize,
+    body: Vec<Statement>,
+}
+
+enum Statement {
+    Line {
+        number: usize,
+        speaker: String,
+        text: String,
+    },
+    /// A `<<set>>` or any other command.
+    Run {
+        number: usize,
+        assignment: Assignment,
+    },
+    /// `<<jump Node>>`, or `<<stop>>` without title.
+    Jump {
+        number: usize,
+        title: Option<String>,
+    },
+    Options(Vec<YarnOption>),
+}
+
+impl Statement {
+    fn number(&self) -> usize {
+        match self {
+            Statement::Line { number, .. }
+            | Statement::Run { number, .. }
+            | Statement::Jump { number, .. } => *number,
+            Statement::Options(options) => options[0].number,
+        }
+    }
+}
+
+struct YarnOption {
+    number: usize,
+    text: String,
+    condition: Option<Condition>,
+    body: Vec<Statement>,
+}
+
+/// Splits the script into its nodes, reporting what is left out.
+fn parse(yarn: &str, report: &mut ImportReport) -> Vec<YarnNode> {
+    let yarn = strip_comments(yarn);
+    let mut nodes: Vec<YarnNode> = Vec::new();
+    let mut title: Option<(String, usize)> = None;
+    // The start of the body and its lines, once in a body
+    let mut body: Option<(usize, Vec<(usize, &str)>)> = None;
+
+    let mut finish = |title: Option<(String, usize)>,
+                      (start, lines): (usize, Vec<(usize, &str)>),
+                      report: &mut ImportReport| {
+        let Some((title, number)) = title else {
+            report.report(start, "node without title left out");
+            return;
+        };
+        if nodes.iter().any(|node| node.title == title) {
+            report.report(number, format!("node `{title}` defined again, left out"));
+            return;
+        }
+        let body = BodyParser {
+            lines: &lines,
+            position: 0,
+            report,
+            blocks: Vec::new(),
+        }
+        .parse();
+        nodes.push(YarnNode {
+            title,
+            number,
+            body,
+        });
+    };
+
+    for (index, line) in yarn.lines().enumerate() {
+        let number = index + 1;
+        match &mut body {
+            Some(_) if line.trim() == "===" => {
+                let body = body.take().expect("within a body");
+                finish(title.take(), body, report);
+            }
+            Some((_, lines)) => {
+                if !line.trim().is_empty() {
+                    lines.push((number, line));
+                }
+            }
+            None => {
+                let header = line.trim();
+                if header == "---" {
+                    body = Some((number, Vec::new()));
+                } else if let Some((key, value)) = header.split_once(':') {
+                    let value = value.trim();
+                    match key.trim() {
+                        "title" => title = Some((value.to_string(), number)),
+                        "tags" if !value.is_empty() => {
+                            report.report(number, format!("tags `{value}` left out"))
+                        }
+                        _ => {}
+                    }
+                } else if !header.is_empty() {
+                    report.report(number, format!("`{header}` out of a node, left out"));
+                }
+            }
+        }
+    }
+    // The last `===` can be missing
+    if let Some(body) = body {
+        finish(title, body, report);
+    }
+    nodes
+}
+
+/// Reads the statements of a body, the options holding the lines indented under them.
+struct BodyParser<'a> {
+    lines: &'a [(usize, &'a str)],
+    position: usize,
+    report: &'a mut ImportReport,
+    /// The `<<if>>` blocks being read.
+    blocks: Vec<IfBlock>,
+}
+
+struct IfBlock {
+    number: usize,
+    /// The conditions of the previous branches, false within this one.
+    previous: Vec<Expression>,
+    /// The condition of this branch, none for `<<else>>` or if left out.
+    condition: Option<Expression>,
+    /// The `<<if>>` is left out around anything but options, and reported once.
+    reported: bool,
+}
+
+impl BodyParser<'_> {
+    fn parse(mut self) -> Vec<Statement> {
+        let statements = self.block(None);
+        self.unclosed();
+        statements
+    }
+
+    /// Reports the `<<if>>` blocks still open.
+    fn unclosed(&mut self) {
+        for block in self.blocks.drain(..) {
+            self.report
+                .report(block.number, "`<<if>>` without `<<endif>>`");
+        }
+    }
+
+    /// Reads the statements indented deeper than the `parent` option, if any.
+    fn block(&mut self, parent: Option<usize>) -> Vec<Statement> {
+        let mut statements = Vec::new();
+        while let Some(&(number, line)) = self.lines.get(self.position) {
+            let indentation = indentation(line);
+            if parent.is_some_and(|parent| indentation <= parent) {
+                break;
+            }
+            let line = line.trim();
+            if line.starts_with("->") {
+                statements.push(Statement::Options(self.options(indentation)));
+                continue;
+            }
+            self.position += 1;
+            let statement = match command(line) {
+                Some(command) if self.branch(command, number) => continue,
+                Some(command) => match self.command(command, number) {
+                    Some(statement) => statement,
+                    None => continue,
+                },
+                None => self.line(line, number),
+            };
+            self.unguarded();
+            statements.push(statement);
+        }
+        statements
+    }
+
+    /// Reads a group of options of the same `indentation`, maybe within `<<if>>` blocks.
+    fn options(&mut self, indentation: usize) -> Vec<YarnOption> {
+        let mut options = Vec::new();
+        while let Some(&(number, line)) = self.lines.get(self.position) {
+            if self::indentation(line) != indentation {
+                break;
+            }
+            let line = line.trim();
+            if let Some(command) = command(line) {
+                if self.branch(command, number) {
+                    self.position += 1;
+                    continue;
+                }
+                break;
+            }
+            let Some(option) = line.strip_prefix("->") else {
+                break;
+            };
+            self.position += 1;
+
+            let option = strip_tags(option, number, self.report);
+            let (text, condition) = match option.split_once("<<") {
+                Some((text, command)) => {
+                    let command = command.trim();
+                    let command = command.strip_suffix(">>").unwrap_or(command).trim();
+                    match command.strip_prefix("if ") {
+                        Some(condition) => (text, self.expression(condition, number)),
+                        None => {
+                            self.report
+                                .report(number, format!("`<<{command}>>` of an option left out"));
+                            (text, None)
+                        }
+                    }
+                }
+                None => (option, None),
+            };
+            let text = text_line(&collapse(text), number, self.report);
+            let condition = self
+                .guard()
+                .into_iter()
+                .chain(condition)
+                .reduce(|left, right| Expression::And(Box::new(left), Box::new(right)))
+                .map(condition_of);
+            // The statements of the option are guarded by its condition
+            let blocks = std::mem::take(&mut self.blocks);
+            let body = self.block(Some(indentation));
+            self.unclosed();
+            self.blocks = blocks;
+            options.push(YarnOption {
+                number,
+                text,
+                condition,
+                body,
+            });
+        }
+        options
+    }
+
+    /// Reads `<<if>>`, `<<elseif>>`, `<<else>>` and `<<endif>>`, returning `false` for other commands.
+    fn branch(&mut self, command: &str, number: usize) -> bool {
+        let (keyword, code) = split_keyword(command);
+        match keyword {
+            "if" => {
+                let condition = self.expression(code, number);
+                self.blocks.push(IfBlock {
+                    number,
+                    previous: Vec::new(),
+                    condition,
+                    reported: false,
+                });
+            }
+            "elseif" | "else" => {
+                let condition = match keyword {
+                    "elseif" => self.expression(code, number),
+                    _ => None,
+                };
+                match self.blocks.last_mut() {
+                    Some(block) => {
+                        block.previous.extend(block.condition.take());
+                        block.condition = condition;
+                    }
+                    None => self.report.report(
+                        number,
+                        format!("`<<{keyword}>>` without `<<if>>`, left out"),
+                    ),
+                }
+            }
+            "endif" => {
+                if self.blocks.pop().is_none() {
+                    self.report
+                        .report(number, "`<<endif>>` without `<<if>>`, left out");
+                }
+            }
+            _ => return false,
+        }
+        true
+    }
+
+    /// The condition of the `<<if>>` blocks being read, if any.
+    fn guard(&self) -> Option<Expression> {
+        self.blocks
+            .iter()
+            .flat_map(|block| {
+                block
+                    .previous
+                    .iter()
+                    .map(|previous| match previous {
+                        Expression::Not(previous) => (**previous).clone(),
+                        previous => Expression::Not(Box::new(previous.clone())),
+                    })
+                    .chain(block.condition.clone())
+            })
+            .reduce(|left, right| Expression::And(Box::new(left), Box::new(right)))
+    }
+
+    /// Reports the `<<if>>` blocks around something else than options.
+    fn unguarded(&mut self) {
+        for block in &mut self.blocks {
+            if !block.reported {
+                block.reported = true;
+                self.report.report(
+                    block.number,
+                    "`<<if>>` around lines left out, they always run",
+                );
+            }
+        }
+    }
+
+    fn command(&mut self, command: &str, number: usize) -> Option<Statement> {
+        let (keyword, code) = split_keyword(command);
+        match keyword {
+            "jump" if is_name(code) => Some(Statement::Jump {
+                number,
+                title: Some(code.to_string()),
+            }),
+            "jump" => {
+                self.report.report(
+                    number,
+                    format!("`<<{command}>>` left out, the dialog ends there"),
+                );
+                Some(Statement::Jump {
+                    number,
+                    title: None,
+                })
+            }
+            "stop" => Some(Statement::Jump {
+                number,
+                title: None,
+            }),
+            "set" => self
+                .set(code, number)
+                .map(|assignment| Statement::Run { number, assignment }),
+            "declare" => {
+                self.report
+                    .report(number, format!("`<<{command}>>` left out"));
+                None
+            }
+            _ => Some(Statement::Run {
+                number,
+                assignment: Assignment::Effect(Effect::Custom(command.to_string())),
+            }),
+        }
+    }
+
+    /// Reads `Speaker: text`, reporting what is left out.
+    fn line(&mut self, line: &str, number: usize) -> Statement {
+        let line = strip_tags(line, number, self.report);
+        let line = match line.split_once("<<") {
+            Some((line, command)) => {
+                self.report
+                    .report(number, format!("`<<{}` of a line left out", command.trim()));
+                line
+            }
+            None => line,
+        };
+        let (speaker, text) = match line.split_once(':') {
+            Some((speaker, text))
+                if !speaker.trim().is_empty()
+                    && !speaker.contains(['{', '[', '"'])
+                    && !text.trim().is_empty() =>
+            {
+                (collapse(speaker), text)
+            }
+            _ => (String::new(), line),
+        };
+        Statement::Line {
+            number,
+            speaker,
+            text: text_line(&collapse(text), number, self.report),
+        }
+    }
+
+    /// Reads the `$name to value` of a `<<set>>`, reporting the unsupported ones.
+    fn set(&mut self, code: &str, number: usize) -> Option<Assignment> {
+        let assignment = code
+            .strip_prefix('$')
+            .and_then(|code| code.split_once(" to ").or_else(|| code.split_once('=')))
+            .map(|(name, value)| (name.trim(), value.trim()))
+            .filter(|(name, _)| is_name(name))
+            .and_then(|(name, value)| match value {
+                "true" => Some(Assignment::Trigger(name.to_string())),
+                "false" => Some(Assignment::Effect(Effect::ClearEvent(name.to_string()))),
+                value => match value.strip_prefix("$karma").filter(|_| name == "karma") {
+                    Some(amount) => {
+                        let amount = amount.trim();
+                        let (sign, amount) = match amount.strip_prefix('+') {
+                            Some(amount) => (1, amount),
+                            None => (-1, amount.strip_prefix('-')?),
+                        };
+                        let amount: i32 = amount.trim().parse().ok()?;
+                        amount
+                            .checked_mul(sign)
+                            .map(|amount| Assignment::Effect(Effect::AddKarma(amount)))
+                    }
+                    None => literal(value).map(|value| {
+                        Assignment::Effect(Effect::SetVar {
+                            name: name.to_string(),
+                            value,
+                        })
+                    }),
+                },
+            });
+        if assignment.is_none() {
+            self.report
+                .report(number, format!("`<<set {code}>>` left out"));
+        }
+        assignment
+    }
+
+    /// Reads a condition, reporting the unsupported ones.
+    fn expression(&mut self, code: &str, number: usize) -> Option<Expression> {
+        match translate(code).parse() {
+            Ok(expression) => Some(events_as_booleans(expression)),
+            Err(_) => {
+                self.report
+                    .report(number, format!("condition `{code}` left out"));
+                None
+            }
+        }
+    }
+}
+
+/// Counts the leading whitespace of a line.
+fn indentation(line: &str) -> usize {
+    line.len() - line.trim_start().len()
+}
+
+/// Returns what is within `<<` and `>>`, if the line is a command.
+fn command(line: &str) -> Option<&str> {
+    line.strip_prefix("<<")
+        .and_then(|line| line.strip_suffix(">>"))
+        .map(str::trim)
+}
+
+/// Splits the first word off a command.
+fn split_keyword(command: &str) -> (&str, &str) {
+    match command.split_once(char::is_whitespace) {
+        Some((keyword, rest)) => (keyword, rest.trim()),
+        None => (command, ""),
+    }
+}
+
+/// Reports the inline expressions of a line of `text`, kept as written.
+fn text_line(text: &str, number: usize, report: &mut ImportReport) -> String {
+    if text.contains('{') {
+        report.report(
+            number,
+            format!("inline expression kept as written: `{text}`"),
+        );
+    }
+    text.to_string()
+}
+
+/// Rewrites a Yarn condition in the [`Expression`] language:
+/// a `$flag` alone becomes `has(flag)`, a compared `$variable` becomes `variable`,
+/// and the word operators like `and`, `not` or `gte` their symbols.
+fn translate(yarn: &str) -> String {
+    let chars: Vec<char> = yarn.chars().collect();
+    let mut tokens: Vec<String> = Vec::new();
+    let mut index = 0;
+    let is_word = |c: char| c.is_alphanumeric() || c == '_' || c == '.' || c == '$';
+    let is_operator = |c: char| "=!<>&|".contains(c);
+    while index < chars.len() {
+        let start = index;
+        let next = chars.get(index + 1).copied();
+        index += 1;
+        match chars[start] {
+            c if c.is_whitespace() => continue,
+            '"' => {
+                while index < chars.len() && chars[index] != '"' {
+                    index += if chars[index] == '\\' { 2 } else { 1 };
+                }
+                index = (index + 1).min(chars.len());
+            }
+            c if is_word(c) || (c == '-' && next.is_some_and(|c| c.is_ascii_digit())) => {
+                while chars.get(index).is_some_and(|c| is_word(*c)) {
+                    index += 1;
+                }
+            }
+            c if is_operator(c) => {
+                while chars.get(index).is_some_and(|c| is_operator(*c)) {
+                    index += 1;
+                }
+            }
+            _ => {}
+        }
+        tokens.push(chars[start..index].iter().collect());
+    }
+
+    let is_comparison = |token: Option<&String>| {
+        token.is_some_and(|token| {
+            [
+                "==", "!=", "<", "<=", ">", ">=", "is", "eq", "neq", "gt", "lt", "gte", "lte",
+            ]
+            .contains(&token.as_str())
+        })
+    };
+    tokens
+        .iter()
+        .enumerate()
+        .map(|(index, token)| match token.as_str() {
+            "and" => "&&".to_string(),
+            "or" => "||".to_string(),
+            "not" => "!".to_string(),
+            "is" | "eq" => "==".to_string(),
+            "neq" => "!=".to_string(),
+            "gt" => ">".to_string(),
+            "lt" => "<".to_string(),
+            "gte" => ">=".to_string(),
+            "lte" => "<=".to_string(),
+            token => match token.strip_prefix('$') {
+                Some(name)
+                    if is_comparison(index.checked_sub(1).and_then(|index| tokens.get(index)))
+                        || is_comparison(tokens.get(index + 1)) =>
+                {
+                    name.to_string()
+                }
+                Some(name) => format!("has({name})"),
+                None => token.to_string(),
+            },
+        })
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+/// Reads the comparisons of a variable to a boolean as events, like `$flag == true`.
+fn events_as_booleans(expression: Expression) -> Expression {
+    match expression {
+        Expression::Compare {
+            variable,
+            comparison: comparison @ (Comparison::Equal | Comparison::NotEqual),
+            value: Value::Bool(value),
+        } => {
+            let has = Expression::Has(variable);
+            if value == (comparison == Comparison::Equal) {
+                has
+            } else {
+                Expression::Not(Box::new(has))
+            }
+        }
+        Expression::Not(expression) => Expression::Not(Box::new(events_as_booleans(*expression))),
+        Expression::And(left, right) => Expression::And(
+            Box::new(events_as_booleans(*left)),
+            Box::new(events_as_booleans(*right)),
+        ),
+        Expression::Or(left, right) => Expression::Or(
+            Box::new(events_as_booleans(*left)),
+            Box::new(events_as_booleans(*right)),
+        ),
+        expression => expression,
+    }
+}
+
+/// Builds a [`Condition`], its events being the `has(..)` joined by `&&` to the rest.
+fn condition_of(expression: Expression) -> Condition {
+    fn conjuncts(expression: Expression, all: &mut Vec<Expression>) {
+        match expression {
+            Expression::And(left, right) => {
+                conjuncts(*left, all);
+                conjuncts(*right, all);
+            }
+            expression => all.push(expression),
+        }
+    }
+    let mut all = Vec::new();
+    conjuncts(expression, &mut all);
+
+    let mut events = Vec::new();
+    let mut others = Vec::new();
+    for expression in all {
+        match expression {
+            Expression::Has(event) => events.push(event),
+            expression => others.push(expression),
+        }
+    }
+    let condition = Condition::new(None, events);
+    match others
+        .into_iter()
+        .reduce(|left, right| Expression::And(Box::new(left), Box::new(right)))
+    {
+        Some(expression) => condition.with_expression(expression),
+        None => condition,
+    }
+}
+
+/// A part of a body between options.
+enum Item<'a> {
+    Chunk(Chunk),
+    /// The options, and the effects happening after their statements.
+    Options(&'a [YarnOption], Vec<Effect>),
+}
+
+/// The lines of a speaker, after the assignments happening when its node is entered.
+struct Chunk {
+    number: usize,
+    speaker: String,
+    lines: Vec<String>,
+    assignments: Vec<Assignment>,
+}
+
+struct Compiler<'a> {
+    nodes: &'a [YarnNode],
+    report: &'a mut ImportReport,
+    imported: Imported,
+}
+
+impl Compiler<'_> {
+    fn compile(mut self) -> Dialog {
+        if self.nodes.is_empty() {
+            return Dialog::default();
+        }
+        for node in self.nodes {
+            if node.body.is_empty() {
+                self.report.report(
+                    node.number,
+                    format!("`{}` is empty, the dialog ends there", node.title),
+                );
+            }
+            let entry = self.block(&node.body, Target::End);
+            self.imported.flows.push(ImportedFlow {
+                label: node.title.clone(),
+                number: node.number,
+                entry,
+            });
+        }
+        let entries = self.imported.flow_entries("jumps", self.report);
+        let entry = self
+            .nodes
+            .iter()
+            .position(|node| node.title == "Start")
+            .unwrap_or(0);
+        self.imported.into_dialog(&entries, entry)
+    }
+
+    /// Compiles statements going to `next` once over, returning their entry.
+    fn block(&mut self, statements: &[Statement], next: Target) -> Target {
+        // The statements up to the first jump: the rest cannot be reached
+        let mut tail = next;
+        let mut reachable = statements.len();
+        for (index, statement) in statements.iter().enumerate() {
+            if let Statement::Jump { number, title } = statement {
+                tail = match title {
+                    Some(title) => self.jump(title, *number),
+                    None => Target::End,
+                };
+                reachable = index;
+                for statement in &statements[index + 1..] {
+                    self.report
+                        .report(statement.number(), "unreachable after a jump, left out");
+                }
+                break;
+            }
+        }
+
+        // A new node for each speaker and for the lines following an assignment,
+        // which happens when it is entered.
+        let mut items: Vec<Item> = Vec::new();
+        for statement in &statements[..reachable] {
+            match statement {
+                Statement::Line {
+                    number,
+                    speaker,
+                    text,
+                } => match items.last_mut() {
+                    Some(Item::Chunk(chunk))
+                        if chunk.lines.is_empty() || chunk.speaker == *speaker =>
+                    {
+                        chunk.speaker.clone_from(speaker);
+                        chunk.lines.push(text.clone());
+                    }
+                    _ => items.push(Item::Chunk(Chunk {
+                        number: *number,
+                        speaker: speaker.clone(),
+                        lines: vec![text.clone()],
+                        assignments: Vec::new(),
+                    })),
+                },
+                Statement::Run { number, assignment } => {
+                    let assignment = match assignment {
+                        Assignment::Trigger(event) => Assignment::Trigger(event.clone()),
+                        Assignment::Effect(effect) => Assignment::Effect(effect.clone()),
+                    };
+                    match items.last_mut() {
+                        Some(Item::Chunk(chunk)) if chunk.lines.is_empty() => {
+                            chunk.assignments.push(assignment)
+                        }
+                        _ => items.push(Item::Chunk(Chunk {
+                            number: *number,
+                            speaker: String::new(),
+                            lines: Vec::new(),
+                            assignments: vec![assignment],
+                        })),
+                    }
+                }
+                Statement::Options(options) => items.push(Item::Options(options, Vec::new())),
+                Statement::Jump { .. } => unreachable!("the statements stop before the jump"),
+            }
+        }
+        // The assignments after the last line happen on the previous node rather than on an empty one,
+        // or on the choices of the previous options.
+        if items.len() > 1
+            && matches!(items.last(), Some(Item::Chunk(chunk)) if chunk.lines.is_empty())
+        {
+            let Some(Item::Chunk(last)) = items.pop() else {
+                unreachable!("the last item is a chunk");
+            };
+            match items.last_mut().expect("several items") {
+                Item::Chunk(previous) => previous.assignments.extend(last.assignments),
+                Item::Options(_, effects) => {
+                    effects.extend(last.assignments.into_iter().map(effect_of))
+                }
+            }
+        }
+
+        let mut target = tail;
+        let mut choices_node = None;
+        for item in items.into_iter().rev() {
+            let index = match item {
+                Item::Options(options, effects) => {
+                    let index = self.options(options, effects, target);
+                    choices_node = Some(index);
+                    index
+                }
+                Item::Chunk(chunk) => {
+                    let index = match choices_node {
+                        Some(index) if chunk.lines.is_empty() && target == Target::Node(index) => {
+                            index
+                        }
+                        _ => {
+                            if chunk.lines.is_empty() {
+                                self.report.report(
+                                    chunk.number,
+                                    "`<<set>>` without a line to go with, kept on an empty node",
+                                );
+                            }
+                            let index = self
+                                .imported
+                                .push(chunk.number, ImportedContent::Monolog(chunk.lines, target));
+                            self.imported.nodes[index].source = chunk.speaker;
+                            index
+                        }
+                    };
+                    let node = &mut self.imported.nodes[index];
+                    node.number = node.number.min(chunk.number);
+                    for assignment in chunk.assignments.into_iter().rev() {
+                        match assignment {
+                            Assignment::Trigger(event) => node.trigger_event.insert(0, event),
+                            Assignment::Effect(effect) => node.effects.insert(0, effect),
+                        }
+                    }
+                    index
+                }
+            };
+            target = Target::Node(index);
+        }
+        target
+    }
+
+    /// Compiles the options whose statements go to `next`, adding `effects` to each choice.
+    fn options(&mut self, options: &[YarnOption], effects: Vec<Effect>, next: Target) -> usize {
+        let mut choices = Vec::new();
+        for option in options {
+            // The assignments starting the statements happen on the choice
+            let leading = option
+                .body
+                .iter()
+                .take_while(|statement| matches!(statement, Statement::Run { .. }))
+                .count();
+            let mut choice_effects: Vec<Effect> = option.body[..leading]
+                .iter()
+                .map(|statement| match statement {
+                    Statement::Run {
+                        assignment: Assignment::Trigger(event),
+                        ..
+                    } => Effect::SetEvent(event.clone()),
+                    Statement::Run {
+                        assignment: Assignment::Effect(effect),
+                        ..
+                    } => effect.clone(),
+                    _ => unreachable!("the leading statements are commands"),
+                })
+                .collect();
+            choice_effects.extend(effects.iter().cloned());
+            let exit_state = self.block(&option.body[leading..], next);
+            choices.push(ImportedChoice {
+                text: option.text.clone(),
+                condition: option.condition.clone(),
+                effects: choice_effects,
+                exit_state,
+            });
+        }
+        self.imported
+            .push(options[0].number, ImportedContent::Choices(choices))
+    }
+
+    fn jump(&mut self, title: &str, number: usize) -> Target {
+        match self.nodes.iter().position(|node| node.title == title) {
+            Some(flow) => Target::Flow(flow),
+            None => {
+                self.report.report(
+                    number,
+                    format!("unknown jump target `{title}`, the dialog ends there"),
+                );
+                Target::End
+            }
+        }
+    }
+}
+
+/// The effect of an assignment, happening on a choice.
+fn effect_of(assignment: Assignment) -> Effect {
+    match assignment {
+        Assignment::Trigger(event) => Effect::SetEvent(event),
+        Assignment::Effect(effect) => effect,
+    }
+}
+
+impl<E, F: fmt::Display> Dialog<Condition, E, F> {
+    /// Writes the dialog as a [Yarn Spinner](https://yarnspinner.dev) script, see [`import_yarn`].
+    ///
+    /// Each node is written as a Yarn node, titled by its label, `Start` for an unlabeled entry,
+    /// or `node_` and its key, the entry node coming first.
+    /// The lines are prefixed by their speaker, the choices written as options,
+    /// their condition as `<<if>>`, the events and effects as `<<set>>` or custom commands,
+    /// and the `exit_state`s as `<<jump>>`s.
+    /// The name of the dialog and the `extra` data of the nodes are left out.
+    ///
+    /// ```rust
+    /// use yml_dialog::*;
+    ///
+    /// let dialog: Dialog = serde_yaml::from_str(
+    ///     "1:
+    ///   source: Frog
+    ///   content:
+    ///     choices:
+    ///     - text: Hi
+    ///       condition:
+    ///         karma_threshold: [0, 10]
+    ///       exit_state: 2
+    /// 2:
+    ///   source: Frog
+    ///   content:
+    ///     monolog:
+    ///       text: [KeroKero]
+    ///       exit_state: 3
+    ///   trigger_event: [FrogLove]\n",
+    /// )
+    /// .unwrap();
+    ///
+    /// assert_eq!(
+    ///     dialog.to_yarn_string(),
+    ///     "title: Start
+    /// ---
+    /// -> Hi <<if $karma >= 0 and $karma <= 10>>
+    ///     <<jump node_2>>
+    /// ===
+    ///
+    /// title: node_2
+    /// ---
+    /// <<set $FrogLove to true>>
+    /// Frog: KeroKero
+    /// ===
+    /// "
+    /// );
+    /// ```
+    pub fn to_yarn_string(&self) -> String {
+        let (titles, order) = export_names(self);
+        let mut yarn = Vec::new();
+        for key in &order {
+            let node = &self.nodes()[key];
+            let mut lines = vec![format!("title: {}", titles[key]), "---".to_string()];
+            lines.extend(
+                node.trigger_event()
+                    .iter()
+                    .map(|event| format!("<<set ${event} to true>>")),
+            );
+            lines.extend(node.effects().iter().map(yarn_effect));
+            let jump = |exit_state: &usize| {
+                titles
+                    .get(exit_state)
+                    .map(|title| format!("<<jump {title}>>"))
+            };
+            match node.content() {
+                Content::Monolog { text, exit_state } => {
+                    let speaker = self.speaker(node);
+                    lines.extend(text.iter().map(|line| match speaker {
+                        "" => line.clone(),
+                        speaker => format!("{speaker}: {line}"),
+                    }));
+                    lines.extend(jump(exit_state));
+                }
+                Content::Choices(choices) => {
+                    for choice in choices {
+                        lines.push(match choice.condition().as_ref().and_then(yarn_condition) {
+                            Some(condition) => format!("-> {} <<if {condition}>>", choice.text()),
+                            None => format!("-> {}", choice.text()),
+                        });
+                        lines.extend(
+                            choice
+                                .effects()
+                                .iter()
+                                .map(yarn_effect)
+                                .chain(jump(choice.exit_state()))
+                                .map(|line| format!("    {line}")),
+                        );
+                    }
+                }
+            }
+            lines.push("===\n".to_string());
+            yarn.push(lines.join("\n"));
+        }
+        yarn.join("\n")
+    }
+}
+
+/// Writes a condition in Yarn, none if it always holds.
+fn yarn_condition(condition: &Condition) -> Option<String> {
+    let mut parts: Vec<String> = condition
+        .events()
+        .iter()
+        .map(|event| format!("${event}"))
+        .collect();
+    let thresholds = condition
+        .karma_threshold()
+        .map(|(min, max)| ("karma".to_string(), (Value::Int(min), Value::Int(max))))
+        .into_iter()
+        .chain(condition.thresholds().clone());
+    for (name, (min, max)) in thresholds {
+        parts.push(format!("${name} >= {min} and ${name} <= {max}"));
+    }
+    if let Some(expression) = condition.expression() {
+        parts.push(yarn_expression(expression, 1));
+    }
+    (!parts.is_empty()).then(|| parts.join(" and "))
+}
+
+/// Writes an expression in Yarn, between parentheses if it binds looser than `precedence`.
+fn yarn_expression(expression: &Expression, precedence: u8) -> String {
+    let (own_precedence, written) = match expression {
+        Expression::Bool(value) => (3, value.to_string()),
+        Expression::Has(event) => (3, format!("${event}")),
+        Expression::Compare {
+            variable,
+            comparison,
+            value,
+        } => (2, format!("${variable} {comparison} {value}")),
+        Expression::Not(expression) => (3, format!("not {}", yarn_expression(expression, 3))),
+        Expression::And(left, right) => (
+            1,
+            format!(
+                "{} and {}",
+                yarn_expression(left, 1),
+                yarn_expression(right, 2)
+            ),
+        ),
+        Expression::Or(left, right) => (
+            0,
+            format!(
+                "{} or {}",
+                yarn_expression(left, 0),
+                yarn_expression(right, 1)
+            ),
+        ),
+    };
+    if own_precedence < precedence {
+        format!("({written})")
+    } else {
+        written
+    }
+}
+
+/// Writes an effect as a `<<set>>` or a custom command.
+fn yarn_effect<F: fmt::Display>(effect: &Effect<F>) -> String {
+    match effect {
+        Effect::SetEvent(event) => format!("<<set ${event} to true>>"),
+        Effect::ClearEvent(event) => format!("<<set ${event} to false>>"),
+        Effect::AddKarma(amount) if *amount < 0 => {
+            format!("<<set $karma to $karma - {}>>", amount.unsigned_abs())
+        }
+        Effect::AddKarma(amount) => format!("<<set $karma to $karma + {amount}>>"),
+        Effect::SetVar { name, value } => format!("<<set ${name} to {value}>>"),
+        Effect::Custom(effect) => format!("<<{effect}>>"),
+    }
+}
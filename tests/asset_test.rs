@@ -0,0 +1,106 @@
+#![cfg(feature = "bevy_asset")]
+
+use bevy::{asset::LoadState, prelude::*};
+use std::{thread, time::Duration};
+use yml_dialog::*;
+
+fn app() -> App {
+    let mut app = App::new();
+    app.add_plugins(MinimalPlugins)
+        .add_plugin(AssetPlugin {
+            asset_folder: "tests/assets".to_string(),
+            ..default()
+        })
+        .add_plugin(YmlDialogPlugin)
+        .add_plugin(DialogAssetPlugin);
+    app
+}
+
+/// Updates the app until the asset is loaded or failed.
+fn wait_for(app: &mut App, handle: &Handle<Dialog>) -> LoadState {
+    for _ in 0..200 {
+        app.update();
+        let state = app.world.resource::<AssetServer>().get_load_state(handle);
+        if matches!(state, LoadState::Loaded | LoadState::Failed) {
+            return state;
+        }
+        thread::sleep(Duration::from_millis(10));
+    }
+    panic!("the dialog is still loading");
+}
+
+/// Edits the first node like a writer would, and updates the app until the dialog is reloaded.
+fn edit(app: &mut App, handle: &Handle<Dialog>, text: Vec<String>) {
+    let mut dialogs = app.world.resource_mut::<Assets<Dialog>>();
+    let dialog = dialogs.get_mut(handle).unwrap();
+    *dialog.get_mut(&1).unwrap().content_mut() = Content::Monolog {
+        text,
+        exit_state: 2,
+    };
+    // The `AssetEvent`s are sent at the end of the update
+    app.update();
+    app.update();
+}
+
+#[test]
+fn test_asset_loaded_and_reloaded() {
+    let mut app = app();
+    let handle: Handle<Dialog> = app.world.resource::<AssetServer>().load("frog.dialog.yml");
+    let frog = app.world.spawn(handle.clone()).id();
+
+    assert_eq!(wait_for(&mut app, &handle), LoadState::Loaded);
+    // The state is inserted, then started
+    app.update();
+    app.update();
+    let state = app.world.get::<DialogState>(frog).unwrap();
+    assert_eq!(state.runner().current_line(), Some(&"KeroKero".to_string()));
+
+    app.world.send_event(AdvanceDialogEvent { entity: frog });
+    app.update();
+
+    // The edited line is read in place
+    edit(
+        &mut app,
+        &handle,
+        vec!["KeroKero".to_string(), "Hi".to_string()],
+    );
+    let state = app.world.get::<DialogState>(frog).unwrap();
+    assert_eq!(state.runner().current_line(), Some(&"Hi".to_string()));
+    let reloaded = app.world.resource::<Events<DialogReloadedEvent>>();
+    assert_eq!(
+        reloaded.iter_current_update_events().collect::<Vec<_>>(),
+        vec![&DialogReloadedEvent {
+            entity: frog,
+            restarted: false,
+        }]
+    );
+
+    // The current line is removed: back to the entry node
+    edit(&mut app, &handle, vec!["Kero".to_string()]);
+    let state = app.world.get::<DialogState>(frog).unwrap();
+    assert_eq!(state.runner().current_line(), Some(&"Kero".to_string()));
+    let reloaded = app.world.resource::<Events<DialogReloadedEvent>>();
+    assert_eq!(
+        reloaded.iter_current_update_events().collect::<Vec<_>>(),
+        vec![&DialogReloadedEvent {
+            entity: frog,
+            restarted: true,
+        }]
+    );
+    let entered = app.world.resource::<Events<NodeEnteredEvent>>();
+    assert_eq!(entered.iter_current_update_events().len(), 1);
+}
+
+#[test]
+fn test_asset_invalid_dialog_fails() {
+    let mut app = app();
+    let handle: Handle<Dialog> = app
+        .world
+        .resource::<AssetServer>()
+        .load("broken.dialog.yml");
+    let frog = app.world.spawn(handle.clone()).id();
+
+    assert_eq!(wait_for(&mut app, &handle), LoadState::Failed);
+    app.update();
+    assert!(app.world.get::<DialogState>(frog).is_none());
+}
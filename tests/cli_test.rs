@@ -0,0 +1,118 @@
+#![cfg(feature = "cli")]
+
+use std::{
+    io::Write,
+    process::{Command, Output, Stdio},
+};
+
+use yml_dialog::*;
+
+const POND_DIALOG: &str = include_str!("assets/pond.dialog.yml");
+
+/// Runs `yml_dialog` within `tests/assets`, writing `input` to it.
+fn yml_dialog(args: &[&str], input: &str) -> Output {
+    let mut child = Command::new(env!("CARGO_BIN_EXE_yml_dialog"))
+        .args(args)
+        .current_dir(concat!(env!("CARGO_MANIFEST_DIR"), "/tests/assets"))
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .unwrap();
+    child
+        .stdin
+        .take()
+        .unwrap()
+        .write_all(input.as_bytes())
+        .unwrap();
+    child.wait_with_output().unwrap()
+}
+
+fn stdout(output: &Output) -> String {
+    String::from_utf8(output.stdout.clone()).unwrap()
+}
+
+#[test]
+fn test_check() {
+    let output = yml_dialog(&["check", "pond.dialog.yml", "broken.dialog.yml"], "");
+    assert!(!output.status.success());
+    assert_eq!(
+        stdout(&output),
+        "pond.dialog.yml: ok
+broken.dialog.yml: error: node 3, content[0].exit_state: the exit_state `2` points to no node
+"
+    );
+
+    let output = yml_dialog(&["check", "--from", "json", "pond.dialog.yml"], "");
+    assert!(!output.status.success());
+    assert!(stdout(&output).starts_with("error: pond.dialog.yml: "));
+}
+
+#[test]
+fn test_play() {
+    let output = yml_dialog(
+        &[
+            "play",
+            "pond.dialog.yml",
+            "--event",
+            "FrogLove",
+            "--karma",
+            "2",
+        ],
+        "3\n1\nq\n",
+    );
+    assert!(output.status.success());
+    assert_eq!(
+        stdout(&output),
+        "Frog: KeroKero
+[event FrogTalk]
+Player:
+  1. Hi
+> pick a number from 1 to 1, or q to quit
+> Frog: KeroKero
+[event FrogTalk]
+Player:
+  1. Hi
+> -- quit --
+karma: 2, events: FrogLove, FrogTalk
+"
+    );
+}
+
+#[test]
+fn test_graph_and_convert() {
+    let dialog: Dialog = Dialog::from_yaml_str(POND_DIALOG).unwrap();
+
+    let output = yml_dialog(&["graph", "--format", "mermaid", "pond.dialog.yml"], "");
+    assert_eq!(stdout(&output), dialog.to_mermaid());
+
+    let output = yml_dialog(&["convert", "pond.dialog.yml", "-", "--to", "yarn"], "");
+    assert_eq!(stdout(&output), dialog.to_yarn_string());
+
+    let output = yml_dialog(&["convert", "pond.dialog.yml", "-", "--to", "json"], "");
+    assert_eq!(
+        Dialog::<Condition>::from_json_str(&stdout(&output)).unwrap(),
+        dialog
+    );
+
+    let output = yml_dialog(&["convert", "pond.dialog.yml", "pond.ink"], "");
+    assert!(!output.status.success());
+    assert_eq!(
+        String::from_utf8(output.stderr).unwrap(),
+        "error: Ink scripts can be read, not written\n"
+    );
+}
+
+#[test]
+fn test_stats() {
+    let output = yml_dialog(&["stats", "pond.dialog.yml"], "");
+    assert_eq!(
+        stdout(&output),
+        "pond.dialog.yml: 2 nodes, 2 speakers
+  speaker  nodes  lines  words  choices
+  Frog         1      1      1        0
+  Player       1      0      2        2
+  total        2      1      3        2
+"
+    );
+}
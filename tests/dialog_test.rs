@@ -0,0 +1,158 @@
+use std::collections::BTreeMap;
+use yml_dialog::*;
+
+fn frog_nodes() -> BTreeMap<usize, DialogNode> {
+    let mut map = BTreeMap::new();
+    map.insert(
+        1,
+        DialogNode::new(
+            "The Frog".to_string(),
+            Content::Choices(vec![
+                Choice::new(String::from("Hello HomeGirl"), None, 2),
+                Choice::new(String::from("KeroKero"), None, 3),
+            ]),
+            vec![],
+        ),
+    );
+    map.insert(
+        2,
+        DialogNode::new(
+            "Random Frog".to_string(),
+            Content::Monolog {
+                text: vec![String::from("Yo Homie")],
+                exit_state: 4,
+            },
+            vec![],
+        ),
+    );
+    map.insert(
+        3,
+        DialogNode::new(
+            String::new(),
+            Content::Monolog {
+                text: vec![String::from("KeroKero")],
+                exit_state: 4,
+            },
+            vec![],
+        ),
+    );
+    map
+}
+
+#[test]
+fn test_dialog_deserialize_bare_map() {
+    let yaml = "1:
+  source: Le Pape
+  content:
+    monolog:
+      text:
+      - Hello Homie
+      exit_state: 2
+  trigger_event: []
+3:
+  source: Le Pape
+  content:
+    monolog:
+      text:
+      - Bye Homie
+      exit_state: 4
+  trigger_event: []\n";
+
+    let dialog: Dialog = serde_yaml::from_str(yaml).unwrap();
+    let map: BTreeMap<usize, DialogNode> = serde_yaml::from_str(yaml).unwrap();
+
+    assert_eq!(dialog, Dialog::new(map));
+    assert_eq!(dialog.entry(), Some(1));
+    assert_eq!(dialog.name(), &None);
+    // Without any metadata, the dialog is written back as a bare map
+    assert_eq!(serde_yaml::to_string(&dialog).unwrap(), yaml);
+}
+
+#[test]
+fn test_dialog_serialize_metadata() {
+    let dialog = Dialog::new(frog_nodes())
+        .with_name("Frog".to_string())
+        .with_default_speaker("Random Frog".to_string())
+        .with_entry(2);
+
+    let yaml = serde_yaml::to_string(&dialog).unwrap();
+
+    assert!(yaml.starts_with(
+        "version: 2
+name: Frog
+default_speaker: Random Frog
+entry: 2
+nodes:
+  1:
+    source: The Frog\n"
+    ));
+    assert_eq!(serde_yaml::from_str::<Dialog>(&yaml).unwrap(), dialog);
+}
+
+#[test]
+fn test_dialog_deserialize_unknown_field() {
+    let yaml = "nam: Frog
+nodes: {}\n";
+
+    let error = serde_yaml::from_str::<Dialog>(yaml).unwrap_err();
+
+    assert!(error.to_string().contains("unknown field `nam`"));
+}
+
+#[test]
+fn test_dialog_deserialize_duplicate_node() {
+    let yaml = "1:
+  source: Le Pape
+nodes:
+  1:
+    source: La Pape\n";
+
+    let error = serde_yaml::from_str::<Dialog>(yaml).unwrap_err();
+
+    assert!(error.to_string().contains("duplicate node key `1`"));
+}
+
+#[test]
+fn test_dialog_lookup_insert_remove() {
+    let mut dialog = Dialog::new(frog_nodes()).with_default_speaker("Random Frog".to_string());
+
+    assert_eq!(dialog.len(), 3);
+    assert!(dialog.contains(&2));
+    assert!(!dialog.contains(&4));
+    assert_eq!(dialog.speaker(dialog.get(&1).unwrap()), "The Frog");
+    assert_eq!(dialog.speaker(dialog.get(&3).unwrap()), "Random Frog");
+
+    dialog.set_entry(Some(3));
+    assert_eq!(dialog.entry_node(), dialog.get(&3));
+
+    let removed = dialog.remove(&3).unwrap();
+    assert_eq!(removed.source(), "");
+    // The entry falls back to the lowest key
+    assert_eq!(dialog.entry(), Some(1));
+
+    assert!(dialog.insert(0, removed.clone()).is_none());
+    assert_eq!(dialog.entry(), Some(0));
+    assert_eq!(dialog.insert(0, removed.clone()), Some(removed));
+
+    let keys: Vec<usize> = dialog.iter().map(|(key, _)| *key).collect();
+    assert_eq!(keys, vec![0, 1, 2]);
+}
+
+#[test]
+fn test_dialog_free_key_and_references() {
+    let mut dialog = Dialog::new(frog_nodes());
+
+    assert_eq!(dialog.get(&1).unwrap().exit_states(), vec![2, 3]);
+    assert_eq!(dialog.references_to(&4), vec![2, 3]);
+    assert_eq!(dialog.references_to(&1), Vec::<usize>::new());
+    // Above the end `4`
+    assert_eq!(dialog.free_key(), 5);
+
+    dialog.set_entry(Some(9));
+    assert_eq!(dialog.free_key(), 10);
+
+    dialog.set_entry(None);
+    dialog.remove(&2);
+    dialog.remove(&3);
+    assert_eq!(dialog.free_key(), 4);
+}
@@ -0,0 +1,162 @@
+use serde::{Deserialize, Serialize};
+use yml_dialog::*;
+
+const FROG_DIALOG: &str = "1:
+  source: Frog
+  content:
+    choices:
+    - text: You = Cool
+      exit_state: 2
+      effects:
+      - add_karma: 5
+      - custom: Croak
+    - text: You = Not Cool
+      exit_state: 3
+      effects:
+      - add_karma: -5
+2:
+  source: Frog
+  content:
+    monolog:
+      text:
+      - Big love on you <3
+      exit_state: 4
+  effects:
+  - set_event: FrogLove
+  - clear_event: FrogHate
+  - set_var:
+      name: trust
+      value: 3
+3:
+  source: Frog
+  content:
+    monolog:
+      text:
+      - I'm sad now.
+      exit_state: 4
+  effects:
+  - set_event: FrogHate\n";
+
+#[derive(Serialize, Deserialize, Clone, Eq, PartialEq, Debug)]
+enum FrogEffect {
+    Croak,
+    Jump { height: u32 },
+}
+
+#[derive(Default)]
+struct Game {
+    events: Vec<String>,
+    karma: i32,
+    trust: i32,
+    croaks: usize,
+}
+
+impl EffectHandler<FrogEffect> for Game {
+    fn set_event(&mut self, event: &str) {
+        self.events.push(event.to_string());
+    }
+
+    fn clear_event(&mut self, event: &str) {
+        self.events.retain(|active| active != event);
+    }
+
+    fn add_karma(&mut self, amount: i32) {
+        self.karma += amount;
+    }
+
+    fn set_var(&mut self, name: &str, value: &Value) {
+        assert_eq!(name, "trust");
+        self.trust = value.as_int().unwrap();
+    }
+
+    fn custom(&mut self, effect: &FrogEffect) {
+        if effect == &FrogEffect::Croak {
+            self.croaks += 1;
+        }
+    }
+}
+
+#[test]
+fn test_effects_applied_through_runner() {
+    let dialog: Dialog<Condition, (), FrogEffect> = serde_yaml::from_str(FROG_DIALOG).unwrap();
+    let mut runner = DialogRunner::new(dialog);
+    let mut game = Game {
+        events: vec!["FrogHate".to_string()],
+        ..Default::default()
+    };
+
+    let step = runner.choose(0, &ConditionContext::default()).unwrap();
+    let Step::Node { key, effects, .. } = step else {
+        panic!("expected a node, got {step:?}");
+    };
+    assert_eq!(key, 2);
+    // The effects of the choice come first
+    assert_eq!(
+        effects[..2],
+        [Effect::AddKarma(5), Effect::Custom(FrogEffect::Croak)]
+    );
+    for effect in &effects {
+        effect.apply(&mut game);
+    }
+
+    assert_eq!(game.events, vec!["FrogLove"]);
+    assert_eq!(game.karma, 5);
+    assert_eq!(game.trust, 3);
+    assert_eq!(game.croaks, 1);
+}
+
+#[test]
+fn test_effects_rejected_at_load() {
+    let cases = [
+        ("- set_evnt: FrogLove\n", "unknown variant `set_evnt`"),
+        (
+            "- add_karma: lots\n",
+            "invalid type: string \"lots\", expected i32",
+        ),
+        ("- set_var:\n    name: trust\n", "missing field `value`"),
+        (
+            "- custom: Sing\n",
+            "unknown variant `Sing`, expected `Croak` or `Jump`",
+        ),
+    ];
+
+    for (yaml, reason) in cases {
+        let error = serde_yaml::from_str::<Vec<Effect<FrogEffect>>>(yaml)
+            .unwrap_err()
+            .to_string();
+        assert!(error.contains(reason), "{yaml}: {error}");
+    }
+
+    let error = serde_yaml::from_str::<Dialog>(&FROG_DIALOG.replace("clear_event", "clear_evnt"))
+        .unwrap_err()
+        .to_string();
+    assert!(error.contains("unknown variant `clear_evnt`"), "{error}");
+}
+
+#[test]
+fn test_effects_round_trip() {
+    let choice = Choice::new("Jump".to_string(), None, 2)
+        .with_effects(vec![Effect::Custom("Jump".to_string())]);
+    let node = DialogNode::new("Frog".to_string(), Content::Choices(vec![choice]), vec![])
+        .with_effects(vec![Effect::SetEvent("FrogTalk".to_string())]);
+
+    let yaml = serde_yaml::to_string(&node).unwrap();
+    assert_eq!(
+        yaml,
+        "source: Frog
+content:
+  choices:
+  - text: Jump
+    condition: null
+    exit_state: 2
+    effects:
+    - custom: Jump
+trigger_event: []
+effects:
+- set_event: FrogTalk\n"
+    );
+    assert_eq!(serde_yaml::from_str::<DialogNode>(&yaml).unwrap(), node);
+
+    let jump: Effect<FrogEffect> = serde_yaml::from_str("custom: !Jump\n  height: 2\n").unwrap();
+    assert_eq!(jump, Effect::Custom(FrogEffect::Jump { height: 2 }));
+}
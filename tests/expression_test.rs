@@ -0,0 +1,147 @@
+use yml_dialog::*;
+
+fn context(karma: Option<i32>, events: &[&str]) -> ConditionContext {
+    ConditionContext::new(karma, events.iter().map(ToString::to_string).collect())
+}
+
+#[test]
+fn test_expression_precedence() {
+    let expression: Expression = "has(A) || has(B) && !has(C)".parse().unwrap();
+
+    assert_eq!(
+        expression,
+        Expression::Or(
+            Box::new(Expression::Has("A".to_string())),
+            Box::new(Expression::And(
+                Box::new(Expression::Has("B".to_string())),
+                Box::new(Expression::Not(Box::new(Expression::Has("C".to_string())))),
+            )),
+        )
+    );
+    assert!(expression.evaluate(&context(None, &["A", "C"])));
+    assert!(!expression.evaluate(&context(None, &["B", "C"])));
+
+    let grouped: Expression = "(has(A) || has(B)) && !has(C)".parse().unwrap();
+    assert!(!grouped.evaluate(&context(None, &["A", "C"])));
+}
+
+#[test]
+fn test_expression_comparisons() {
+    let cases = [
+        ("karma == 3", 3, true),
+        ("karma != 3", 3, false),
+        ("karma < -2", -3, true),
+        ("karma <= -2", -2, true),
+        ("karma > 10", 10, false),
+        ("karma >= 10", 10, true),
+    ];
+
+    for (input, karma, expected) in cases {
+        let expression: Expression = input.parse().unwrap();
+        assert_eq!(
+            expression.evaluate(&context(Some(karma), &[])),
+            expected,
+            "{input}"
+        );
+        // An unknown variable never verifies a comparison
+        assert!(!expression.evaluate(&context(None, &[])), "{input}");
+    }
+}
+
+#[test]
+fn test_expression_display_round_trip() {
+    let inputs = [
+        "has(HasFriend) && !has(FirstKill) || karma >= 10",
+        "(has(A) || has(B)) && !(karma < 0)",
+        "!(has(A) && has(B)) || false",
+        "has(\"Deal HP\") && true",
+    ];
+
+    for input in inputs {
+        let expression: Expression = input.parse().unwrap();
+        assert_eq!(expression.to_string(), input);
+    }
+    assert_eq!(
+        "((has(A)))&&(has(B)&&has(C))"
+            .parse::<Expression>()
+            .unwrap()
+            .to_string(),
+        "has(A) && (has(B) && has(C))"
+    );
+}
+
+#[test]
+fn test_expression_parse_errors() {
+    let cases = [
+        ("", "expected `has(..)`, a comparison, `true`, `false`, `!` or `(`, found the end at column 1"),
+        ("has(A) &&", "expected `has(..)`, a comparison, `true`, `false`, `!` or `(`, found the end at column 10"),
+        ("has(A", "expected `)`, found the end at column 6"),
+        ("has()", "expected an event name, found `)` at column 5"),
+        ("karma has(A)", "expected a comparison operator after `karma`, found `has` at column 7"),
+        ("karma >= ten", "expected a value, found `ten` at column 10"),
+        ("has(A) has(B)", "expected `&&`, `||` or the end, found `has` at column 8"),
+        ("has(A) & has(B)", "unexpected character `&` at column 8"),
+        ("has(\"A)", "unterminated string at column 5"),
+    ];
+
+    for (input, message) in cases {
+        let error = input.parse::<Expression>().unwrap_err();
+        assert_eq!(error.to_string(), message, "{input}");
+    }
+
+    // Too deep to be parsed without overflowing the stack
+    for input in ["(".repeat(100_000), "!".repeat(100_000)] {
+        let error = input.parse::<Expression>().unwrap_err();
+        assert_eq!(error.column(), 129);
+        assert_eq!(
+            error.message(),
+            "the expression is nested more than 128 levels deep"
+        );
+    }
+    let nested = format!("{}true{}", "(".repeat(128), ")".repeat(128));
+    assert_eq!(nested.parse::<Expression>(), Ok(Expression::Bool(true)));
+
+    // Each `&&` or `||` of a chain is one level deeper
+    for operator in [" && ", " || "] {
+        let chain = vec!["has(A)"; 200_000].join(operator);
+        let error = chain.parse::<Expression>().unwrap_err();
+        // The 129th operator, after 128 terms and operators
+        assert_eq!(error.column(), 128 * 10 + 8);
+        assert_eq!(
+            error.message(),
+            "the expression is nested more than 128 levels deep"
+        );
+        let chain = vec!["has(A)"; 129].join(operator);
+        assert!(chain.parse::<Expression>().is_ok());
+    }
+    // Including the operators of the parentheses it is in
+    let chain = format!("!({})", vec!["has(A)"; 129].join(" && "));
+    assert!(chain.parse::<Expression>().is_err());
+}
+
+#[test]
+fn test_expression_condition_in_yaml() {
+    let yaml = "text: Hello friend
+condition: has(HasFriend) && !has(FirstKill) || karma >= 10
+exit_state: 2\n";
+    let choice: Choice = serde_yaml::from_str(yaml).unwrap();
+
+    assert!(choice.is_verified(&context(Some(0), &["HasFriend"])));
+    assert!(!choice.is_verified(&context(Some(0), &["HasFriend", "FirstKill"])));
+    assert!(choice.is_verified(&context(Some(10), &["FirstKill"])));
+
+    let error = serde_yaml::from_str::<Choice>("text: Hi\ncondition: karma >> 2\nexit_state: 2\n")
+        .unwrap_err()
+        .to_string();
+    assert!(
+        error.contains("invalid expression `karma >> 2`: expected a value, found `>` at column 8"),
+        "{error}"
+    );
+
+    let condition =
+        Condition::new(Some((0, 5)), vec![]).with_expression("has(FrogLove)".parse().unwrap());
+    assert_eq!(
+        serde_yaml::to_string(&condition).unwrap(),
+        "events: []\nkarma_threshold:\n- 0\n- 5\nexpression: has(FrogLove)\n"
+    );
+}
@@ -0,0 +1,150 @@
+#![cfg(feature = "fluent")]
+
+use yml_dialog::*;
+
+const MERCHANT_DIALOG: &str = "entry: merchant
+nodes:
+  merchant:
+    source: Merchant
+    content:
+      text:
+      - You have some coins.
+      - Welcome!
+      exit_state: answer
+    line_ids: [coins]
+  answer:
+    source: Player
+    content:
+    - text: Buy
+      exit_state: 3
+    - id: leave
+      text: Leave
+      exit_state: 3\n";
+
+const ENGLISH: &str = "coins = You have { $coins ->
+    [one] one coin
+   *[other] { $coins } coins
+}.
+merchant =
+    .line1 = { $gender ->
+        [female] Welcome, madam!
+       *[other] Welcome, sir!
+    }
+answer =
+    .choice0 = Buy for { $price } coins
+leave = Leave
+";
+
+const FRENCH: &str = "coins = Vous avez { $coins ->
+    [one] une pièce
+   *[other] { $coins } pièces
+}.
+leave = Partir
+";
+
+fn fluent() -> FluentLocalizer {
+    let mut fluent = FluentLocalizer::new();
+    fluent.add_resource("en", ENGLISH).unwrap();
+    fluent.add_resource("fr", FRENCH).unwrap();
+    fluent
+}
+
+#[test]
+fn test_fluent_runner() {
+    let dialog: Dialog = serde_yaml::from_str(MERCHANT_DIALOG).unwrap();
+    let mut fluent = fluent();
+    let variables = Variables::from_iter([
+        ("coins", Value::Int(3)),
+        ("gender", Value::String("female".to_string())),
+        ("price", Value::Float(2.5)),
+    ]);
+    let context = ConditionContext::default();
+
+    let mut runner = DialogRunner::new(dialog);
+    assert_eq!(
+        runner.localized_line_with(&fluent, &variables).as_deref(),
+        Some("You have 3 coins.")
+    );
+    // Without variables, the placeholder shows what is missing
+    assert_eq!(
+        runner.localized_line(&fluent).as_deref(),
+        Some("You have {$coins} coins.")
+    );
+    runner.advance().unwrap();
+    assert_eq!(
+        runner.localized_line_with(&fluent, &variables).as_deref(),
+        Some("Welcome, madam!")
+    );
+    runner.advance().unwrap();
+    assert_eq!(
+        runner.localized_choices_with(&context, &fluent, &variables),
+        ["Buy for 2.5 coins", "Leave"]
+    );
+
+    // French first, falling back to English
+    fluent.request_locales(&["fr-FR", "de"]);
+    assert_eq!(fluent.locales(), ["fr", "en"]);
+    assert_eq!(
+        runner.localized_choices_with(&context, &fluent, &variables),
+        ["Buy for 2.5 coins", "Partir"]
+    );
+    let variables = Variables::from_iter([("coins", 1)]);
+    assert_eq!(
+        fluent.localize_with("coins", &variables).as_deref(),
+        Some("Vous avez une pièce.")
+    );
+
+    // A chain with a static table
+    let mut table = StringTable::new("fr".to_string());
+    table.insert("coins".to_string(), "Des pièces".to_string());
+    let chain: [&dyn Localizer; 2] = [&table, &fluent];
+    assert_eq!(
+        chain.localize_with("coins", &variables).as_deref(),
+        Some("Des pièces")
+    );
+    assert_eq!(
+        chain.localize_with("leave", &variables).as_deref(),
+        Some("Partir")
+    );
+}
+
+#[test]
+fn test_fluent_diagnostics() {
+    let dialog: Dialog = serde_yaml::from_str(MERCHANT_DIALOG).unwrap();
+    let fluent = fluent();
+
+    let variables = Variables::from_iter([("coins", 3)]);
+    let diagnostics: Vec<String> = fluent
+        .check(&dialog, &variables)
+        .iter()
+        .map(ToString::to_string)
+        .collect();
+    assert_eq!(
+        diagnostics,
+        [
+            "en: `merchant.line1`: Resolver error: Unknown variable: $gender",
+            "fr: no message for `merchant.line1`",
+            "en: `answer.choice0`: Resolver error: Unknown variable: $price",
+            "fr: no message for `answer.choice0`",
+        ]
+    );
+
+    let mut fluent = FluentLocalizer::new();
+    let errors = fluent
+        .add_resource("en", "hello = Hello\n\nbroken {\nbye = Bye\nhello = Hi\n")
+        .unwrap_err();
+    let errors: Vec<(Option<usize>, Option<String>)> = errors
+        .iter()
+        .map(|error| (*error.line(), error.id().clone()))
+        .collect();
+    assert_eq!(errors, [(Some(3), None), (None, None)]);
+    // What can be read is kept
+    assert_eq!(fluent.localize("bye").as_deref(), Some("Bye"));
+    assert_eq!(fluent.localize("hello").as_deref(), Some("Hello"));
+
+    let errors = fluent.add_resource("not a locale", "").unwrap_err();
+    assert_eq!(
+        errors[0].to_string(),
+        "not a locale: invalid locale `not a locale`"
+    );
+}
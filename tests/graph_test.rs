@@ -0,0 +1,88 @@
+use yml_dialog::*;
+
+#[cfg(feature = "yaml")]
+#[test]
+fn test_graph_of_pond() {
+    let dialog: Dialog = Dialog::from_yaml_str(include_str!("assets/pond.dialog.yml")).unwrap();
+
+    let dot = dialog.to_dot();
+    assert_eq!(
+        dot,
+        r##"digraph "Frog Pond" {
+    node [shape=box, style=rounded];
+    start [shape=point];
+    start -> n1;
+    n1 [label="1\nFrog\nKeroKero"];
+    n4 [label="4: greet_player\nPlayer\n2 choices\ntrigger: FrogTalk", style="rounded,filled,bold", fillcolor="#ffe08a"];
+    exit3 [label="end 3", shape=doublecircle];
+    n1 -> n4;
+    n4 -> n1 [label="Hi\n[has(FrogLove) && karma >= 2]"];
+    n4 -> exit3 [label="Bye\n[has(FrogHate) && karma >= 0 && karma <= 10 && trust >= 2 && trust <= 10]"];
+}
+"##
+    );
+
+    let mermaid = dialog.to_mermaid();
+    assert!(mermaid.starts_with("flowchart TD\n    start(( )) --> n1\n"));
+    assert!(mermaid.contains(
+        "    n4[\"4: greet_player<br/>Player<br/>2 choices<br/>trigger: FrogTalk\"]:::trigger\n"
+    ));
+    assert!(mermaid.contains("    n4 -->|\"Hi<br/>[has(FrogLove) && karma >= 2]\"| n1\n"));
+    assert!(mermaid.contains("    exit3(((\"end 3\")))\n"));
+}
+
+#[test]
+fn test_graph_escapes_and_shortens() {
+    let dialog: Dialog<Expression> = Dialog::new(
+        [
+            (
+                1,
+                DialogNode::new(
+                    "The \"Frog\"".to_string(),
+                    Content::Monolog {
+                        text: vec![
+                            "A line far too long to be shown whole in the graph of the dialog"
+                                .to_string(),
+                            "The second line".to_string(),
+                        ],
+                        exit_state: 2,
+                    },
+                    vec![],
+                ),
+            ),
+            (
+                2,
+                DialogNode::new(
+                    String::new(),
+                    Content::Choices(vec![Choice::new(
+                        "Say \"hi\"".to_string(),
+                        Some(r#"has("Any Event") || !has(FrogHate)"#.parse().unwrap()),
+                        1,
+                    )]),
+                    vec![],
+                ),
+            ),
+        ]
+        .into_iter()
+        .collect(),
+    );
+
+    let dot = dialog.to_dot();
+    assert!(dot.starts_with("digraph \"dialog\" {\n"));
+    assert!(dot.contains(
+        r#"    n1 [label="1\nThe \"Frog\"\nA line far too long to be shown whole i…"];"#
+    ));
+    assert!(dot.contains(r#"    n2 [label="2\n1 choice"];"#));
+    assert!(dot
+        .contains(r#"    n2 -> n1 [label="Say \"hi\"\n[has(\"Any Event\") || !has(FrogHate)]"];"#));
+    assert!(!dot.contains("exit"));
+
+    let mermaid = dialog.to_mermaid();
+    assert!(mermaid.contains(
+        "    n1[\"1<br/>The #quot;Frog#quot;<br/>A line far too long to be shown whole i…\"]\n"
+    ));
+    assert!(mermaid.contains(
+        "    n2 -->|\"Say #quot;hi#quot;<br/>[has(#quot;Any Event#quot;) || !has(FrogHate)]\"| n1\n"
+    ));
+    assert!(!mermaid.contains("classDef"));
+}
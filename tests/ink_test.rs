@@ -0,0 +1,197 @@
+use yml_dialog::*;
+
+const FROG_INK: &str = "// The frog of the pond
+Welcome to the pond.
+-> pond
+
+=== pond ===
+~ temp mood = \"grumpy\"
+KeroKero
+* [Hi]
+    Hi frog!
+    ** {FrogLove} [Kiss the frog]
+        ~ karma += 2
+        -> kiss
+    ** [Wave]
+        ~ waved = true
+        You wave.
+    -- The frog stares at you.
+* {not FrogHate} Bye[.] friend
+    ~ FrogLove = false
+    -> END
++ {kiss} [Again?] -> kiss
+- The frog jumps away.
+-> DONE
+
+=== kiss ===
+~ karma -= 1
+Smooch.
+-> pond
+";
+
+/// Returns the content of the node reached by following the monologs from `key`.
+fn choices_after(dialog: &Dialog, mut key: usize) -> &Vec<Choice> {
+    loop {
+        match dialog.get(&key).unwrap().content() {
+            Content::Monolog { exit_state, .. } => key = *exit_state,
+            Content::Choices(choices) => return choices,
+        }
+    }
+}
+
+#[test]
+fn test_ink_import() {
+    let (dialog, report) = import_ink(FROG_INK);
+    assert!(report.issues().is_empty(), "{:?}", report.issues());
+    assert!(
+        dialog
+            .validate()
+            .iter()
+            .all(|diagnostic| !diagnostic.is_error()),
+        "{:?}",
+        dialog.validate()
+    );
+
+    let entry = dialog.entry_node().unwrap();
+    assert_eq!(
+        entry.content(),
+        &Content::Monolog {
+            text: vec!["Welcome to the pond.".to_string()],
+            exit_state: dialog.key_of("pond").unwrap(),
+        }
+    );
+
+    let pond = dialog.get(&dialog.key_of("pond").unwrap()).unwrap();
+    assert_eq!(
+        pond.effects(),
+        &vec![Effect::SetVar {
+            name: "mood".to_string(),
+            value: Value::String("grumpy".to_string()),
+        }]
+    );
+
+    let choices = choices_after(&dialog, dialog.key_of("pond").unwrap());
+    let texts: Vec<&String> = choices.iter().map(Choice::text).collect();
+    assert_eq!(texts, ["Hi", "Bye.", "Again?"]);
+    assert_eq!(
+        choices[1].condition().as_ref().unwrap().expression(),
+        &Some("!has(FrogHate)".parse().unwrap())
+    );
+    assert_eq!(
+        choices[2].condition(),
+        &Some(Condition::new(None, vec!["kiss".to_string()]))
+    );
+    assert_eq!(choices[2].exit_state(), &dialog.key_of("kiss").unwrap());
+
+    // The visit of `kiss` is checked: entering it triggers the event
+    let kiss = dialog.get(&dialog.key_of("kiss").unwrap()).unwrap();
+    assert_eq!(kiss.trigger_event(), &vec!["kiss".to_string()]);
+    assert_eq!(kiss.effects(), &vec![Effect::AddKarma(-1)]);
+
+    // The output of `Bye[.] friend`, then the end
+    let bye = dialog.get(choices[1].exit_state()).unwrap();
+    assert_eq!(
+        bye.content(),
+        &Content::Monolog {
+            text: vec!["Bye friend".to_string()],
+            exit_state: dialog.len() + 1,
+        }
+    );
+    assert_eq!(
+        bye.effects(),
+        &vec![Effect::ClearEvent("FrogLove".to_string())]
+    );
+
+    // The nested choices, whose loose ends go to the gathers
+    let nested = choices_after(&dialog, *choices[0].exit_state());
+    assert_eq!(
+        nested[0].exit_state(),
+        dialog
+            .get(nested[0].exit_state())
+            .map(|_| nested[0].exit_state())
+            .unwrap()
+    );
+    let kiss_path = dialog.get(nested[0].exit_state()).unwrap();
+    assert_eq!(kiss_path.effects(), &vec![Effect::AddKarma(2)]);
+    let wave = dialog.get(nested[1].exit_state()).unwrap();
+    assert_eq!(wave.trigger_event(), &vec!["waved".to_string()]);
+    let Content::Monolog {
+        exit_state: stare, ..
+    } = wave.content()
+    else {
+        panic!("`You wave.` is a monolog");
+    };
+    let Content::Monolog {
+        text,
+        exit_state: jump,
+    } = dialog.get(stare).unwrap().content()
+    else {
+        panic!("the gather is a monolog");
+    };
+    assert_eq!(text, &vec!["The frog stares at you.".to_string()]);
+    assert_eq!(
+        dialog.get(jump).unwrap().content(),
+        &Content::Monolog {
+            text: vec!["The frog jumps away.".to_string()],
+            exit_state: dialog.len() + 1,
+        }
+    );
+}
+
+#[test]
+fn test_ink_import_reports() {
+    let (dialog, report) = import_ink(
+        "VAR fear = 0
+Hello <> there # greeting
+{fear > 2: Run!}
+~ fear++
+* (first) {fear > 2} [Flee] -> nowhere
+* -> pond
+- -> pond ->
+Unreachable
+=== function double(x) ===
+~ return x * 2
+=== pond ===
+",
+    );
+
+    let issues: Vec<String> = report.issues().iter().map(ToString::to_string).collect();
+    assert_eq!(
+        issues,
+        [
+            "line 1: `VAR` left out: `VAR fear = 0`",
+            "line 2: tag `greeting` left out",
+            "line 2: glue `<>` left out",
+            "line 3: inline logic kept as written: `{fear > 2: Run!}`",
+            "line 4: logic `~ fear++` left out",
+            "line 5: label `(first)` left out",
+            "line 5: condition `{fear > 2}` left out",
+            "line 5: unknown divert target `nowhere`, the dialog ends there",
+            "line 6: fallback choice left out",
+            "line 7: tunnel left out, the dialog ends there",
+            "line 8: unreachable after a divert, left out",
+            "line 9: function `function double(x)` left out",
+            "line 11: `pond` is empty, the dialog ends there",
+        ]
+    );
+
+    let entry = dialog.entry_node().unwrap();
+    assert_eq!(
+        entry.content(),
+        &Content::Monolog {
+            text: vec!["Hello there".to_string(), "{fear > 2: Run!}".to_string()],
+            exit_state: dialog.entry().unwrap() + 1,
+        }
+    );
+    let choices = choices_after(&dialog, dialog.entry().unwrap());
+    assert_eq!(choices.len(), 1);
+    assert_eq!(choices[0].condition(), &None);
+    assert!(!dialog.contains(choices[0].exit_state()));
+
+    // The opposite of the lowest integer overflows
+    let (_, report) = import_ink("~ karma -= -2147483648\nHello\n");
+    assert_eq!(
+        report.issues()[0].to_string(),
+        "line 1: logic `~ karma -= -2147483648` left out"
+    );
+}
@@ -0,0 +1,78 @@
+#![cfg(all(feature = "json", feature = "yaml"))]
+
+use yml_dialog::*;
+
+const POND_DIALOG: &str = include_str!("assets/pond.dialog.yml");
+
+#[test]
+fn test_json_round_trip() {
+    let dialog: Dialog = Dialog::from_yaml_str(POND_DIALOG).unwrap();
+
+    let json = dialog.to_json_string().unwrap();
+    assert!(json.contains("\"1\": {"), "{json}");
+    assert!(json.contains("\"monolog\": {"), "{json}");
+    assert!(json.contains("\"choices\": ["), "{json}");
+    assert_eq!(Dialog::from_json_str(&json), Ok(dialog.clone()));
+    assert_eq!(Dialog::from_json_reader(json.as_bytes()), Ok(dialog));
+}
+
+#[test]
+fn test_json_untagged_content() {
+    let tagged: Dialog = Dialog::from_json_str(
+        r#"{
+  "1": { "content": { "monolog": { "text": ["KeroKero"], "exit_state": 2 } } },
+  "2": { "content": { "choices": [{ "text": "Hi", "exit_state": 1 }] } }
+}"#,
+    )
+    .unwrap();
+    let untagged: Dialog = Dialog::from_json_str(
+        r#"{
+  "1": { "content": { "text": ["KeroKero"], "exit_state": 2 } },
+  "2": { "content": [{ "text": "Hi", "exit_state": 1 }] }
+}"#,
+    )
+    .unwrap();
+    assert_eq!(untagged, tagged);
+
+    let error = Dialog::<Condition>::from_json_str(
+        r#"{ "1": { "content": { "monolog": { "text": [], "exit_state": 2 }, "text": [] } } }"#,
+    )
+    .unwrap_err();
+    assert_eq!(error.node(), Some("1"));
+    assert_eq!(error.path(), "content");
+    assert_eq!(
+        error.message(),
+        "`monolog` or `choices` must be the only field of the content"
+    );
+}
+
+#[test]
+fn test_json_error_locations() {
+    let json = r#"{
+  "entry": 1,
+  "nodes": {
+    "1": {
+      "source": "Frog",
+      "content": { "choices": [
+        { "text": "Hi", "exit_state": 2 },
+        { "text": "Bye", "exit_state": true }
+      ] }
+    }
+  }
+}"#;
+
+    let error = Dialog::<Condition>::from_json_str(json).unwrap_err();
+    assert_eq!(error.node(), Some("1"));
+    assert_eq!(error.path(), "content.choices[1].exit_state");
+    assert_eq!(error.line(), Some(8));
+    assert_eq!(
+        error.message(),
+        "invalid type: boolean `true`, expected a node key or label"
+    );
+
+    let error =
+        Dialog::<Condition>::from_json_str(&json.replace("\"Bye\",", "\"Bye\"")).unwrap_err();
+    assert_eq!(error.node(), None);
+    assert_eq!(error.line(), Some(8));
+    assert_eq!(error.message(), "expected `,` or `}`");
+}
@@ -0,0 +1,192 @@
+use yml_dialog::*;
+
+const LABELED_DIALOG: &str = "name: Frog Pond
+entry: intro
+nodes:
+  intro:
+    source: Frog
+    content:
+      monolog:
+        text:
+        - KeroKero
+        exit_state: greet_player
+  1:
+    source: Frog
+    content:
+      monolog:
+        text:
+        - Bye
+        exit_state: 2
+  greet_player:
+    source: Player
+    content:
+      choices:
+      - text: Again
+        exit_state: intro
+      - text: Leave
+        exit_state: 1\n";
+
+#[test]
+fn test_labels_resolved_after_numeric_keys() {
+    let dialog: Dialog = serde_yaml::from_str(LABELED_DIALOG).unwrap();
+
+    // After the end `2`, `intro` is met first, then `greet_player` as an exit_state
+    assert_eq!(dialog.key_of("intro"), Some(3));
+    assert_eq!(dialog.key_of("greet_player"), Some(4));
+    assert_eq!(dialog.entry(), Some(3));
+    assert_eq!(dialog.validate(), vec![]);
+
+    let mut runner = DialogRunner::new(dialog);
+    assert_eq!(runner.current(), Some(3));
+    runner.advance().unwrap();
+    assert_eq!(runner.current_speaker(), Some("Player"));
+    runner.choose(0, &ConditionContext::default()).unwrap();
+    assert_eq!(runner.current(), Some(3));
+}
+
+#[test]
+fn test_labels_round_trip() {
+    let dialog: Dialog = serde_yaml::from_str(LABELED_DIALOG).unwrap();
+
+    let yaml = serde_yaml::to_string(&dialog).unwrap();
+
+    assert_eq!(
+        yaml,
+        "version: 2
+name: Frog Pond
+entry: intro
+nodes:
+  1:
+    source: Frog
+    content:
+      monolog:
+        text:
+        - Bye
+        exit_state: 2
+    trigger_event: []
+  intro:
+    source: Frog
+    content:
+      monolog:
+        text:
+        - KeroKero
+        exit_state: greet_player
+    trigger_event: []
+  greet_player:
+    source: Player
+    content:
+      choices:
+      - text: Again
+        condition: null
+        exit_state: intro
+      - text: Leave
+        condition: null
+        exit_state: 1
+    trigger_event: []\n"
+    );
+    assert_eq!(serde_yaml::from_str::<Dialog>(&yaml).unwrap(), dialog);
+}
+
+#[test]
+fn test_labels_named_like_fields_round_trip() {
+    let yaml = "nodes:
+  name:
+    source: Frog
+    content:
+      monolog:
+        text:
+        - KeroKero
+        exit_state: entry
+  entry:
+    source: Frog
+    content:
+      monolog:
+        text:
+        - Bye
+        exit_state: 1\n";
+    let dialog: Dialog = serde_yaml::from_str(yaml).unwrap();
+    assert_eq!(dialog.key_of("name"), Some(3));
+    assert_eq!(dialog.key_of("entry"), Some(2));
+    assert_eq!(dialog.name(), &None);
+
+    // Without any metadata, the nodes are still written under `nodes:`
+    let serialized = serde_yaml::to_string(&dialog).unwrap();
+    assert!(
+        serialized.starts_with("version: 2\nnodes:\n  entry:\n"),
+        "{serialized}"
+    );
+    assert_eq!(serde_yaml::from_str::<Dialog>(&serialized).unwrap(), dialog);
+}
+
+#[test]
+fn test_labels_errors() {
+    let cases = [
+        (
+            LABELED_DIALOG.replace("exit_state: intro", "exit_state: outro"),
+            "unknown label `outro` in the node `greet_player`",
+        ),
+        (
+            LABELED_DIALOG.replace("entry: intro", "entry: start"),
+            "unknown label `start`",
+        ),
+        (
+            LABELED_DIALOG.replace("  1:", "  greet_player:"),
+            "duplicate node key `greet_player`",
+        ),
+        // The keys given to the labels while they are read are out of reach
+        (
+            LABELED_DIALOG.replace("exit_state: 2", "exit_state: 9223372036854775807"),
+            "the node key `9223372036854775807` is too large",
+        ),
+        (
+            LABELED_DIALOG.replace("exit_state: 2", "exit_state: \"9223372036854775807\""),
+            "the node key `9223372036854775807` is too large",
+        ),
+        (
+            "1:\n  content: []\nnme: Frog\n".to_string(),
+            "unknown field `nme`, expected one of `name`, `default_speaker`, `entry`, `nodes`",
+        ),
+    ];
+
+    for (yaml, reason) in cases {
+        let error = serde_yaml::from_str::<Dialog>(&yaml)
+            .unwrap_err()
+            .to_string();
+        assert!(error.contains(reason), "{error}");
+    }
+
+    let error = serde_yaml::from_str::<Choice>("text: Hi\nexit_state: intro\n")
+        .unwrap_err()
+        .to_string();
+    assert!(error.contains("the label `intro` can only be resolved within a dialog"));
+}
+
+#[test]
+fn test_labels_edition() {
+    let mut dialog: Dialog = serde_yaml::from_str(LABELED_DIALOG).unwrap();
+
+    assert_eq!(dialog.set_label(1, "outro".to_string()), Ok(None));
+    assert_eq!(
+        dialog.set_label(1, "intro".to_string()),
+        Ok(Some("outro".to_string()))
+    );
+    // A label names a single node
+    assert_eq!(dialog.label(&3), None);
+    assert_eq!(dialog.key_of("intro"), Some(1));
+
+    // Neither a number nor a field of the dialog, which would be read back as such
+    for (label, message) in [
+        ("12", "the label `12` is a number"),
+        ("name", "the label `name` is a field of the dialog"),
+        ("version", "the label `version` is a field of the dialog"),
+    ] {
+        let error = dialog.set_label(1, label.to_string()).unwrap_err();
+        assert_eq!(error.message(), message);
+    }
+    assert_eq!(dialog.label(&1), Some(&"intro".to_string()));
+
+    dialog.remove(&1);
+    assert_eq!(dialog.key_of("intro"), None);
+    assert_eq!(dialog.remove_label(&4), Some("greet_player".to_string()));
+    assert!(dialog.labels().is_empty());
+}
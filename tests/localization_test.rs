@@ -0,0 +1,139 @@
+use yml_dialog::*;
+
+const BRIDGE_DIALOG: &str = "1:
+  source: Troll
+  content:
+    text:
+    - Halt!
+    - Who goes there?
+    exit_state: answer
+  line_ids: [troll.halt]
+answer:
+  source: Player
+  content:
+  - id: player.friend
+    text: A friend
+    exit_state: 3
+  - text: Nobody
+    condition:
+      events: [Invisible]
+    exit_state: 4
+  - text: Run away
+    exit_state: 4
+3:
+  source: Troll
+  content:
+    text:
+    - Pass, friend.
+    exit_state: 4\n";
+
+fn french() -> StringTable {
+    serde_yaml::from_str(
+        "locale: fr
+strings:
+  troll.halt: Halte !
+  player.friend: Un ami
+  answer.choice2: Fuir\n",
+    )
+    .unwrap()
+}
+
+#[test]
+fn test_localization_string_table() {
+    let dialog: Dialog = serde_yaml::from_str(BRIDGE_DIALOG).unwrap();
+
+    let strings = dialog.translatable_strings();
+    assert_eq!(strings.len(), 6);
+    assert_eq!(strings[0].id(), "troll.halt");
+    assert_eq!(strings[1].id(), "1.line1");
+    assert_eq!(strings[1].position(), TextPosition::Line(1));
+    assert_eq!(strings[2].id(), "3.line0");
+    assert_eq!(strings[2].node(), 3);
+    let answer = dialog.key_of("answer").unwrap();
+    assert_eq!(strings[3].id(), "player.friend");
+    assert_eq!(strings[3].node(), answer);
+    assert_eq!(strings[3].speaker(), "Player");
+    assert_eq!(strings[5].id(), "answer.choice2");
+    assert_eq!(strings[5].position(), TextPosition::Choice(2));
+    assert_eq!(strings[5].text(), "Run away");
+
+    let table = dialog.string_table("en".to_string());
+    assert_eq!(table.locale(), "en");
+    assert_eq!(
+        table.iter().collect::<Vec<_>>(),
+        [
+            (&"1.line1".to_string(), &"Who goes there?".to_string()),
+            (&"3.line0".to_string(), &"Pass, friend.".to_string()),
+            (&"answer.choice1".to_string(), &"Nobody".to_string()),
+            (&"answer.choice2".to_string(), &"Run away".to_string()),
+            (&"player.friend".to_string(), &"A friend".to_string()),
+            (&"troll.halt".to_string(), &"Halt!".to_string()),
+        ]
+    );
+
+    let yaml = serde_yaml::to_string(&table).unwrap();
+    assert_eq!(serde_yaml::from_str::<StringTable>(&yaml).unwrap(), table);
+
+    // The ids are written back
+    #[cfg(feature = "yaml")]
+    {
+        let written = dialog.to_yaml_string().unwrap();
+        assert!(written.contains("  line_ids:\n  - troll.halt\n"));
+        assert!(written.contains("    - id: player.friend\n      text: A friend\n"));
+        assert_eq!(Dialog::from_yaml_str(&written).unwrap(), dialog);
+    }
+}
+
+#[test]
+fn test_localization_runner() {
+    let dialog: Dialog = serde_yaml::from_str(BRIDGE_DIALOG).unwrap();
+    let french = french();
+    let mut runner = DialogRunner::new(dialog.clone());
+
+    assert_eq!(runner.localized_line(&french).as_deref(), Some("Halte !"));
+    runner.advance().unwrap();
+    // Falls back to the inline text
+    assert_eq!(
+        runner.localized_line(&french).as_deref(),
+        Some("Who goes there?")
+    );
+    runner.advance().unwrap();
+    assert_eq!(runner.localized_line(&french), None);
+
+    let context = ConditionContext::default();
+    assert_eq!(
+        runner.localized_choices(&context, &french),
+        ["Un ami", "Fuir"]
+    );
+
+    let translated = dialog.translated(&french);
+    assert_eq!(
+        translated.get(&1).unwrap().content(),
+        &Content::Monolog {
+            text: vec!["Halte !".to_string(), "Who goes there?".to_string()],
+            exit_state: dialog.key_of("answer").unwrap(),
+        }
+    );
+    assert_eq!(translated.string_table("fr".to_string()).len(), 6);
+    assert_eq!(
+        translated.text_id(&1, TextPosition::Line(0)),
+        dialog.text_id(&1, TextPosition::Line(0))
+    );
+}
+
+#[test]
+fn test_localization_duplicate_ids() {
+    let mut dialog: Dialog = serde_yaml::from_str(BRIDGE_DIALOG).unwrap();
+    *dialog.get_mut(&3).unwrap().line_ids_mut() = vec![Some("troll.halt".to_string())];
+
+    let diagnostics: Vec<String> = dialog
+        .validate()
+        .iter()
+        .filter(|diagnostic| diagnostic.is_error())
+        .map(ToString::to_string)
+        .collect();
+    assert_eq!(
+        diagnostics,
+        ["error: node 3, content.text[0]: the string id `troll.halt` is already used in the node 1"]
+    );
+}
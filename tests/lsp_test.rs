@@ -0,0 +1,234 @@
+#![cfg(feature = "lsp")]
+
+use std::{
+    io::{BufRead, BufReader, Read, Write},
+    process::{Child, ChildStdin, ChildStdout, Command, Stdio},
+};
+
+use serde_json::{json, Value};
+
+const URI: &str = "file:///pond.dialog.yml";
+
+const POND_DIALOG: &str = "name: Frog Pond
+default_speaker: Frog
+nodes:
+  1:
+    content:
+      monolog:
+        text:
+        - KeroKero
+        exit_state: greet_player
+    trigger_event: [FrogTalk]
+  greet_player:
+    source: Player
+    content:
+      choices:
+      - text: Hi
+        condition: has(FrogLove)
+        exit_state: 1
+      - text: Bye
+        exit_state: 2
+      - text: Later
+        exit_state: 3
+  3:
+    content:
+      monolog:
+        text: [Bye bye]
+        exit_state: 5
+";
+
+/// A `yml_dialog_lsp` talking over its standard input and output.
+struct Server {
+    child: Child,
+    input: ChildStdin,
+    output: BufReader<ChildStdout>,
+}
+
+impl Server {
+    fn start() -> Self {
+        let mut child = Command::new(env!("CARGO_BIN_EXE_yml_dialog_lsp"))
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .spawn()
+            .unwrap();
+        let input = child.stdin.take().unwrap();
+        let output = BufReader::new(child.stdout.take().unwrap());
+        let mut server = Server {
+            child,
+            input,
+            output,
+        };
+        server.request(0, "initialize", json!({ "capabilities": {} }));
+        server.notify("initialized", json!({}));
+        server
+    }
+
+    fn send(&mut self, message: Value) {
+        let body = message.to_string();
+        write!(self.input, "Content-Length: {}\r\n\r\n{body}", body.len()).unwrap();
+        self.input.flush().unwrap();
+    }
+
+    fn receive(&mut self) -> Value {
+        let mut length = 0;
+        loop {
+            let mut header = String::new();
+            self.output.read_line(&mut header).unwrap();
+            match header.trim_end() {
+                "" => break,
+                header => {
+                    if let Some(value) = header.strip_prefix("Content-Length: ") {
+                        length = value.parse().unwrap();
+                    }
+                }
+            }
+        }
+        let mut body = vec![0; length];
+        self.output.read_exact(&mut body).unwrap();
+        serde_json::from_slice(&body).unwrap()
+    }
+
+    fn notify(&mut self, method: &str, params: Value) {
+        self.send(json!({ "jsonrpc": "2.0", "method": method, "params": params }));
+    }
+
+    /// Sends a request and returns its result.
+    fn request(&mut self, id: u64, method: &str, params: Value) -> Value {
+        self.send(json!({ "jsonrpc": "2.0", "id": id, "method": method, "params": params }));
+        loop {
+            let message = self.receive();
+            if message["id"] == id {
+                return message["result"].clone();
+            }
+        }
+    }
+
+    /// Opens or changes the document and returns its diagnostics.
+    fn diagnostics(&mut self, method: &str, version: i32, text: &str) -> Vec<Value> {
+        let params = if method == "textDocument/didOpen" {
+            json!({ "textDocument": { "uri": URI, "languageId": "yaml", "version": version, "text": text } })
+        } else {
+            json!({ "textDocument": { "uri": URI, "version": version }, "contentChanges": [{ "text": text }] })
+        };
+        self.notify(method, params);
+        loop {
+            let message = self.receive();
+            if message["method"] == "textDocument/publishDiagnostics" {
+                return message["params"]["diagnostics"].as_array().unwrap().clone();
+            }
+        }
+    }
+
+    fn at(&mut self, id: u64, method: &str, line: u32, character: u32) -> Value {
+        let mut params = json!({
+            "textDocument": { "uri": URI },
+            "position": { "line": line, "character": character },
+        });
+        if method == "textDocument/references" {
+            params["context"] = json!({ "includeDeclaration": true });
+        }
+        self.request(id, method, params)
+    }
+
+    fn stop(mut self) {
+        self.request(99, "shutdown", Value::Null);
+        self.notify("exit", Value::Null);
+        assert!(self.child.wait().unwrap().success());
+    }
+}
+
+fn range(line: u32, start: u32, end: u32) -> Value {
+    json!({
+        "start": { "line": line, "character": start },
+        "end": { "line": line, "character": end },
+    })
+}
+
+#[test]
+fn test_lsp_diagnostics() {
+    let mut server = Server::start();
+
+    let diagnostics = server.diagnostics("textDocument/didOpen", 1, POND_DIALOG);
+    assert_eq!(diagnostics.len(), 1, "{diagnostics:?}");
+    assert_eq!(diagnostics[0]["range"], range(18, 20, 21));
+    assert_eq!(diagnostics[0]["severity"], 1);
+    assert_eq!(
+        diagnostics[0]["message"],
+        "content[1].exit_state: the exit_state `2` points to no node"
+    );
+
+    let malformed = POND_DIALOG.replace("has(FrogLove)", "has(FrogLove) &&");
+    let diagnostics = server.diagnostics("textDocument/didChange", 2, &malformed);
+    assert_eq!(diagnostics.len(), 1, "{diagnostics:?}");
+    assert_eq!(diagnostics[0]["range"]["start"]["line"], 15);
+    assert!(diagnostics[0]["message"]
+        .as_str()
+        .unwrap()
+        .starts_with("content.choices[0].condition: "));
+
+    server.stop();
+}
+
+#[test]
+fn test_lsp_navigation() {
+    let mut server = Server::start();
+    server.diagnostics("textDocument/didOpen", 1, POND_DIALOG);
+
+    // From the `exit_state: greet_player` of the node 1
+    let definition = server.at(1, "textDocument/definition", 8, 25);
+    assert_eq!(definition["uri"], URI);
+    assert_eq!(definition["range"], range(10, 2, 14));
+
+    let references = server.at(2, "textDocument/references", 3, 2);
+    let ranges: Vec<&Value> = references
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|location| &location["range"])
+        .collect();
+    assert_eq!(ranges, [&range(3, 2, 3), &range(16, 20, 21)]);
+
+    let hover = server.at(3, "textDocument/hover", 16, 20);
+    assert_eq!(
+        hover["contents"]["value"],
+        "**1**, node 1\n\nFrog: KeroKero"
+    );
+    let hover = server.at(4, "textDocument/hover", 8, 20);
+    assert_eq!(
+        hover["contents"]["value"],
+        "**greet_player**, node 6\n\nPlayer: Hi / Bye / Later"
+    );
+
+    server.stop();
+}
+
+#[test]
+fn test_lsp_completion() {
+    let mut server = Server::start();
+    server.diagnostics("textDocument/didOpen", 1, POND_DIALOG);
+    let typing = POND_DIALOG.replace("exit_state: 5", "exit_state: ");
+    server.diagnostics("textDocument/didChange", 2, &typing);
+
+    let labels = |completion: Value| -> Vec<String> {
+        completion
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|item| item["label"].as_str().unwrap().to_string())
+            .collect()
+    };
+    let nodes = server.at(1, "textDocument/completion", 25, 20);
+    assert_eq!(labels(nodes.clone()), ["1", "greet_player", "3"]);
+    assert_eq!(nodes[0]["detail"], "Frog: KeroKero");
+
+    // Within `has(..)` and the `trigger_event`
+    let events = server.at(2, "textDocument/completion", 15, 23);
+    assert_eq!(labels(events), ["FrogLove", "FrogTalk"]);
+    let events = server.at(3, "textDocument/completion", 9, 20);
+    assert_eq!(labels(events), ["FrogLove", "FrogTalk"]);
+
+    let nothing = server.at(4, "textDocument/completion", 14, 14);
+    assert_eq!(labels(nothing), Vec::<String>::new());
+
+    server.stop();
+}
@@ -0,0 +1,180 @@
+#![cfg(feature = "yaml")]
+
+use serde::Deserialize;
+use yml_dialog::*;
+
+const LEGACY_DIALOG: &str = include_str!("../examples/test_dialog.yml");
+
+#[test]
+fn test_migration_legacy_example() {
+    let (yaml, report) = migrate_yaml(LEGACY_DIALOG).unwrap();
+
+    assert_eq!(report.from_version(), 1);
+    assert_eq!(
+        report
+            .issues()
+            .iter()
+            .map(ToString::to_string)
+            .collect::<Vec<_>>(),
+        vec![
+            "node 5: no `state`, the monolog now ends the dialog with the exit_state `7`",
+            "node 6: no `state`, the monolog now ends the dialog with the exit_state `7`",
+        ]
+    );
+
+    let dialog: Dialog = Dialog::from_yaml_str(&yaml).unwrap();
+    assert_eq!(dialog.len(), 6);
+    assert_eq!(
+        dialog.get(&1).unwrap().content(),
+        &Content::Monolog {
+            text: vec!["Bonjour tu vas bien ?".to_string()],
+            exit_state: 2,
+        }
+    );
+    assert_eq!(
+        dialog.get(&4).unwrap().content(),
+        &Content::Choices(vec![
+            Choice::new(
+                "Va acheter du pain là".to_string(),
+                Some(Condition::new(
+                    None,
+                    vec![
+                        "ColapsingWorld".to_string(),
+                        "ThereIsNoMoreBreadHome".to_string()
+                    ]
+                )),
+                5
+            ),
+            Choice::new(
+                "Tu es faible".to_string(),
+                Some(Condition::new(Some((-10, 0)), vec![])),
+                6
+            ),
+        ])
+    );
+    assert_eq!(
+        dialog.get(&6).unwrap().trigger_event(),
+        &vec!["DealHP(-30)".to_string()]
+    );
+    assert_eq!(dialog.validate(), vec![]);
+
+    // Already migrated
+    let (again, report) = migrate_yaml(&yaml).unwrap();
+    assert_eq!(report.from_version(), DIALOG_VERSION);
+    assert!(report.issues().is_empty());
+    assert_eq!(again, yaml);
+}
+
+#[test]
+fn test_migration_reports_unconverted() {
+    let legacy = "1:
+  source: Frog
+  monolog:
+    - KeroKero
+  choices:
+    - text: Hi
+      state: 1
+  state: 2
+  mood: happy
+2:
+  source: Player
+  choices:
+    - text: Hi
+      karma_threshold: lots
+      state: 1
+    - text: Bye
+  state: 1\n";
+
+    let (yaml, report) = migrate_yaml(legacy).unwrap();
+
+    assert_eq!(
+        report
+            .issues()
+            .iter()
+            .map(ToString::to_string)
+            .collect::<Vec<_>>(),
+        vec![
+            "node 1, choices: the node has a monolog too, the choices are left out",
+            "node 1, mood: unknown field `mood`, left out",
+            "node 2, choices[0].karma_threshold: \
+             unreadable threshold `lots`, expected `(min, max)`, left out",
+            "node 2, choices[1]: no `state`, the choice now ends the dialog with the exit_state `3`",
+            "node 2, state: the `state` of a node with choices is left out",
+        ]
+    );
+    assert!(Dialog::<Condition>::from_yaml_str(&yaml).is_ok());
+}
+
+#[test]
+fn test_migration_versions() {
+    let current = "version: 2\n1:\n  source: Frog\n  content:\n    monolog:\n      text: [Hi]\n      exit_state: 2\n";
+    assert!(Dialog::<Condition>::from_yaml_str(current).is_ok());
+
+    let error = Dialog::<Condition>::from_yaml_str(&current.replace("version: 2", "version: 1"))
+        .unwrap_err();
+    assert!(error
+        .message()
+        .starts_with("the dialog is written in version 1, expected version 2"));
+
+    let error = migrate_yaml(&current.replace("version: 2", "version: 3")).unwrap_err();
+    assert_eq!(
+        error.to_string(),
+        "unknown version 3, expected at most version 2"
+    );
+}
+
+#[derive(Deserialize, Clone, PartialEq, Debug, Default)]
+struct Staging {
+    camera: Option<String>,
+}
+
+#[derive(Deserialize, Clone, PartialEq, Debug)]
+struct Jump {
+    height: u32,
+}
+
+#[test]
+fn test_migration_keeps_payloads() {
+    let current = "version: 2
+name: Pond
+nodes:
+  1:
+    source: Frog
+    content:
+      monolog:
+        text: [KeroKero]
+        exit_state: 2
+    effects:
+    - custom:
+        height: 2
+    extra:
+      camera: close_up\n";
+
+    let (yaml, report) = migrate_yaml(current).unwrap();
+    assert_eq!(report.from_version(), DIALOG_VERSION);
+    let dialog = Dialog::<Condition, Staging, Jump>::from_yaml_str(&yaml).unwrap();
+    assert_eq!(dialog.name(), &Some("Pond".to_string()));
+    let node = dialog.get(&1).unwrap();
+    assert_eq!(node.extra().camera.as_deref(), Some("close_up"));
+    assert_eq!(node.effects(), &vec![Effect::Custom(Jump { height: 2 })]);
+
+    // Legacy nodes under `nodes:`
+    let legacy = "name: Pond
+nodes:
+  1:
+    source: Frog
+    monolog:
+      - KeroKero
+    state: 2\n";
+    let (yaml, report) = migrate_yaml(legacy).unwrap();
+    assert_eq!(report.from_version(), 1);
+    let dialog = Dialog::<Condition>::from_yaml_str(&yaml).unwrap();
+    assert_eq!(dialog.name(), &Some("Pond".to_string()));
+    assert_eq!(
+        dialog.get(&1).unwrap().content(),
+        &Content::Monolog {
+            text: vec!["KeroKero".to_string()],
+            exit_state: 2
+        }
+    );
+}
@@ -0,0 +1,147 @@
+#![cfg(feature = "bevy")]
+
+use bevy::prelude::*;
+use yml_dialog::*;
+
+const FROG_DIALOG: &str = "1:
+  source: Frog
+  content:
+    text:
+    - KeroKero
+    exit_state: 2
+2:
+  source: Player
+  content:
+  - text: Hi
+    condition:
+      events:
+      - FrogLove
+    exit_state: 1
+  - text: Bye
+    exit_state: 3
+    effects:
+    - add_karma: -1
+  trigger_event:
+  - FrogTalk
+  effects:
+  - set_event: FrogTalk\n";
+
+/// The events sent during the last update.
+fn read<T: Event + Clone>(app: &App) -> Vec<T> {
+    app.world
+        .resource::<Events<T>>()
+        .iter_current_update_events()
+        .cloned()
+        .collect()
+}
+
+#[test]
+fn test_plugin_walks_through_dialog() {
+    let dialog: Dialog = serde_yaml::from_str(FROG_DIALOG).unwrap();
+    let mut app = App::new();
+    app.add_plugin(YmlDialogPlugin);
+    let frog = app.world.spawn(DialogState::new(dialog)).id();
+
+    app.update();
+    assert_eq!(
+        read::<NodeEnteredEvent>(&app),
+        vec![NodeEnteredEvent {
+            entity: frog,
+            key: 1,
+            trigger_event: vec![],
+        }]
+    );
+    // The entry node has no effect
+    assert_eq!(read::<EffectsFiredEvent>(&app), vec![]);
+
+    app.world.send_event(AdvanceDialogEvent { entity: frog });
+    app.update();
+    assert_eq!(
+        read::<EffectsFiredEvent>(&app),
+        vec![EffectsFiredEvent {
+            entity: frog,
+            effects: vec![Effect::SetEvent("FrogTalk".to_string())],
+        }]
+    );
+    assert_eq!(
+        read::<NodeEnteredEvent>(&app),
+        vec![NodeEnteredEvent {
+            entity: frog,
+            key: 2,
+            trigger_event: vec!["FrogTalk".to_string()],
+        }]
+    );
+
+    // `Hi` is not available without `FrogLove`: `Bye` is the first choice
+    app.world.send_event(ChooseDialogEvent {
+        entity: frog,
+        index: 0,
+    });
+    app.update();
+    assert_eq!(
+        read::<EffectsFiredEvent>(&app),
+        vec![EffectsFiredEvent {
+            entity: frog,
+            effects: vec![Effect::AddKarma(-1)],
+        }]
+    );
+    assert_eq!(
+        read::<DialogEndedEvent>(&app),
+        vec![DialogEndedEvent {
+            entity: frog,
+            exit_state: 3,
+        }]
+    );
+    assert!(app
+        .world
+        .get::<DialogState>(frog)
+        .unwrap()
+        .runner()
+        .is_ended());
+}
+
+#[test]
+fn test_plugin_uses_dialog_context() {
+    let dialog: Dialog = serde_yaml::from_str(FROG_DIALOG).unwrap();
+    let mut app = App::new();
+    app.add_plugin(YmlDialogPlugin);
+    app.insert_resource(DialogContext(ConditionContext::new(
+        None,
+        vec!["FrogLove".to_string()],
+    )));
+    let frog = app.world.spawn(DialogState::new(dialog)).id();
+    app.update();
+
+    app.world.send_event(AdvanceDialogEvent { entity: frog });
+    app.update();
+    app.world.send_event(ChooseDialogEvent {
+        entity: frog,
+        index: 0,
+    });
+    app.update();
+
+    assert_eq!(
+        app.world
+            .get::<DialogState>(frog)
+            .unwrap()
+            .runner()
+            .current(),
+        Some(1)
+    );
+    assert_eq!(read::<DialogEndedEvent>(&app), vec![]);
+
+    // A wrong request is ignored
+    app.world.send_event(ChooseDialogEvent {
+        entity: frog,
+        index: 0,
+    });
+    app.update();
+    assert_eq!(
+        app.world
+            .get::<DialogState>(frog)
+            .unwrap()
+            .runner()
+            .current(),
+        Some(1)
+    );
+}
@@ -0,0 +1,121 @@
+use yml_dialog::*;
+
+const BRIDGE_DIALOG: &str = "name: Bridge
+nodes:
+  1:
+    source: Troll
+    note: |-
+      The troll shouts.
+      Keep it short.
+    content:
+      text:
+      - Halt!
+      - \"Who goes \\\"there\\\"?\"
+      exit_state: answer
+    line_ids: [troll.halt]
+  answer:
+    source: Player
+    content:
+    - text: A friend
+      exit_state: 3
+    - text: Run away
+      exit_state: 3\n";
+
+fn dialog() -> Dialog {
+    serde_yaml::from_str(BRIDGE_DIALOG).unwrap()
+}
+
+#[test]
+fn test_po_round_trip() {
+    let dialog = dialog();
+    let mut french = StringTable::new("fr".to_string());
+    french.insert("troll.halt".to_string(), "Halte !".to_string());
+    french.insert("1.line1".to_string(), "Qui va \"là\" ?".to_string());
+    french.insert("answer.choice0".to_string(), "Un ami".to_string());
+
+    let po = dialog.to_po_string(&french);
+    assert!(po.starts_with(
+        "msgid \"\"\nmsgstr \"\"\n\"Project-Id-Version: Bridge\\n\"\n\"Language: fr\\n\"\n"
+    ));
+    assert!(po.contains(
+        "\n#. The troll shouts.\n#. Keep it short.\nmsgctxt \"1|Troll|line0\"\nmsgid \"Halt!\"\nmsgstr \"Halte !\"\n"
+    ));
+    assert!(po.contains(
+        "\nmsgctxt \"1|Troll|line1\"\nmsgid \"Who goes \\\"there\\\"?\"\nmsgstr \"Qui va \\\"là\\\" ?\"\n"
+    ));
+    assert!(po.ends_with("\nmsgctxt \"answer|Player|choice1\"\nmsgid \"Run away\"\nmsgstr \"\"\n"));
+
+    let (imported, report) = dialog.import_po(&po);
+    assert_eq!(imported, french);
+    let issues: Vec<String> = report.issues().iter().map(ToString::to_string).collect();
+    assert_eq!(issues, ["line 25: `answer.choice1` is not translated"]);
+
+    // A template
+    let template = dialog.to_po_string(&StringTable::default());
+    let (imported, report) = dialog.import_po(&template);
+    assert!(imported.is_empty());
+    assert_eq!(report.issues().len(), 4);
+}
+
+#[test]
+fn test_po_import_issues() {
+    let dialog = dialog();
+    let po = r#"# Translation of the bridge
+msgid ""
+msgstr ""
+"Language: de\n"
+
+#, fuzzy
+msgctxt "1|Troll|line0"
+msgid "Halt!"
+msgstr "Halt!"
+
+msgctxt "1|Troll|line1"
+msgid "Who goes "
+"\"there\"?"
+msgstr "Wer "
+"da?"
+
+msgctxt "2|Troll|line0"
+msgid "Gone"
+msgstr "Weg"
+
+msgctxt "answer|Someone else|choice0"
+msgid "A friend"
+msgstr "Ein Freund"
+
+msgctxt "answer|Player|choice1"
+msgid "Run"
+msgstr "Lauf"
+
+#~ msgid "Obsolete"
+#~ msgstr "Veraltet"
+msgid "Plural"
+msgid_plural "Plurals"
+msgstr[0] "Mehrzahl"
+msgstr[1] "Mehrzahlen"
+msgstr oops
+"#;
+
+    let (german, report) = dialog.import_po(po);
+    assert_eq!(german.locale(), "de");
+    assert_eq!(
+        german.iter().collect::<Vec<_>>(),
+        [
+            (&"1.line1".to_string(), &"Wer da?".to_string()),
+            // The speaker only helps the translators
+            (&"answer.choice0".to_string(), &"Ein Freund".to_string()),
+        ]
+    );
+    let issues: Vec<String> = report.issues().iter().map(ToString::to_string).collect();
+    assert_eq!(
+        issues,
+        [
+            "line 6: `troll.halt` needs a review, left out",
+            "line 17: `2|Troll|line0` is no string of the dialog, left out",
+            "line 25: `answer.choice1` was translated from `Run`, which changed since, left out",
+            "line 31: the dialogs have no plural forms, left out",
+            "line 35: expected a quoted string in `msgstr oops`",
+        ]
+    );
+}
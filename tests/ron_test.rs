@@ -0,0 +1,77 @@
+#![cfg(all(feature = "ron", feature = "yaml"))]
+
+use yml_dialog::*;
+
+const POND_DIALOG: &str = include_str!("assets/pond.dialog.yml");
+
+#[test]
+fn test_ron_round_trip() {
+    let dialog: Dialog = Dialog::from_yaml_str(POND_DIALOG).unwrap();
+
+    let ron = dialog.to_ron_string().unwrap();
+    assert!(ron.contains("name: \"Frog Pond\","), "{ron}");
+    assert!(ron.contains("\"monolog\": ("), "{ron}");
+    assert!(ron.contains("\"choices\": ["), "{ron}");
+    assert_eq!(Dialog::from_ron_str(&ron), Ok(dialog.clone()));
+    assert_eq!(Dialog::from_ron_reader(ron.as_bytes()), Ok(dialog));
+}
+
+#[test]
+fn test_ron_untagged_content() {
+    let tagged: Dialog = Dialog::from_ron_str(
+        r#"{
+    1: (content: { "monolog": (text: ["KeroKero"], exit_state: 2) }),
+    2: (content: { "choices": [(text: "Hi", condition: "has(FrogLove)", exit_state: 1)] }),
+}"#,
+    )
+    .unwrap();
+    let untagged: Dialog = Dialog::from_ron_str(
+        r#"{
+    1: (content: (text: ["KeroKero"], exit_state: 2)),
+    2: (content: [(text: "Hi", condition: Some("has(FrogLove)"), exit_state: 1)]),
+}"#,
+    )
+    .unwrap();
+    assert_eq!(untagged, tagged);
+
+    let error = Dialog::<Condition>::from_ron_str(
+        r#"{ 1: (content: { "monolog": (text: [], exit_state: 2), "text": [] }) }"#,
+    )
+    .unwrap_err();
+    assert_eq!(error.node(), Some("1"));
+    assert_eq!(error.path(), "content");
+    assert_eq!(
+        error.message(),
+        "`monolog` or `choices` must be the only field of the content"
+    );
+}
+
+#[test]
+fn test_ron_error_locations() {
+    let ron = r#"(
+    entry: 1,
+    nodes: {
+        1: (
+            source: "Frog",
+            content: { "choices": [
+                (text: "Hi", exit_state: 2),
+                (text: "Bye", exit_state: true),
+            ] },
+        ),
+    },
+)"#;
+
+    let error = Dialog::<Condition>::from_ron_str(ron).unwrap_err();
+    assert_eq!(error.node(), Some("1"));
+    assert_eq!(error.path(), "content.choices[1].exit_state");
+    assert_eq!(error.line(), Some(8));
+    assert_eq!(
+        error.message(),
+        "Expected a node key or label but found the boolean `true` instead"
+    );
+
+    let error = Dialog::<Condition>::from_ron_str(&ron.replace("\"Bye\",", "\"Bye\"")).unwrap_err();
+    assert_eq!(error.node(), None);
+    assert_eq!(error.line(), Some(8));
+    assert_eq!(error.message(), "Expected comma");
+}
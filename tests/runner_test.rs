@@ -0,0 +1,176 @@
+use yml_dialog::*;
+
+const WARRIOR_DIALOG: &str = "1:
+  source: Warrior Frog
+  content:
+    text:
+      - Hey
+      - Can you bring my love to my homegirl the Frog in the Middle ?
+    exit_state: 2
+2:
+  source: Player
+  content:
+    - text: Oh Jeez I messed up
+      condition:
+        events:
+            - FrogHate
+      exit_state: 3
+    - text: The Frog is in love
+      condition:
+        events:
+            - FrogLove
+      exit_state: 4
+    - text: Bye
+      exit_state: 5
+3:
+  source: Warrior Frog
+  content:
+    text:
+      - :0
+    exit_state: 5
+  trigger_event:
+    - FrogTalk
+4:
+  source: Warrior Frog
+  content:
+    text:
+      - <3
+    exit_state: 5
+  trigger_event:
+    - FrogTalk\n";
+
+fn runner() -> DialogRunner {
+    DialogRunner::new(serde_yaml::from_str(WARRIOR_DIALOG).unwrap())
+}
+
+#[test]
+fn test_runner_monolog_lines() {
+    let mut runner = runner();
+
+    assert_eq!(runner.current(), Some(1));
+    assert_eq!(runner.current_speaker(), Some("Warrior Frog"));
+    assert_eq!(runner.current_line(), Some(&"Hey".to_string()));
+    assert_eq!(runner.advance(), Ok(Step::Line));
+    assert_eq!(runner.line_index(), 1);
+    assert_eq!(
+        runner.advance(),
+        Ok(Step::Node {
+            key: 2,
+            trigger_event: vec![],
+            effects: vec![],
+        })
+    );
+    assert_eq!(runner.current_line(), None);
+    assert_eq!(runner.advance(), Err(RunnerError::ExpectedChoice));
+}
+
+#[test]
+fn test_runner_verified_choices() {
+    let mut runner = runner();
+    runner.advance().unwrap();
+    runner.advance().unwrap();
+
+    let context = ConditionContext::new(None, vec!["FrogLove".to_string()]);
+    let choices: Vec<&String> = runner
+        .available_choices(&context)
+        .into_iter()
+        .map(Choice::text)
+        .collect();
+    assert_eq!(choices, vec!["The Frog is in love", "Bye"]);
+
+    assert_eq!(
+        runner.choose(2, &context),
+        Err(RunnerError::ChoiceOutOfRange {
+            index: 2,
+            available: 2
+        })
+    );
+    assert_eq!(
+        runner.choose(0, &context),
+        Ok(Step::Node {
+            key: 4,
+            trigger_event: vec!["FrogTalk".to_string()],
+            effects: vec![],
+        })
+    );
+    assert_eq!(
+        runner.choose(0, &ConditionContext::default()),
+        Err(RunnerError::ExpectedAdvance)
+    );
+}
+
+#[test]
+fn test_runner_end_and_restart() {
+    let mut runner = runner();
+    runner.advance().unwrap();
+    runner.advance().unwrap();
+
+    assert_eq!(
+        runner.choose(0, &ConditionContext::default()),
+        Ok(Step::End {
+            exit_state: 5,
+            effects: vec![]
+        })
+    );
+    assert!(runner.is_ended());
+    assert_eq!(runner.exit_state(), Some(5));
+    assert_eq!(runner.advance(), Err(RunnerError::Ended));
+
+    assert_eq!(
+        runner.restart(),
+        Step::Node {
+            key: 1,
+            trigger_event: vec![],
+            effects: vec![],
+        }
+    );
+    assert!(!runner.is_ended());
+    assert_eq!(runner.exit_state(), None);
+}
+
+#[test]
+fn test_runner_empty_dialog() {
+    let runner: DialogRunner = DialogRunner::new(Dialog::default());
+
+    assert!(runner.is_ended());
+    assert_eq!(runner.current_node(), None);
+}
+
+#[test]
+fn test_runner_reload() {
+    let mut runner = runner();
+    runner.advance().unwrap();
+
+    // The second line of the node 1 is still there
+    let edited = WARRIOR_DIALOG.replace("- Hey", "- Hello");
+    assert_eq!(runner.reload(serde_yaml::from_str(&edited).unwrap()), None);
+    assert_eq!(runner.current(), Some(1));
+    assert_eq!(runner.line_index(), 1);
+    assert_eq!(
+        runner.current_line(),
+        Some(&"Can you bring my love to my homegirl the Frog in the Middle ?".to_string())
+    );
+
+    // Not anymore
+    let edited = WARRIOR_DIALOG.replace("      - Hey\n", "");
+    assert_eq!(
+        runner.reload(serde_yaml::from_str(&edited).unwrap()),
+        Some(Step::Node {
+            key: 1,
+            trigger_event: vec![],
+            effects: vec![],
+        })
+    );
+    assert_eq!(
+        runner.current_line(),
+        Some(&"Can you bring my love to my homegirl the Frog in the Middle ?".to_string())
+    );
+
+    runner.advance().unwrap();
+    runner.choose(0, &ConditionContext::default()).unwrap();
+    assert_eq!(
+        runner.reload(serde_yaml::from_str(WARRIOR_DIALOG).unwrap()),
+        None
+    );
+    assert!(runner.is_ended());
+}
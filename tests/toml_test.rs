@@ -0,0 +1,106 @@
+#![cfg(all(feature = "toml", feature = "yaml"))]
+
+use yml_dialog::*;
+
+const POND_DIALOG: &str = include_str!("assets/pond.dialog.yml");
+
+#[test]
+fn test_toml_round_trip() {
+    let dialog: Dialog = Dialog::from_yaml_str(POND_DIALOG).unwrap();
+
+    let toml = dialog.to_toml_string().unwrap();
+    assert!(toml.contains("\n[nodes.1]\n"), "{toml}");
+    assert!(toml.contains("\n[nodes.1.content.monolog]\n"), "{toml}");
+    assert!(
+        toml.contains("\n[[nodes.greet_player.content.choices]]\n"),
+        "{toml}"
+    );
+    assert_eq!(Dialog::from_toml_str(&toml), Ok(dialog.clone()));
+    assert_eq!(Dialog::from_toml_reader(toml.as_bytes()), Ok(dialog));
+}
+
+#[test]
+fn test_toml_numeric_keys() {
+    let dialog: Dialog = Dialog::from_toml_str(
+        r#"[1]
+content.monolog = { text = ["KeroKero"], exit_state = 2 }
+
+["2"]
+content.monolog = { text = ["Yo Homie"], exit_state = 3 }
+"#,
+    )
+    .unwrap();
+    assert_eq!(dialog.entry(), Some(1));
+    assert_eq!(
+        dialog.get(&2).unwrap().content(),
+        &Content::Monolog {
+            text: vec!["Yo Homie".to_string()],
+            exit_state: 3
+        }
+    );
+
+    let toml = dialog.to_toml_string().unwrap();
+    assert!(toml.starts_with("[1]\n"), "{toml}");
+    assert!(toml.contains("\n[2]\n"), "{toml}");
+    assert_eq!(Dialog::from_toml_str(&toml), Ok(dialog));
+}
+
+#[test]
+fn test_toml_untagged_content() {
+    let tagged: Dialog = Dialog::from_toml_str(
+        r#"[1]
+content.monolog = { text = ["KeroKero"], exit_state = 2 }
+
+[[2.content.choices]]
+text = "Hi"
+exit_state = 1
+"#,
+    )
+    .unwrap();
+    let untagged: Dialog = Dialog::from_toml_str(
+        r#"[1]
+content = { text = ["KeroKero"], exit_state = 2 }
+
+[[2.content]]
+text = "Hi"
+exit_state = 1
+"#,
+    )
+    .unwrap();
+    assert_eq!(untagged, tagged);
+
+    let error = Dialog::<Condition>::from_toml_str(
+        "[1]\ncontent = { monolog = { text = [], exit_state = 2 }, text = [] }\n",
+    )
+    .unwrap_err();
+    assert_eq!(error.node(), Some("1"));
+    assert_eq!(error.path(), "content");
+    assert_eq!(
+        error.message(),
+        "`monolog` or `choices` must be the only field of the content"
+    );
+}
+
+#[test]
+fn test_toml_error_locations() {
+    let toml = r#"entry = 1
+
+[nodes.1]
+source = "Frog"
+content.monolog = { text = ["KeroKero"], exit_state = true }
+"#;
+
+    let error = Dialog::<Condition>::from_toml_str(toml).unwrap_err();
+    assert_eq!(error.node(), Some("1"));
+    assert_eq!(error.path(), "content.monolog.exit_state");
+    assert_eq!(error.line(), Some(5));
+    assert_eq!(
+        error.message(),
+        "invalid type: boolean `true`, expected a node key or label"
+    );
+
+    let error = Dialog::<Condition>::from_toml_str(&toml.replace("\"Frog\"", "Frog")).unwrap_err();
+    assert_eq!(error.node(), None);
+    assert_eq!(error.line(), Some(4));
+    assert!(error.message().starts_with("invalid string"), "{error}");
+}
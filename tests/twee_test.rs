@@ -0,0 +1,167 @@
+use yml_dialog::*;
+
+const FROG_TWEE: &str = ":: StoryTitle
+Frog Pond
+
+:: StoryData
+{
+  \"ifid\": \"D674C58C-DEFA-4F70-B7A2-27742230C0FC\",
+  \"format\": \"Harlowe\",
+  \"start\": \"Pond\"
+}
+
+:: Intro
+Somewhere else.
+
+:: Pond [outdoor] {\"position\":\"600,400\",\"size\":\"100,100\"}
+KeroKero
+The frog looks at you. Will you [[say hi->Greet]]?
+[[Bye<-Leave]]
+[[Kiss the frog|Kiss]]
+
+:: Greet
+Hello there!
+[[Bye]]
+
+:: Kiss
+[[Again|Kiss]]
+[[Bye]]
+
+:: Bye
+";
+
+#[test]
+fn test_twee_import() {
+    let (dialog, report) = import_twee(FROG_TWEE);
+    let issues: Vec<String> = report.issues().iter().map(ToString::to_string).collect();
+    assert_eq!(issues, ["line 14: tags `outdoor` left out"]);
+    assert_eq!(dialog.name(), &Some("Frog Pond".to_string()));
+
+    // The keys follow the passages
+    let keys: Vec<(&String, usize)> = dialog
+        .labels()
+        .iter()
+        .map(|(key, label)| (label, *key))
+        .collect();
+    assert_eq!(
+        keys,
+        [
+            (&"Intro".to_string(), 1),
+            (&"Pond".to_string(), 2),
+            (&"Greet".to_string(), 4),
+            (&"Kiss".to_string(), 6)
+        ]
+    );
+    assert_eq!(dialog.len(), 6);
+    assert_eq!(dialog.entry(), Some(2));
+    assert_eq!(
+        dialog.get(&2).unwrap().content(),
+        &Content::Monolog {
+            text: vec![
+                "KeroKero".to_string(),
+                "The frog looks at you. Will you say hi?".to_string()
+            ],
+            exit_state: 3,
+        }
+    );
+    // `Bye` is empty: the dialog ends there
+    assert_eq!(
+        dialog.get(&3).unwrap().content(),
+        &Content::Choices(vec![
+            Choice::new("say hi".to_string(), None, 4),
+            Choice::new("Leave".to_string(), None, 7),
+            Choice::new("Kiss the frog".to_string(), None, 6),
+        ])
+    );
+    // A passage of links alone is its choices
+    assert_eq!(
+        dialog.get(&6).unwrap().content(),
+        &Content::Choices(vec![
+            Choice::new("Again".to_string(), None, 6),
+            Choice::new("Bye".to_string(), None, 7),
+        ])
+    );
+    assert_eq!(dialog.key_of("Bye"), None);
+
+    // And back, the same keys given again
+    let (imported, report) = import_twee(&dialog.to_twee_string());
+    assert!(report.issues().is_empty(), "{:?}", report.issues());
+    assert_eq!(imported, dialog);
+}
+
+#[test]
+fn test_twee_import_reports() {
+    let (dialog, report) = import_twee(
+        "Before any passage
+:: Start
+(set: $fear to 1)Hello
+<<if $fear>>Run!<</if>>
+[[Go->Nowhere]]
+:: Start
+Again
+:: UserScript [script]
+window.frog = true;
+",
+    );
+
+    let issues: Vec<String> = report.issues().iter().map(ToString::to_string).collect();
+    assert_eq!(
+        issues,
+        [
+            "line 1: `Before any passage` out of a passage, left out",
+            "line 3: macro kept as written: `(set: $fear to 1)Hello`",
+            "line 4: macro kept as written: `<<if $fear>>Run!<</if>>`",
+            "line 5: unknown link target `Nowhere`, the dialog ends there",
+            "line 6: passage `Start` defined again, left out",
+            "line 8: script passage `UserScript` left out",
+        ]
+    );
+    assert_eq!(
+        dialog.get(&2).unwrap().content(),
+        &Content::Choices(vec![Choice::new("Go".to_string(), None, 3)])
+    );
+}
+
+#[cfg(feature = "yaml")]
+#[test]
+fn test_twee_export() {
+    let dialog: Dialog = Dialog::from_yaml_str(include_str!("assets/pond.dialog.yml")).unwrap();
+    let twee = dialog.to_twee_string();
+    assert_eq!(
+        twee,
+        ":: StoryTitle
+Frog Pond
+
+:: StoryData
+{
+  \"start\": \"Start\"
+}
+
+:: Start
+KeroKero
+[[Continue->greet_player]]
+
+:: greet_player
+[[Hi->Start]]
+[[Bye->node_3]]
+
+:: node_3
+"
+    );
+
+    // The speakers, conditions and effects are left out
+    let (imported, report) = import_twee(&twee);
+    assert!(report.issues().is_empty(), "{:?}", report.issues());
+    assert_eq!(imported.name(), dialog.name());
+    assert_eq!(imported.len(), 3);
+    let greet = imported
+        .get(&imported.key_of("greet_player").unwrap())
+        .unwrap();
+    assert_eq!(
+        greet.content(),
+        &Content::Choices(vec![
+            Choice::new("Hi".to_string(), None, 1),
+            Choice::new("Bye".to_string(), None, 4),
+        ])
+    );
+}
@@ -0,0 +1,129 @@
+use yml_dialog::*;
+
+const FROG_DIALOG: &str = "1:
+  source: Frog
+  content:
+    - text: KeroKero
+      condition: null
+      exit_state: 2
+    - text: Crôaa
+      condition: null
+      exit_state: 3
+2:
+  source: Frog
+  content:
+    text:
+      - KeroKero
+    exit_state: 5
+3:
+  source: Frog
+  content:
+    text:
+      - Crôaa
+    exit_state: 5
+5:
+  source: Player
+  content:
+  - text: You = Cool
+    condition: null
+    exit_state: 7
+  - text: You = Not Cool
+    condition: null
+    exit_state: 8
+7:
+  source: Frog
+  content:
+    text:
+      - Big love on you <3
+    exit_state: 9
+  trigger_event:
+    - FrogLove
+8:
+  source: Frog
+  content:
+    text:
+      - I'm sad now.
+    exit_state: 9
+  trigger_event:
+    - FrogHate\n";
+
+#[test]
+fn test_validate_valid_dialog() {
+    let dialog: Dialog = serde_yaml::from_str(FROG_DIALOG).unwrap();
+
+    assert_eq!(dialog.validate(), vec![]);
+}
+
+#[test]
+fn test_validate_dangling_exit() {
+    let mut dialog: Dialog = serde_yaml::from_str(FROG_DIALOG).unwrap();
+    if let Content::Choices(choices) = dialog.get_mut(&5).unwrap().content_mut() {
+        *choices[1].exit_state_mut() = 6;
+    }
+
+    let diagnostics = dialog.validate();
+
+    assert_eq!(
+        diagnostics,
+        vec![
+            Diagnostic::new(
+                Some(5),
+                "content[1].exit_state".to_string(),
+                DiagnosticKind::DanglingExit { exit_state: 6 }
+            ),
+            Diagnostic::new(Some(8), String::new(), DiagnosticKind::UnreachableNode),
+        ]
+    );
+    assert!(diagnostics[0].is_error());
+    assert_eq!(diagnostics[1].severity(), Severity::Warning);
+}
+
+#[test]
+fn test_validate_loop_without_end() {
+    let yaml = "1:
+  source: Frog
+  content:
+    text: []
+    exit_state: 2
+2:
+  source: Player
+  content:
+  - text: Again
+    exit_state: 1
+  - text: Again
+    exit_state: 1
+  - text: ''
+    exit_state: 2\n";
+    let dialog: Dialog = serde_yaml::from_str(yaml).unwrap();
+
+    let kinds: Vec<(Option<usize>, DiagnosticKind)> = dialog
+        .validate()
+        .into_iter()
+        .map(|diagnostic| (diagnostic.node(), diagnostic.kind().clone()))
+        .collect();
+
+    assert_eq!(
+        kinds,
+        vec![
+            (Some(1), DiagnosticKind::EmptyText),
+            (Some(1), DiagnosticKind::NoReachableEnd),
+            (Some(2), DiagnosticKind::DuplicateChoice { first: 0 }),
+            (Some(2), DiagnosticKind::EmptyChoiceText),
+            (Some(2), DiagnosticKind::NoReachableEnd),
+        ]
+    );
+}
+
+#[test]
+fn test_validate_missing_entry() {
+    let dialog: Dialog = Dialog::new(serde_yaml::from_str(FROG_DIALOG).unwrap()).with_entry(4);
+
+    let diagnostics = dialog.validate();
+
+    assert_eq!(
+        diagnostics[0].to_string(),
+        "error: entry: the entry `4` is not a node of the dialog"
+    );
+    // Nothing is reachable from a missing entry
+    assert_eq!(diagnostics.len(), 1 + dialog.len());
+}
@@ -0,0 +1,133 @@
+use yml_dialog::*;
+
+#[test]
+fn test_variables_yaml_types() {
+    let variables: Variables = serde_yaml::from_str(
+        "trust: 3
+fear: 0.5
+met_the_frog: true
+last_place: Frog Pond\n",
+    )
+    .unwrap();
+
+    assert_eq!(variables.get("trust"), Some(&Value::Int(3)));
+    assert_eq!(variables.get("fear"), Some(&Value::Float(0.5)));
+    assert_eq!(variables.get("met_the_frog"), Some(&Value::Bool(true)));
+    assert_eq!(
+        variables.get("last_place").and_then(Value::as_str),
+        Some("Frog Pond")
+    );
+
+    let error = serde_yaml::from_str::<Variables>("gold: [1, 2]\n")
+        .unwrap_err()
+        .to_string();
+    assert!(
+        error.contains("expected an integer, a float, a boolean or a string"),
+        "{error}"
+    );
+}
+
+#[test]
+fn test_variables_in_expressions() {
+    let context = ConditionContext::new(Some(4), vec![]).with_variables(Variables::from_iter([
+        ("fear", Value::Float(0.5)),
+        ("met_the_frog", Value::Bool(true)),
+        ("last_place", Value::from("Frog Pond")),
+        ("gold", Value::Int(12)),
+    ]));
+
+    let cases = [
+        ("fear < 1", true),
+        ("fear >= 0.75", false),
+        ("gold > 11.5", true),
+        ("karma == 4", true),
+        ("met_the_frog == true", true),
+        ("last_place == \"Frog Pond\"", true),
+        ("last_place != \"Frog Pond\"", false),
+        // values of different types are only different
+        ("met_the_frog == 1", false),
+        ("met_the_frog != 1", true),
+        ("gold > true", false),
+        ("trust >= 0", false),
+    ];
+
+    for (input, expected) in cases {
+        let expression: Expression = input.parse().unwrap();
+        assert_eq!(expression.evaluate(&context), expected, "{input}");
+        assert_eq!(expression.to_string(), input);
+    }
+}
+
+#[test]
+fn test_variables_thresholds() {
+    let condition: Condition = serde_yaml::from_str(
+        "thresholds:
+  trust: [2, 10]
+  fear: [0.0, 0.5]\n",
+    )
+    .unwrap();
+    let mut context = ConditionContext::default()
+        .with_variables(Variables::from_iter([("trust", 2.5), ("fear", 0.5)]));
+
+    assert!(condition.is_verified_within(&context));
+    context.variables_mut().set("trust", 11);
+    assert!(!condition.is_verified_within(&context));
+    context.variables_mut().remove("trust");
+    assert!(!condition.is_verified_within(&context));
+    // Without any variable
+    assert!(!condition.is_verified(None, vec![]));
+}
+
+#[test]
+fn test_variables_save_game() {
+    let mut context = ConditionContext::new(Some(1), vec!["FrogTalk".to_string()]);
+    let effects: Vec<Effect> = vec![
+        Effect::AddKarma(-3),
+        Effect::SetVar {
+            name: "gold".to_string(),
+            value: Value::Int(30),
+        },
+        Effect::SetVar {
+            name: "last_place".to_string(),
+            value: Value::from("Frog Pond"),
+        },
+        Effect::ClearEvent("FrogTalk".to_string()),
+    ];
+    for effect in &effects {
+        assert_eq!(context.apply(effect), None);
+    }
+
+    let save = serde_yaml::to_string(&context).unwrap();
+    assert_eq!(
+        save,
+        "karma: -2
+active_events: []
+variables:
+  gold: 30
+  last_place: Frog Pond\n"
+    );
+    assert_eq!(
+        serde_yaml::from_str::<ConditionContext>(&save).unwrap(),
+        context
+    );
+}
+
+#[test]
+fn test_variables_float_exponents_round_trip() {
+    let condition: Condition = serde_yaml::from_str("fear < 0.00001 && gold > 1e21\n").unwrap();
+    let context = ConditionContext::default().with_variables(Variables::from_iter([
+        ("fear", Value::Float(0.000001)),
+        ("gold", Value::Float(2e21)),
+    ]));
+    assert!(condition.is_verified_within(&context));
+
+    // The floats are written with an exponent, and read back
+    let saved = serde_yaml::to_string(&condition).unwrap();
+    assert!(saved.contains("fear < 1e-5"), "{saved}");
+    let loaded: Condition = serde_yaml::from_str(&saved).unwrap();
+    assert_eq!(loaded, condition);
+    assert_eq!(
+        "x == 2.5E+3".parse::<Expression>().unwrap().to_string(),
+        "x == 2500.0"
+    );
+}
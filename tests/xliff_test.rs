@@ -0,0 +1,144 @@
+#![cfg(feature = "xliff")]
+
+use yml_dialog::*;
+
+const BRIDGE_DIALOG: &str = "name: Bridge & Troll
+nodes:
+  1:
+    source: Troll
+    note: The troll shouts
+    content:
+      text:
+      - Halt!
+      - Who goes <there>?
+      exit_state: answer
+    line_ids: [troll.halt]
+  answer:
+    source: Player
+    content:
+    - text: A friend
+      exit_state: 3
+    - text: Run away
+      exit_state: 3\n";
+
+fn dialog() -> Dialog {
+    serde_yaml::from_str(BRIDGE_DIALOG).unwrap()
+}
+
+fn french() -> StringTable {
+    let mut french = StringTable::new("fr".to_string());
+    french.insert("troll.halt".to_string(), "Halte !".to_string());
+    french.insert("1.line1".to_string(), "Qui va <là> ?".to_string());
+    french.insert("answer.choice0".to_string(), "Un ami".to_string());
+    french
+}
+
+#[test]
+fn test_xliff_round_trip() {
+    let dialog = dialog();
+    let french = french();
+
+    for version in [XliffVersion::V1_2, XliffVersion::V2_0] {
+        let xliff = dialog.to_xliff_string(version, "en", &french);
+        let (imported, report) = dialog.import_xliff(&xliff);
+        assert_eq!(imported, french, "{version}");
+        let issues: Vec<&TranslationIssueKind> =
+            report.issues().iter().map(TranslationIssue::kind).collect();
+        assert_eq!(
+            issues,
+            [&TranslationIssueKind::Missing {
+                id: "answer.choice1".to_string()
+            }],
+            "{version}"
+        );
+    }
+
+    let xliff = dialog.to_xliff_string(XliffVersion::V1_2, "en", &french);
+    assert!(xliff.contains(
+        "  <file original=\"Bridge &amp; Troll\" datatype=\"plaintext\" \
+         source-language=\"en\" target-language=\"fr\">\n    <body>\n"
+    ));
+    assert!(xliff.contains(
+        "      <trans-unit id=\"troll.halt\" resname=\"1|Troll|line0\">
+        <source>Halt!</source>
+        <target state=\"translated\">Halte !</target>
+        <note>The troll shouts</note>
+      </trans-unit>\n"
+    ));
+    assert!(xliff.contains("<source>Who goes &lt;there&gt;?</source>"));
+    assert!(xliff.contains(
+        "      <trans-unit id=\"answer.choice1\" resname=\"answer|Player|choice1\">
+        <source>Run away</source>
+      </trans-unit>\n"
+    ));
+
+    let xliff = dialog.to_xliff_string(XliffVersion::V2_0, "en", &french);
+    assert!(xliff.contains(
+        "    <unit id=\"troll.halt\" name=\"1|Troll|line0\">
+      <notes>
+        <note>The troll shouts</note>
+      </notes>
+      <segment state=\"translated\">\n"
+    ));
+    assert!(xliff.contains(
+        "      <segment state=\"initial\">
+        <source>Run away</source>
+      </segment>\n"
+    ));
+}
+
+#[test]
+fn test_xliff_import_issues() {
+    let dialog = dialog();
+    let xliff = r#"<?xml version="1.0" encoding="UTF-8"?>
+<xliff xmlns="urn:oasis:names:tc:xliff:document:2.0" version="2.0" srcLang="en" trgLang="de">
+  <file id="bridge">
+    <unit id="troll.halt" name="1|Troll|line0">
+      <segment state="initial">
+        <source>Halt!</source>
+        <target>Halt!</target>
+      </segment>
+    </unit>
+    <unit id="1.line1" name="1|Troll|line1">
+      <segment state="translated">
+        <source>Who goes <ph id="1"/>&lt;there&gt;?</source>
+        <target>Wer <pc id="2">da</pc>?</target>
+      </segment>
+    </unit>
+    <unit id="gone" name="2|Troll|line0">
+      <segment><source>Gone</source><target>Weg</target></segment>
+    </unit>
+    <unit id="answer.choice1" name="answer|Player|choice1">
+      <segment><source>Run</source><target>Lauf</target></segment>
+    </unit>
+  </file>
+</xliff>"#;
+
+    let (german, report) = dialog.import_xliff(xliff);
+    assert_eq!(german.locale(), "de");
+    assert_eq!(
+        german.iter().collect::<Vec<_>>(),
+        [(&"1.line1".to_string(), &"Wer da?".to_string())]
+    );
+    let issues: Vec<String> = report.issues().iter().map(ToString::to_string).collect();
+    assert_eq!(
+        issues,
+        [
+            "line 4: `troll.halt` needs a review, left out",
+            "line 16: `2|Troll|line0` is no string of the dialog, left out",
+            "line 19: `answer.choice1` was translated from `Run`, which changed since, left out",
+            "`answer.choice0` is not translated",
+        ]
+    );
+
+    let (_, report) = dialog.import_xliff("<xliff version=\"1.2\"><file>");
+    assert!(matches!(
+        report.issues()[0].kind(),
+        TranslationIssueKind::Malformed { .. }
+    ));
+    let (_, report) = dialog.import_xliff("<xliff version=\"1.0\"/>");
+    assert_eq!(
+        report.issues()[0].to_string(),
+        "line 1: unsupported XLIFF version `1.0`"
+    );
+}
@@ -1,3 +1,4 @@
+use serde::{Deserialize, Serialize};
 #[cfg(test)]
 use std::collections::BTreeMap;
 use std::{fmt, str::FromStr};
@@ -6,7 +7,7 @@ use yml_dialog::*;
 // const KARMA_MAX: i32 = 100;
 // const KARMA_MIN: i32 = -KARMA_MAX;
 
-#[derive(Copy, Clone, Eq, PartialEq, Ord, PartialOrd, Hash, Debug)]
+#[derive(Serialize, Deserialize, Copy, Clone, Eq, PartialEq, Ord, PartialOrd, Hash, Debug)]
 enum WorldEvent {
     BeatTheGame,
     FirstKill,
@@ -47,7 +48,7 @@ impl FromStr for WorldEvent {
 
 #[test]
 fn test_yaml_monolog_serialize_1() {
-    let mut map = BTreeMap::new();
+    let mut map: BTreeMap<usize, DialogNode> = BTreeMap::new();
     map.insert(
         1,
         DialogNode::new(
@@ -68,9 +69,10 @@ fn test_yaml_monolog_serialize_1() {
         "1:
   source: Le Pape
   content:
-    text:
-    - Hello Homie
-    exit_state: 2
+    monolog:
+      text:
+      - Hello Homie
+      exit_state: 2
   trigger_event: []\n"
             .to_string()
     )
@@ -78,7 +80,7 @@ fn test_yaml_monolog_serialize_1() {
 
 #[test]
 fn test_yaml_monolog_serialize_2() {
-    let mut map = BTreeMap::new();
+    let mut map: BTreeMap<usize, DialogNode> = BTreeMap::new();
     map.insert(
         1,
         DialogNode::new(
@@ -114,18 +116,20 @@ fn test_yaml_monolog_serialize_2() {
         "1:
   source: The Frog
   content:
-    text:
-    - Hello Homie
-    - I mean...
-    - KeroKero
-    exit_state: 2
+    monolog:
+      text:
+      - Hello Homie
+      - I mean...
+      - KeroKero
+      exit_state: 2
   trigger_event: []
 2:
   source: Random Frog
   content:
-    text:
-    - KeroKero
-    exit_state: 3
+    monolog:
+      text:
+      - KeroKero
+      exit_state: 3
   trigger_event: []\n"
             .to_string()
     )
@@ -133,7 +137,7 @@ fn test_yaml_monolog_serialize_2() {
 
 #[test]
 fn test_yaml_choices_serialize_1() {
-    let mut map = BTreeMap::new();
+    let mut map: BTreeMap<usize, DialogNode> = BTreeMap::new();
     map.insert(
         1,
         DialogNode::new(
@@ -176,26 +180,29 @@ fn test_yaml_choices_serialize_1() {
         "1:
   source: The Frog
   content:
-  - text: Hello HomeGirl
-    condition: null
-    exit_state: 2
-  - text: KeroKero
-    condition: null
-    exit_state: 3
+    choices:
+    - text: Hello HomeGirl
+      condition: null
+      exit_state: 2
+    - text: KeroKero
+      condition: null
+      exit_state: 3
   trigger_event: []
 2:
   source: Random Frog
   content:
-    text:
-    - Yo Homie
-    exit_state: 4
+    monolog:
+      text:
+      - Yo Homie
+      exit_state: 4
   trigger_event: []
 3:
   source: Random Frog
   content:
-    text:
-    - KeroKero
-    exit_state: 4
+    monolog:
+      text:
+      - KeroKero
+      exit_state: 4
   trigger_event: []\n"
             .to_string()
     )
@@ -203,7 +210,7 @@ fn test_yaml_choices_serialize_1() {
 
 #[test]
 fn test_yaml_choices_condition_serialize() {
-    let mut map = BTreeMap::new();
+    let mut map: BTreeMap<usize, DialogNode> = BTreeMap::new();
     map.insert(
         1,
         DialogNode::new(
@@ -253,29 +260,32 @@ fn test_yaml_choices_condition_serialize() {
         "1:
   source: The Frog
   content:
-  - text: Hello HomeGirl
-    condition:
-      events:
-      - HasFriend
-      karma_threshold: null
-    exit_state: 2
-  - text: KeroKero
-    condition: null
-    exit_state: 3
+    choices:
+    - text: Hello HomeGirl
+      condition:
+        events:
+        - HasFriend
+        karma_threshold: null
+      exit_state: 2
+    - text: KeroKero
+      condition: null
+      exit_state: 3
   trigger_event: []
 2:
   source: Random Frog
   content:
-    text:
-    - Yo Homie
-    exit_state: 4
+    monolog:
+      text:
+      - Yo Homie
+      exit_state: 4
   trigger_event: []
 3:
   source: Random Frog
   content:
-    text:
-    - KeroKero
-    exit_state: 4
+    monolog:
+      text:
+      - KeroKero
+      exit_state: 4
   trigger_event: []\n"
             .to_string()
     )
@@ -290,7 +300,7 @@ fn test_yaml_monolog_deserialize_field_missing() {
     - Hello Homie
     exit_state: 2\n";
 
-    let mut map = BTreeMap::new();
+    let mut map: BTreeMap<usize, DialogNode> = BTreeMap::new();
     map.insert(
         1,
         DialogNode::new(
@@ -308,20 +318,118 @@ fn test_yaml_monolog_deserialize_field_missing() {
     assert_eq!(map, deserialized_map)
 }
 
-/*
-#[derive(Serialize, Deserialize, Default)]
-struct Condition(Vec<WorldEvent>);
+#[test]
+fn test_yaml_tagged_deserialize() {
+    let yaml = "1:
+  source: The Frog
+  content:
+    choices:
+    - text: Hello HomeGirl
+      condition:
+        events:
+        - HasFriend
+      exit_state: 2
+2:
+  source: Random Frog
+  content:
+    monolog:
+      text:
+      - Yo Homie
+      exit_state: 4\n";
+    let untagged_yaml = "1:
+  source: The Frog
+  content:
+  - text: Hello HomeGirl
+    condition:
+      events:
+      - HasFriend
+    exit_state: 2
+2:
+  source: Random Frog
+  content:
+    text:
+    - Yo Homie
+    exit_state: 4\n";
 
-#[derive(Serialize, Deserialize, Default)]
-#[serde(untagged)]
-enum WorldEvent {
-    #[default]
-    HasCharisma,
-    HasFriends,
+    let deserialized_map: BTreeMap<usize, DialogNode> = serde_yaml::from_str(&yaml).unwrap();
+    let untagged_map: BTreeMap<usize, DialogNode> = serde_yaml::from_str(untagged_yaml).unwrap();
+
+    assert_eq!(deserialized_map, untagged_map);
+    assert_eq!(
+        deserialized_map[&1].content(),
+        &Content::Choices(vec![Choice::new(
+            String::from("Hello HomeGirl"),
+            Some(Condition::new(
+                None,
+                vec![WorldEvent::HasFriend.to_string()]
+            )),
+            2
+        )])
+    );
 }
 
 #[test]
-fn test_generic_type_deserialize() {
+fn test_yaml_content_deserialize_errors() {
+    // (yaml, failing variant, reason)
+    let errors = [
+        (
+            "monolog:\n  text: [Yo Homie]\n",
+            "invalid monolog: ",
+            "missing field `exit_state`",
+        ),
+        (
+            "monolog:\n  text: [Yo Homie]\n  exit_sate: 2\n",
+            "invalid monolog: ",
+            "unknown field `exit_sate`, expected `text` or `exit_state`",
+        ),
+        (
+            "choices:\n  text: Hello\n",
+            "invalid choices: ",
+            "invalid type: map, expected a sequence",
+        ),
+        (
+            "text: [Yo Homie]\nexit_state: true\n",
+            "invalid monolog: ",
+            "invalid type: boolean `true`, expected a node key or label",
+        ),
+        (
+            "txt: [Yo Homie]\nexit_state: 2\n",
+            "",
+            "unknown field `txt`, expected one of `monolog`, `choices`, `text`, `exit_state`",
+        ),
+        (
+            "monolog:\n  text: []\n  exit_state: 2\nchoices: []\n",
+            "",
+            "`monolog` or `choices` must be the only field of the content",
+        ),
+        ("{}", "", "expected `monolog:` or `choices:` in the content"),
+    ];
+
+    for (yaml, variant, reason) in errors {
+        let error = serde_yaml::from_str::<Content>(yaml)
+            .unwrap_err()
+            .to_string();
+        assert!(
+            error.starts_with(variant) && error.contains(reason),
+            "`{error}` should start with `{variant}` and contain `{reason}`"
+        );
+    }
+}
+
+/// A game-specific condition: all these events are active.
+#[derive(Serialize, Deserialize, Clone, PartialEq, Debug)]
+struct EventsCondition(Vec<WorldEvent>);
+
+impl DialogCondition for EventsCondition {
+    type Context = Vec<WorldEvent>;
+
+    fn is_verified(&self, active_events: &Vec<WorldEvent>) -> bool {
+        self.0.iter().all(|event| active_events.contains(event))
+    }
+}
+
+#[test]
+fn test_generic_condition_deserialize() {
     let yaml = "1:
   source: La Pape
   content:
@@ -332,7 +440,7 @@ fn test_generic_type_deserialize() {
   source: The Frog
   content:
   - text: Hello HomeGirl
-    condition: !Condition
+    condition:
     - HasCharisma
     exit_state: 3
   - text: KeroKero
@@ -340,11 +448,11 @@ fn test_generic_type_deserialize() {
     exit_state: 1
   trigger_event: []\n";
 
-    let mut map = BTreeMap::new();
+    let mut map: BTreeMap<usize, DialogNode<EventsCondition>> = BTreeMap::new();
     map.insert(
         1,
         DialogNode::new(
-            "Le Pape".to_string(),
+            "La Pape".to_string(),
             Content::Monolog {
                 text: vec![String::from("Hello Homie")],
                 exit_state: 2,
@@ -359,7 +467,7 @@ fn test_generic_type_deserialize() {
             Content::Choices(vec![
                 Choice::new(
                     "Hello HomeGirl".to_string(),
-                    Some(Condition(vec![WorldEvent::HasCharisma])),
+                    Some(EventsCondition(vec![WorldEvent::HasCharisma])),
                     3,
                 ),
                 Choice::new("KeroKero".to_string(), None, 1),
@@ -368,11 +476,69 @@ fn test_generic_type_deserialize() {
         ),
     );
 
-    let deserialized_map: BTreeMap<usize, DialogNode> = serde_yaml::from_str(&yaml).unwrap();
+    let deserialized_map = serde_yaml::from_str(yaml).unwrap();
 
-    assert_eq!(map, deserialized_map)
+    assert_eq!(map, deserialized_map);
+
+    if let Content::Choices(choices) = deserialized_map[&2].content() {
+        assert!(!choices[0].is_verified(&vec![WorldEvent::HasFriend]));
+        assert!(choices[0].is_verified(&vec![WorldEvent::HasCharisma]));
+        assert!(choices[1].is_verified(&vec![]));
+    }
+}
+
+/// Game-specific data attached to a node.
+#[derive(Serialize, Deserialize, Clone, PartialEq, Debug, Default)]
+#[serde(default)]
+struct Staging {
+    camera: Option<String>,
+    emotion: Option<String>,
+    deal_hp: i32,
 }
 
+#[test]
+fn test_yaml_extra_round_trip() {
+    let yaml = "5:
+  source: NARATOR
+  content:
+    monolog:
+      text:
+      - ta mere c un npc
+      exit_state: 6
+  trigger_event: []
+6:
+  source: NARATOR
+  content:
+    monolog:
+      text:
+      - Mérité
+      exit_state: 7
+  trigger_event: []
+  extra:
+    camera: close_up
+    emotion: null
+    deal_hp: -30\n";
+
+    let dialog: Dialog<Condition, Staging> = serde_yaml::from_str(yaml).unwrap();
+
+    assert_eq!(dialog.get(&5).unwrap().extra(), &Staging::default());
+    assert_eq!(
+        dialog.get(&6).unwrap().extra(),
+        &Staging {
+            camera: Some("close_up".to_string()),
+            emotion: None,
+            deal_hp: -30,
+        }
+    );
+
+    let serialized = serde_yaml::to_string(&dialog).unwrap();
+    assert_eq!(
+        serde_yaml::from_str::<Dialog<Condition, Staging>>(&serialized).unwrap(),
+        dialog
+    );
+}
+
+/*
 #[test]
 fn test_custom_serialize() {
     let mut map = BTreeMap::new();
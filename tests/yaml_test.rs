@@ -0,0 +1,125 @@
+#![cfg(feature = "yaml")]
+
+use yml_dialog::*;
+
+const FROG_DIALOG: &str = "name: Frog Pond
+entry: 1
+nodes:
+  1:
+    source: Frog
+    content:
+      monolog:
+        text:
+        - KeroKero
+        exit_state: greet_player
+  greet_player:
+    source: Player
+    content:
+      choices:
+      - text: Hi
+        condition: has(FrogLove)
+        exit_state: 1
+      - text: Bye
+        exit_state: 3
+        effects:
+        - add_karma: -1
+    trigger_event:
+    - FrogTalk\n";
+
+#[test]
+fn test_yaml_read_and_write() {
+    let dialog: Dialog = Dialog::from_yaml_str(FROG_DIALOG).unwrap();
+    assert_eq!(
+        Dialog::<Condition>::from_yaml_reader(FROG_DIALOG.as_bytes()).unwrap(),
+        dialog
+    );
+
+    let yaml = dialog.to_yaml_string().unwrap();
+    assert_eq!(yaml, serde_yaml::to_string(&dialog).unwrap());
+    assert_eq!(Dialog::from_yaml_str(&yaml), Ok(dialog));
+}
+
+#[test]
+fn test_yaml_error_locations() {
+    // (faulty dialog, node, path, line, message)
+    let cases = [
+        (
+            FROG_DIALOG.replace("exit_state: 3", "exit_state: [3]"),
+            Some("greet_player"),
+            "content.choices[1].exit_state",
+            Some(19),
+            "invalid type: sequence, expected a node key or label",
+        ),
+        (
+            FROG_DIALOG.replace("add_karma: -1", "add_karma: lots"),
+            Some("greet_player"),
+            "content.choices[1].effects[0].add_karma",
+            Some(21),
+            "invalid type: string \"lots\", expected i32",
+        ),
+        (
+            FROG_DIALOG.replace("has(FrogLove)", "has(FrogLove"),
+            Some("greet_player"),
+            "content.choices[0].condition",
+            Some(16),
+            "invalid expression `has(FrogLove`: expected `)`, found the end at column 13",
+        ),
+        (
+            FROG_DIALOG.replace("trigger_event:\n    - FrogTalk", "trigger_event: FrogTalk"),
+            Some("greet_player"),
+            "trigger_event",
+            Some(22),
+            "invalid type: string \"FrogTalk\", expected a sequence",
+        ),
+        (
+            FROG_DIALOG.replace("entry: 1", "entry: [1]"),
+            None,
+            "entry",
+            Some(2),
+            "invalid type: sequence, expected a node key or label",
+        ),
+        (
+            FROG_DIALOG.replace("exit_state: 1", "exit_state: intro"),
+            None,
+            "",
+            None,
+            "unknown label `intro` in the node `greet_player`",
+        ),
+        (
+            FROG_DIALOG.replace("  greet_player:", "greet_player"),
+            None,
+            "",
+            Some(12),
+            "could not find expected ':', while scanning a simple key at line 11 column 1",
+        ),
+    ];
+
+    for (yaml, node, path, line, message) in cases {
+        let error = Dialog::<Condition>::from_yaml_str(&yaml).unwrap_err();
+        assert_eq!(error.node(), node, "{error}");
+        assert_eq!(error.path(), path, "{error}");
+        assert_eq!(error.line(), line, "{error}");
+        assert!(error.message().starts_with(message), "{error}");
+    }
+}
+
+#[test]
+fn test_yaml_error_in_bare_map() {
+    let error = Dialog::<Condition>::from_yaml_str(
+        "1:
+  source: Frog
+  content:
+  - text: Hi
+    exit_state: 2
+  - text: Bye
+    exit_state: true\n",
+    )
+    .unwrap_err();
+
+    assert_eq!(
+        error.to_string(),
+        "node 1, content[1].exit_state: \
+         invalid type: boolean `true`, expected a node key or label at line 7 column 17"
+    );
+    assert_eq!(error.column(), Some(17));
+}
@@ -0,0 +1,315 @@
+use yml_dialog::*;
+
+const FROG_YARN: &str = "title: Start
+position: 0,0
+---
+// The frog of the pond
+<<set $mood to \"grumpy\">>
+Frog: KeroKero
+Frog: Who goes there?
+Player: Just me.
+-> Hi
+    Frog: Hi yourself.
+    -> Kiss the frog <<if $FrogLove and $karma >= 2>>
+        <<set $karma to $karma + 2>>
+        <<jump Kiss>>
+    -> Wave
+        <<set $waved to true>>
+        You wave.
+    Frog: The frog stares at you.
+<<if not $FrogHate>>
+-> Bye
+    <<set $FrogLove to false>>
+    <<stop>>
+<<else>>
+-> Flee
+<<endif>>
+Frog: The frog jumps away.
+<<shake_camera 2>>
+===
+
+title: Kiss
+---
+<<set $karma to $karma - 1>>
+Frog: Smooch.
+<<jump Start>>
+===
+";
+
+/// Returns the content of the node reached by following the monologs from `key`.
+fn choices_after(dialog: &Dialog, mut key: usize) -> &Vec<Choice> {
+    loop {
+        match dialog.get(&key).unwrap().content() {
+            Content::Monolog { exit_state, .. } => key = *exit_state,
+            Content::Choices(choices) => return choices,
+        }
+    }
+}
+
+#[test]
+fn test_yarn_import() {
+    let (dialog, report) = import_yarn(FROG_YARN);
+    assert!(report.issues().is_empty(), "{:?}", report.issues());
+    assert!(
+        dialog
+            .validate()
+            .iter()
+            .all(|diagnostic| !diagnostic.is_error()),
+        "{:?}",
+        dialog.validate()
+    );
+
+    // A node per speaker
+    let start = dialog.key_of("Start").unwrap();
+    assert_eq!(dialog.entry(), Some(start));
+    let frog = dialog.get(&start).unwrap();
+    assert_eq!(frog.source(), "Frog");
+    assert_eq!(
+        frog.effects(),
+        &vec![Effect::SetVar {
+            name: "mood".to_string(),
+            value: Value::String("grumpy".to_string()),
+        }]
+    );
+    let Content::Monolog {
+        text,
+        exit_state: player,
+    } = frog.content()
+    else {
+        panic!("the node starts with a monolog");
+    };
+    assert_eq!(text, &vec!["KeroKero", "Who goes there?"]);
+    assert_eq!(dialog.get(player).unwrap().source(), "Player");
+
+    let choices = choices_after(&dialog, start);
+    let texts: Vec<&String> = choices.iter().map(Choice::text).collect();
+    assert_eq!(texts, ["Hi", "Bye", "Flee"]);
+    assert_eq!(choices[0].condition(), &None);
+    assert_eq!(
+        choices[1].condition().as_ref().unwrap().expression(),
+        &Some("!has(FrogHate)".parse().unwrap())
+    );
+    assert_eq!(
+        choices[1].effects(),
+        &vec![Effect::ClearEvent("FrogLove".to_string())]
+    );
+    assert!(!dialog.contains(choices[1].exit_state()));
+    assert_eq!(
+        choices[2].condition(),
+        &Some(Condition::new(None, vec!["FrogHate".to_string()]))
+    );
+
+    // After the options, and their statements
+    let away = dialog.get(choices[2].exit_state()).unwrap();
+    assert_eq!(
+        away.content(),
+        &Content::Monolog {
+            text: vec!["The frog jumps away.".to_string()],
+            exit_state: dialog.len() + 1,
+        }
+    );
+    assert_eq!(
+        away.effects(),
+        &vec![Effect::Custom("shake_camera 2".to_string())]
+    );
+
+    // The nested options, going on with the line after them
+    let nested = choices_after(&dialog, *choices[0].exit_state());
+    let kiss = dialog.key_of("Kiss").unwrap();
+    assert_eq!(nested[0].exit_state(), &kiss);
+    assert_eq!(nested[0].effects(), &vec![Effect::AddKarma(2)]);
+    let condition = nested[0].condition().as_ref().unwrap();
+    assert_eq!(condition.events(), &vec!["FrogLove".to_string()]);
+    assert_eq!(condition.expression(), &Some("karma >= 2".parse().unwrap()));
+    assert_eq!(
+        nested[1].effects(),
+        &vec![Effect::SetEvent("waved".to_string())]
+    );
+    let wave = dialog.get(nested[1].exit_state()).unwrap();
+    assert_eq!(wave.source(), "");
+    let Content::Monolog {
+        exit_state: stare, ..
+    } = wave.content()
+    else {
+        panic!("`You wave.` is a monolog");
+    };
+    assert_eq!(
+        dialog.get(stare).unwrap().content(),
+        &Content::Monolog {
+            text: vec!["The frog stares at you.".to_string()],
+            exit_state: *choices[2].exit_state(),
+        }
+    );
+
+    let kiss = dialog.get(&kiss).unwrap();
+    assert_eq!(kiss.effects(), &vec![Effect::AddKarma(-1)]);
+    assert_eq!(
+        kiss.content(),
+        &Content::Monolog {
+            text: vec!["Smooch.".to_string()],
+            exit_state: start,
+        }
+    );
+}
+
+#[test]
+fn test_yarn_import_reports() {
+    let (dialog, report) = import_yarn(
+        "title: Start
+tags: intro
+---
+Frog: Hello {$name} #line:a1
+Frog: Hi <<if $x>>
+<<declare $fear = 0>>
+<<set $fear to $fear * 2>>
+<<if $karma > 2>>
+Frog: Run!
+<<endif>>
+<<endif>>
+-> Flee <<if visited(\"Pond\")>>
+-> Stay <<once>>
+<<jump {$next}>>
+Unreachable
+===
+---
+Nobody
+===
+title: Loop
+---
+<<jump Loop>>
+===
+title: Start
+---
+Again
+===
+title: Empty
+---
+===
+<<if $x>>
+",
+    );
+
+    let issues: Vec<String> = report.issues().iter().map(ToString::to_string).collect();
+    assert_eq!(
+        issues,
+        [
+            "line 2: tags `intro` left out",
+            "line 4: tag `line:a1` left out",
+            "line 4: inline expression kept as written: `Hello {$name}`",
+            "line 5: `<<if $x>>` of a line left out",
+            "line 6: `<<declare $fear = 0>>` left out",
+            "line 7: `<<set $fear to $fear * 2>>` left out",
+            "line 8: `<<if>>` around lines left out, they always run",
+            "line 11: `<<endif>>` without `<<if>>`, left out",
+            "line 12: condition `visited(\"Pond\")` left out",
+            "line 13: `<<once>>` of an option left out",
+            "line 14: `<<jump {$next}>>` left out, the dialog ends there",
+            "line 15: unreachable after a jump, left out",
+            "line 17: node without title left out",
+            "line 20: `Loop` jumps in a loop, the dialog ends there",
+            "line 24: node `Start` defined again, left out",
+            "line 28: `Empty` is empty, the dialog ends there",
+            "line 31: `<<if $x>>` out of a node, left out",
+        ]
+    );
+
+    let entry = dialog.entry_node().unwrap();
+    assert_eq!(
+        entry.content(),
+        &Content::Monolog {
+            text: vec![
+                "Hello {$name}".to_string(),
+                "Hi".to_string(),
+                "Run!".to_string()
+            ],
+            exit_state: dialog.entry().unwrap() + 1,
+        }
+    );
+    let choices = choices_after(&dialog, dialog.entry().unwrap());
+    assert_eq!(choices.len(), 2);
+    assert_eq!(choices[0].condition(), &None);
+    assert!(!dialog.contains(choices[1].exit_state()));
+    assert_eq!(dialog.key_of("Loop"), None);
+
+    // The opposite of the lowest integer overflows
+    let (_, report) =
+        import_yarn("title: Start\n---\n<<set $karma to $karma - -2147483648>>\nHello\n===\n");
+    assert_eq!(
+        report.issues()[0].to_string(),
+        "line 3: `<<set $karma to $karma - -2147483648>>` left out"
+    );
+}
+
+#[cfg(feature = "yaml")]
+#[test]
+fn test_yarn_export() {
+    let dialog: Dialog = Dialog::from_yaml_str(include_str!("assets/pond.dialog.yml")).unwrap();
+    let yarn = dialog.to_yarn_string();
+    assert_eq!(
+        yarn,
+        "title: Start
+---
+<<set $fear to 0.5>>
+Frog: KeroKero
+<<jump greet_player>>
+===
+
+title: greet_player
+---
+<<set $FrogTalk to true>>
+-> Hi <<if $FrogLove and $karma >= 2>>
+    <<jump Start>>
+-> Bye <<if $FrogHate and $karma >= 0 and $karma <= 10 and $trust >= 2 and $trust <= 10>>
+    <<set $karma to $karma - 1>>
+    <<wave>>
+===
+"
+    );
+
+    // The conditions are written otherwise, but hold alike
+    let (imported, report) = import_yarn(&yarn);
+    assert!(report.issues().is_empty(), "{:?}", report.issues());
+    assert_eq!(imported.len(), dialog.len());
+    let (entry, imported_entry) = (dialog.entry_node().unwrap(), imported.entry_node().unwrap());
+    assert_eq!(imported_entry.source(), dialog.speaker(entry));
+    let Content::Monolog { text, exit_state } = entry.content() else {
+        panic!("the frog talks first");
+    };
+    assert_eq!(
+        imported_entry.content(),
+        &Content::Monolog {
+            text: text.clone(),
+            exit_state: imported.key_of(dialog.label(exit_state).unwrap()).unwrap(),
+        }
+    );
+    assert_eq!(imported_entry.effects(), entry.effects());
+    let greet = dialog.key_of("greet_player").unwrap();
+    let Content::Choices(choices) = dialog.get(&greet).unwrap().content() else {
+        panic!("the player chooses");
+    };
+    let imported_greet = imported
+        .get(&imported.key_of("greet_player").unwrap())
+        .unwrap();
+    assert_eq!(
+        imported_greet.trigger_event(),
+        &vec!["FrogTalk".to_string()]
+    );
+    let Content::Choices(imported_choices) = imported_greet.content() else {
+        panic!("the player still chooses");
+    };
+    for (karma, events, trust) in [
+        (3, vec!["FrogLove"], 0),
+        (1, vec!["FrogLove"], 0),
+        (5, vec!["FrogHate"], 3),
+        (5, vec!["FrogHate"], 1),
+        (12, vec!["FrogHate", "FrogLove"], 3),
+    ] {
+        let context =
+            ConditionContext::new(Some(karma), events.into_iter().map(String::from).collect())
+                .with_variables(Variables::from_iter([("trust", trust)]));
+        for (choice, imported) in choices.iter().zip(imported_choices) {
+            assert_eq!(choice.is_verified(&context), imported.is_verified(&context));
+            assert_eq!(choice.effects(), imported.effects());
+        }
+    }
+}